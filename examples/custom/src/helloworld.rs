@@ -6,7 +6,7 @@
     clippy::derivable_impls,
     clippy::unit_arg
 )]
-/// DO NOT MODIFY. Auto-generated file
+// DO NOT MODIFY. Auto-generated file
 
 ///  The request message containing the user's name.
 #[derive(Clone, PartialEq, Debug)]
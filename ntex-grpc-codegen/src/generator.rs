@@ -102,9 +102,17 @@ fn generate_client(service: &Service, buf: &mut String, priv_buf: &mut String) {
     );
     buf.push_str(&stream);
 
+    let file_descriptors = service
+        .file_descriptors
+        .iter()
+        .map(|buf| byte_string(buf))
+        .collect::<Vec<_>>()
+        .join(", ");
+
     let impl_stream = format!(
         "impl ::ntex_grpc::ServiceDef for {} {{
             const NAME: &'static str = \"{}\";
+            const FILE_DESCRIPTORS: &'static [&'static [u8]] = &[{}];
             type Methods = {};
 
             #[inline]
@@ -153,6 +161,7 @@ fn generate_client(service: &Service, buf: &mut String, priv_buf: &mut String) {
         {}",
         service_ident,
         service_name,
+        file_descriptors,
         service_methods_name,
         service_methods_match,
         client_ident,
@@ -192,19 +201,48 @@ fn gen_method(method: &Method, service: &Service) -> (String, String) {
         .collect();
     let comments = comments.join("");
 
-    (
-        format!(
-            "#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-        pub struct {};
+    let mut consts = String::new();
+    if method.client_streaming {
+        consts.push_str("\n            const CLIENT_STREAMING: bool = true;");
+    }
+    if method.server_streaming {
+        consts.push_str("\n            const SERVER_STREAMING: bool = true;");
+    }
 
-        impl ::ntex_grpc::MethodDef for {} {{
-            const NAME: &'static str = \"{}\";
-            const PATH: ::ntex_grpc::ByteString = ::ntex_grpc::ByteString::from_static(\"{}\");
-            type Input = {};
-            type Output = {};
+    let client_method = if method.client_streaming && method.server_streaming {
+        format!(
+            "impl<T: ::ntex_grpc::client::StreamingTransport<{}>> {}<T> {{
+            {}
+            pub fn {}(&self) -> ::ntex_grpc::client::BidiStreamingRequest<'_, T, {}> {{
+                ::ntex_grpc::client::BidiStreamingRequest::new(&self.0)
+            }}
         }}",
-            def_ident, def_ident, proto_name, path, input_type, output_type,
-        ),
+            def_ident, service_ident, comments, method_ident, def_ident
+        )
+    } else if method.server_streaming {
+        format!(
+            "impl<T: ::ntex_grpc::client::StreamingTransport<{}>> {}<T> {{
+            {}
+            pub fn {}<'a>(&'a self, req: &'a {}) -> ::ntex_grpc::client::ServerStreamingRequest<'a, T, {}> {{
+                ::ntex_grpc::client::ServerStreamingRequest::new(&self.0, req)
+            }}
+        }}",
+            def_ident, service_ident, comments, method_ident, req_input_type, def_ident
+        )
+    } else if method.client_streaming {
+        format!(
+            "impl<T: ::ntex_grpc::client::StreamingTransport<{}>> {}<T> {{
+            {}
+            pub fn {}<'a, S>(&'a self, req: S) -> ::ntex_grpc::client::ClientStreamingRequest<'a, T, {}>
+            where
+                S: ::ntex_grpc::Stream<Item = {}> + 'a,
+            {{
+                ::ntex_grpc::client::ClientStreamingRequest::new(&self.0, req)
+            }}
+        }}",
+            def_ident, service_ident, comments, method_ident, def_ident, req_input_type
+        )
+    } else {
         format!(
             "impl<T: ::ntex_grpc::client::Transport<{}>> {}<T> {{
             {}
@@ -213,6 +251,32 @@ fn gen_method(method: &Method, service: &Service) -> (String, String) {
             }}
         }}",
             def_ident, service_ident, comments, method_ident, req_input_type, def_ident
+        )
+    };
+
+    (
+        format!(
+            "#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+        pub struct {};
+
+        impl ::ntex_grpc::MethodDef for {} {{
+            const NAME: &'static str = \"{}\";
+            const PATH: ::ntex_grpc::ByteString = ::ntex_grpc::ByteString::from_static(\"{}\");{}
+            type Input = {};
+            type Output = {};
+        }}",
+            def_ident, def_ident, proto_name, path, consts, input_type, output_type,
         ),
+        client_method,
     )
 }
+
+/// Byte string literal of the buffer
+fn byte_string(buf: &[u8]) -> String {
+    let mut s = String::from("b\"");
+    for b in buf {
+        s.extend(std::ascii::escape_default(*b).map(char::from));
+    }
+    s.push('"');
+    s
+}
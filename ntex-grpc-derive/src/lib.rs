@@ -32,26 +32,29 @@ fn server_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
     let srvmod = srv.service_mod;
     let modname = quote::format_ident!("_priv_{}", srv.name);
     let methods_prefix = quote::format_ident!("{}Methods", srvname);
-    let mut methods_path = srvmod;
+    let mut methods_path = srvmod.clone();
     methods_path.segments.push(methods_prefix.into());
 
     let mut methods = Vec::new();
-    for (m_name, fn_name, span) in srv.methods {
+    for m in srv.methods {
+        let (m_name, fn_name, span) = (m.name, m.fn_name, m.span);
+
+        // method kind is defined by method definition consts
+        let mut def = srvmod.clone();
+        def.segments
+            .push(quote::format_ident!("{}{}Method", srvname, m_name).into());
+
         methods.push(quote::quote_spanned! {span=>
             Some(#methods_path::#m_name(method)) => {
-                let req = ::ntex_grpc::server::Request {
-                    message: method.decode(&mut req.payload)?,
-                    name: req.name,
-                    headers: req.headers
-                };
+                let req = <#def as ::ntex_grpc::server::MethodRequest<
+                    { <#def as ::ntex_grpc::MethodDef>::CLIENT_STREAMING }
+                >>::request(&method, req).await?;
 
                 let result = #ty::#fn_name(self, ::ntex_grpc::server::FromRequest::from(req)).await;
 
-                let response = ::ntex_grpc::server::Response::from(result);
-                let mut buf = ::ntex_grpc::BytesMut::new();
-                method.encode(response.message, &mut buf);
-
-                Ok(::ntex_grpc::server::ServerResponse::with_headers(buf.freeze(), response.headers))
+                <#def as ::ntex_grpc::server::MethodResponse<
+                    { <#def as ::ntex_grpc::MethodDef>::SERVER_STREAMING }
+                >>::response(&method, result)
             }
         });
     }
@@ -64,8 +67,14 @@ fn server_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
                 type Response = ::ntex_grpc::server::ServerResponse;
                 type Error = ::ntex_grpc::server::ServerError;
 
-                async fn call(&self, mut req: ::ntex_grpc::server::ServerRequest, _: ::ntex_grpc::ServiceCtx<'_, Self>) -> Result<Self::Response, Self::Error> {
-                    use ::ntex_grpc::{ServiceDef, MethodDef};
+                async fn call(&self, req: ::ntex_grpc::server::ServerRequest, _: ::ntex_grpc::ServiceCtx<'_, Self>) -> Result<Self::Response, Self::Error> {
+                    use ::ntex_grpc::ServiceDef;
+
+                    if req.service != <#srvpath as ServiceDef>::NAME {
+                        return Err(::ntex_grpc::server::ServerError::NotFound(
+                            format!("/{}/{}", req.service, req.name).into()
+                        ));
+                    }
 
                     match #srvpath::method_by_name(&req.name) {
                         #(#methods)*
@@ -74,6 +83,10 @@ fn server_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
                     }
                 }
             }
+
+            impl ::ntex_grpc::server::NamedService for #ty {
+                const NAME: &'static str = <#srvpath as ::ntex_grpc::ServiceDef>::NAME;
+            }
         }
     };
 
@@ -91,7 +104,14 @@ struct GrpcService {
     service: syn::Path,
     service_mod: syn::Path,
     service_name: syn::Ident,
-    methods: Vec<(syn::Ident, syn::Ident, proc_macro2::Span)>,
+    methods: Vec<Method>,
+}
+
+#[derive(Debug)]
+struct Method {
+    name: syn::Ident,
+    fn_name: syn::Ident,
+    span: proc_macro2::Span,
 }
 
 impl Parse for GrpcService {
@@ -140,8 +160,11 @@ impl Fold for GrpcService {
                 };
 
                 let _ = m.attrs.remove(idx);
-                self.methods
-                    .push((m_name, m.sig.ident.clone(), m.sig.fn_token.span));
+                self.methods.push(Method {
+                    name: m_name,
+                    fn_name: m.sig.ident.clone(),
+                    span: m.sig.fn_token.span,
+                });
                 break;
             }
         }
@@ -0,0 +1,272 @@
+use std::time::{Duration, Instant};
+use std::{cell::Cell, cell::RefCell, fmt, rc::Rc};
+
+use crate::{service::MethodDef, GrpcStatus, Status};
+
+use super::{
+    ClientError, RequestContext, Response, Sender, Streaming, StreamingTransport, Transport,
+};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// Endpoint selection policy
+pub enum LoadBalancingPolicy {
+    /// Use first available endpoint
+    #[default]
+    PickFirst,
+    /// Use available endpoints in turn
+    RoundRobin,
+}
+
+/// Transport that balances calls over a set of endpoints
+///
+/// Endpoint is taken out of rotation for ejection time if call fails
+/// with connection error or `Unavailable` status. If all endpoints are
+/// out of rotation, endpoint that was ejected first is used.
+///
+/// ```rust,ignore
+/// let transport = Balancer::new(LoadBalancingPolicy::RoundRobin)
+///     .endpoint(Client::new(h2::Client::with_default("10.0.0.1:50051").finish()))
+///     .endpoint(Client::new(h2::Client::with_default("10.0.0.2:50051").finish()));
+/// let client = GreeterClient::new(transport);
+/// ```
+pub struct Balancer<T> {
+    policy: LoadBalancingPolicy,
+    ejection_time: Duration,
+    endpoints: Rc<RefCell<Vec<Rc<Endpoint<T>>>>>,
+    next: Rc<Cell<usize>>,
+}
+
+struct Endpoint<T> {
+    transport: T,
+    ejected: Cell<Option<Instant>>,
+}
+
+impl<T> Balancer<T> {
+    /// Create balancer without endpoints
+    ///
+    /// Default ejection time is 10 seconds.
+    pub fn new(policy: LoadBalancingPolicy) -> Self {
+        Self {
+            policy,
+            ejection_time: Duration::from_secs(10),
+            endpoints: Rc::new(RefCell::new(Vec::new())),
+            next: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// Add endpoint
+    pub fn endpoint(self, transport: T) -> Self {
+        self.endpoints.borrow_mut().push(Rc::new(Endpoint {
+            transport,
+            ejected: Cell::new(None),
+        }));
+        self
+    }
+
+    /// Replace endpoints
+    ///
+    /// Ejection state of previous endpoints is dropped.
+    pub fn set_endpoints(&self, endpoints: Vec<T>) {
+        *self.endpoints.borrow_mut() = endpoints
+            .into_iter()
+            .map(|transport| {
+                Rc::new(Endpoint {
+                    transport,
+                    ejected: Cell::new(None),
+                })
+            })
+            .collect();
+        self.next.set(0);
+    }
+
+    /// Set time failed endpoint is out of rotation
+    pub fn ejection_time(mut self, time: Duration) -> Self {
+        self.ejection_time = time;
+        self
+    }
+
+    #[inline]
+    /// Get load balancing policy
+    pub fn policy(&self) -> LoadBalancingPolicy {
+        self.policy
+    }
+
+    /// Number of endpoints
+    pub fn len(&self) -> usize {
+        self.endpoints.borrow().len()
+    }
+
+    /// Check if balancer has no endpoints
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.borrow().is_empty()
+    }
+
+    /// Select endpoint for next call
+    fn pick(&self) -> Result<Rc<Endpoint<T>>, ClientError> {
+        let endpoints = self.endpoints.borrow();
+        if endpoints.is_empty() {
+            return Err(Status::new(GrpcStatus::Unavailable, "No endpoints available").into());
+        }
+
+        let now = Instant::now();
+        let start = match self.policy {
+            LoadBalancingPolicy::PickFirst => 0,
+            LoadBalancingPolicy::RoundRobin => self.next.get() % endpoints.len(),
+        };
+        let idx = (0..endpoints.len())
+            .map(|i| (start + i) % endpoints.len())
+            .find(|idx| endpoints[*idx].is_available(now))
+            .unwrap_or_else(|| {
+                (0..endpoints.len())
+                    .min_by_key(|idx| endpoints[*idx].ejected.get())
+                    .unwrap()
+            });
+        self.next.set(idx + 1);
+        Ok(endpoints[idx].clone())
+    }
+
+    /// Take endpoint out of rotation if error is caused by endpoint failure
+    fn check(&self, endpoint: &Endpoint<T>, err: &ClientError) {
+        let failed = match err {
+            ClientError::Client(_) | ClientError::Operation(_) => true,
+            ClientError::GrpcStatus(st, _) => st.code == GrpcStatus::Unavailable,
+            _ => false,
+        };
+        if failed {
+            log::debug!(
+                "Endpoint is out of rotation for {:?}: {}",
+                self.ejection_time,
+                err
+            );
+            endpoint
+                .ejected
+                .set(Some(Instant::now() + self.ejection_time));
+        }
+    }
+}
+
+impl<T> Endpoint<T> {
+    fn is_available(&self, now: Instant) -> bool {
+        match self.ejected.get() {
+            Some(until) if until > now => false,
+            Some(_) => {
+                self.ejected.set(None);
+                true
+            }
+            None => true,
+        }
+    }
+}
+
+impl<T> Clone for Balancer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            policy: self.policy,
+            ejection_time: self.ejection_time,
+            endpoints: self.endpoints.clone(),
+            next: self.next.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Balancer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Balancer")
+            .field("policy", &self.policy)
+            .field("ejection_time", &self.ejection_time)
+            .field("endpoints", &self.len())
+            .finish()
+    }
+}
+
+impl<T, M> Transport<M> for Balancer<T>
+where
+    T: Transport<M, Error = ClientError>,
+    M: MethodDef,
+{
+    type Error = ClientError;
+
+    async fn request(
+        &self,
+        val: &M::Input,
+        ctx: RequestContext,
+    ) -> Result<Response<M>, Self::Error> {
+        let endpoint = self.pick()?;
+        let res = endpoint.transport.request(val, ctx).await;
+        if let Err(ref err) = res {
+            self.check(&endpoint, err);
+        }
+        res
+    }
+}
+
+impl<T, M> StreamingTransport<M> for Balancer<T>
+where
+    T: StreamingTransport<M>,
+    M: MethodDef,
+{
+    async fn streaming(
+        &self,
+        ctx: RequestContext,
+    ) -> Result<(Sender<M>, Streaming<M>), ClientError> {
+        let endpoint = self.pick()?;
+        let res = endpoint.transport.streaming(ctx).await;
+        if let Err(ref err) = res {
+            self.check(&endpoint, err);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picks(balancer: &Balancer<usize>, num: usize) -> Vec<usize> {
+        (0..num)
+            .map(|_| balancer.pick().unwrap().transport)
+            .collect()
+    }
+
+    #[test]
+    fn pick() {
+        let balancer = Balancer::new(LoadBalancingPolicy::RoundRobin);
+        assert!(balancer.is_empty());
+        assert!(balancer.pick().is_err());
+
+        let balancer = balancer.endpoint(0).endpoint(1).endpoint(2);
+        assert_eq!(picks(&balancer, 4), vec![0, 1, 2, 0]);
+
+        let err = ClientError::from(Status::new(GrpcStatus::Unavailable, "down"));
+        balancer.check(&balancer.endpoints.borrow()[2], &err);
+        assert_eq!(picks(&balancer, 4), vec![1, 0, 1, 0]);
+
+        let err = ClientError::from(Status::new(GrpcStatus::NotFound, "missing"));
+        balancer.check(&balancer.endpoints.borrow()[1], &err);
+        assert_eq!(picks(&balancer, 2), vec![1, 0]);
+
+        let balancer = Balancer::new(LoadBalancingPolicy::PickFirst)
+            .endpoint(0)
+            .endpoint(1);
+        assert_eq!(picks(&balancer, 2), vec![0, 0]);
+
+        let err = ClientError::from(Status::new(GrpcStatus::Unavailable, "down"));
+        balancer.check(&balancer.endpoints.borrow()[0], &err);
+        assert_eq!(picks(&balancer, 2), vec![1, 1]);
+
+        // all endpoints are ejected
+        balancer.check(&balancer.endpoints.borrow()[1], &err);
+        assert_eq!(picks(&balancer, 2), vec![0, 0]);
+    }
+
+    #[test]
+    fn ejection_time() {
+        let balancer = Balancer::new(LoadBalancingPolicy::PickFirst)
+            .ejection_time(Duration::ZERO)
+            .endpoint(0)
+            .endpoint(1);
+        let err = ClientError::from(Status::new(GrpcStatus::Unavailable, "down"));
+        balancer.check(&balancer.endpoints.borrow()[0], &err);
+        assert_eq!(picks(&balancer, 2), vec![0, 0]);
+    }
+}
@@ -0,0 +1,293 @@
+use std::{cell::RefCell, fmt, net::SocketAddr, rc::Rc, rc::Weak, time::Duration};
+
+use ntex_h2::client as h2;
+use ntex_net::connect::{Connect, Connector};
+use ntex_util::time::sleep;
+
+use crate::service::MethodDef;
+
+use super::resolver::{DnsResolver, Resolver, Target};
+use super::{Balancer, Client, ClientError, LoadBalancingPolicy};
+use super::{RequestContext, Response, Sender, Streaming, StreamingTransport, Transport};
+
+type ConfigureH2 = Box<dyn Fn(h2::ClientBuilder) -> h2::ClientBuilder>;
+type ConfigureClient = Box<dyn Fn(Client) -> Client>;
+
+/// Client transport connected to resolved target
+///
+/// Target is resolved to a list of endpoints, calls are balanced over
+/// endpoints with configured load balancing policy. Host names are
+/// periodically re-resolved, connections to endpoints that are still
+/// resolved are kept.
+///
+/// ```rust,ignore
+/// let channel = Channel::build("dns:///greeter.local:50051")?
+///     .policy(LoadBalancingPolicy::RoundRobin)
+///     .finish()
+///     .await?;
+/// let client = GreeterClient::new(channel);
+/// ```
+pub struct Channel {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    authority: String,
+    balancer: Balancer<Client>,
+    endpoints: RefCell<Vec<(SocketAddr, Client)>>,
+    configure_h2: ConfigureH2,
+    configure_client: ConfigureClient,
+}
+
+/// Channel builder
+pub struct ChannelBuilder<R = DnsResolver> {
+    target: Target,
+    resolver: R,
+    policy: LoadBalancingPolicy,
+    resolve_interval: Duration,
+    configure_h2: ConfigureH2,
+    configure_client: ConfigureClient,
+}
+
+impl Channel {
+    /// Configure channel for target
+    ///
+    /// See [`Target`] for supported target formats.
+    pub fn build(target: &str) -> Result<ChannelBuilder, ClientError> {
+        Ok(ChannelBuilder {
+            target: target.parse()?,
+            resolver: DnsResolver,
+            policy: LoadBalancingPolicy::default(),
+            resolve_interval: Duration::from_secs(30),
+            configure_h2: Box::new(|builder| builder),
+            configure_client: Box::new(|client| client),
+        })
+    }
+
+    /// Create channel for target with default settings
+    pub async fn connect(target: &str) -> Result<Self, ClientError> {
+        Self::build(target)?.finish().await
+    }
+
+    #[inline]
+    /// Get reference to endpoints balancer
+    pub fn get_ref(&self) -> &Balancer<Client> {
+        &self.inner.balancer
+    }
+}
+
+impl<R> ChannelBuilder<R> {
+    /// Set host name resolver
+    ///
+    /// By default system dns resolver is used.
+    pub fn resolver<U: Resolver>(self, resolver: U) -> ChannelBuilder<U> {
+        ChannelBuilder {
+            resolver,
+            target: self.target,
+            policy: self.policy,
+            resolve_interval: self.resolve_interval,
+            configure_h2: self.configure_h2,
+            configure_client: self.configure_client,
+        }
+    }
+
+    /// Set load balancing policy
+    ///
+    /// By default `PickFirst` policy is used.
+    pub fn policy(mut self, policy: LoadBalancingPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set interval of host name re-resolution
+    ///
+    /// By default host name is resolved every 30 seconds.
+    pub fn resolve_interval(mut self, interval: Duration) -> Self {
+        self.resolve_interval = interval;
+        self
+    }
+
+    /// Configure h2 client of every endpoint
+    pub fn configure_h2<F>(mut self, f: F) -> Self
+    where
+        F: Fn(h2::ClientBuilder) -> h2::ClientBuilder + 'static,
+    {
+        self.configure_h2 = Box::new(f);
+        self
+    }
+
+    /// Configure grpc client of every endpoint
+    pub fn configure<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Client) -> Client + 'static,
+    {
+        self.configure_client = Box::new(f);
+        self
+    }
+}
+
+impl<R: Resolver + 'static> ChannelBuilder<R> {
+    /// Resolve target and create channel
+    pub async fn finish(self) -> Result<Channel, ClientError> {
+        let inner = Rc::new(Inner {
+            authority: self.target.authority(),
+            balancer: Balancer::new(self.policy),
+            endpoints: RefCell::new(Vec::new()),
+            configure_h2: self.configure_h2,
+            configure_client: self.configure_client,
+        });
+
+        match self.target {
+            Target::Dns { host, port } => {
+                let addrs = resolve(&self.resolver, &host, port).await?;
+                inner.update(addrs);
+
+                let interval = self.resolve_interval;
+                let resolver = self.resolver;
+                let inner = Rc::downgrade(&inner);
+                ntex_net::spawn(re_resolve(inner, resolver, host, port, interval));
+            }
+            Target::Ip(addrs) => inner.update(addrs),
+            Target::Unix(path) => {
+                let client = inner.unix_client(path)?;
+                inner.balancer.set_endpoints(vec![client]);
+            }
+        }
+        Ok(Channel { inner })
+    }
+}
+
+impl Inner {
+    /// Replace endpoints, clients of known addresses are reused
+    fn update(&self, mut addrs: Vec<SocketAddr>) {
+        addrs.sort();
+        addrs.dedup();
+
+        let mut endpoints = self.endpoints.borrow_mut();
+        if endpoints.iter().map(|(addr, _)| addr).eq(addrs.iter()) {
+            return;
+        }
+        log::debug!("Endpoints of {} are updated: {:?}", self.authority, addrs);
+
+        let new: Vec<_> = addrs
+            .into_iter()
+            .map(|addr| {
+                let client = endpoints
+                    .iter()
+                    .find(|(a, _)| *a == addr)
+                    .map(|(_, client)| client.clone())
+                    .unwrap_or_else(|| self.tcp_client(addr));
+                (addr, client)
+            })
+            .collect();
+        self.balancer
+            .set_endpoints(new.iter().map(|(_, client)| client.clone()).collect());
+        *endpoints = new;
+    }
+
+    fn tcp_client(&self, addr: SocketAddr) -> Client {
+        let client = Client::build(
+            Connect::with(self.authority.clone(), addr),
+            Connector::default(),
+            &self.configure_h2,
+        );
+        (self.configure_client)(client)
+    }
+
+    #[cfg(unix)]
+    fn unix_client(&self, path: std::path::PathBuf) -> Result<Client, ClientError> {
+        use ntex_net::connect::ConnectError;
+
+        let connector = ntex_service::fn_service(move |_: Connect<String>| {
+            let path = path.clone();
+            async move { ntex_net::unix_connect(path).await.map_err(ConnectError::Io) }
+        });
+        let client = Client::build(self.authority.clone(), connector, &self.configure_h2);
+        Ok((self.configure_client)(client))
+    }
+
+    #[cfg(not(unix))]
+    fn unix_client(&self, _: std::path::PathBuf) -> Result<Client, ClientError> {
+        Err(ClientError::Target(
+            "unix domain sockets are not supported".to_string(),
+        ))
+    }
+}
+
+async fn resolve<R: Resolver>(
+    resolver: &R,
+    host: &str,
+    port: u16,
+) -> Result<Vec<SocketAddr>, ClientError> {
+    match resolver.resolve(host, port).await {
+        Ok(addrs) if !addrs.is_empty() => Ok(addrs),
+        Ok(_) => Err(ClientError::Client(
+            ntex_net::connect::ConnectError::NoRecords.into(),
+        )),
+        Err(err) => Err(ClientError::Client(
+            ntex_net::connect::ConnectError::Resolver(err).into(),
+        )),
+    }
+}
+
+/// Periodically re-resolve host name until channel is dropped
+async fn re_resolve<R: Resolver>(
+    inner: Weak<Inner>,
+    resolver: R,
+    host: String,
+    port: u16,
+    interval: Duration,
+) {
+    loop {
+        sleep(interval).await;
+        if inner.strong_count() == 0 {
+            break;
+        }
+        let res = resolve(&resolver, &host, port).await;
+        match inner.upgrade() {
+            Some(inner) => match res {
+                Ok(addrs) => inner.update(addrs),
+                Err(err) => log::warn!("Cannot resolve {}:{}: {}", host, port, err),
+            },
+            None => break,
+        }
+    }
+}
+
+impl Clone for Channel {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Channel")
+            .field("authority", &self.inner.authority)
+            .field("balancer", &self.inner.balancer)
+            .finish()
+    }
+}
+
+impl<M: MethodDef> Transport<M> for Channel {
+    type Error = ClientError;
+
+    async fn request(
+        &self,
+        val: &M::Input,
+        ctx: RequestContext,
+    ) -> Result<Response<M>, Self::Error> {
+        Transport::<M>::request(&self.inner.balancer, val, ctx).await
+    }
+}
+
+impl<M: MethodDef> StreamingTransport<M> for Channel {
+    async fn streaming(
+        &self,
+        ctx: RequestContext,
+    ) -> Result<(Sender<M>, Streaming<M>), ClientError> {
+        StreamingTransport::<M>::streaming(&self.inner.balancer, ctx).await
+    }
+}
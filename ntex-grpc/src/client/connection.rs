@@ -0,0 +1,514 @@
+use std::time::{Duration, Instant};
+use std::{cell::Cell, cell::RefCell, fmt, rc::Rc};
+
+use ntex_io::IoBoxed;
+use ntex_net::connect::{Address, Connect, ConnectError};
+use ntex_service::{Service, ServiceCtx};
+use ntex_util::future::{select, Either};
+use ntex_util::{channel::condition::Condition, time::sleep};
+
+use crate::{GrpcStatus, Status};
+
+use super::ClientError;
+
+/// Connection backoff parameters
+///
+/// Follows grpc connection backoff protocol. After failed connection
+/// attempt next attempt is delayed by current backoff with random jitter,
+/// backoff grows with multiplier up to max backoff. Backoff is reset
+/// after successful connection.
+#[derive(Clone, Debug)]
+pub struct ConnectBackoff {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+}
+
+impl Default for ConnectBackoff {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(120),
+            multiplier: 1.6,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ConnectBackoff {
+    /// Create connection backoff
+    ///
+    /// By default backoff starts at 1 second, grows with 1.6 multiplier up
+    /// to 120 seconds, jitter is 0.2.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set backoff after first failed attempt
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set max backoff
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Set multiplier that backoff is increased by after each failed attempt
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Set random jitter of backoff
+    ///
+    /// Value is limited by 1.0, delay is random value between
+    /// `backoff * (1 - jitter)` and `backoff * (1 + jitter)`.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Client connectivity state
+pub enum ConnectivityState {
+    /// Client is not connected, next call starts connection
+    Idle,
+    /// Client is establishing connection
+    Connecting,
+    /// Connection is established
+    Ready,
+    /// Connection failed, client moves to `Idle` state after backoff
+    TransientFailure,
+    /// Client is closed
+    Shutdown,
+}
+
+/// Connection attempts state shared by client clones
+pub(crate) struct Connection {
+    backoff: RefCell<ConnectBackoff>,
+    state: RefCell<State>,
+    changed: Condition,
+    opened: Cell<usize>,
+}
+
+struct State {
+    connectivity: ConnectivityState,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+    error: Option<String>,
+}
+
+impl Connection {
+    pub(super) fn new(backoff: ConnectBackoff) -> Self {
+        Self {
+            state: RefCell::new(State {
+                connectivity: ConnectivityState::Idle,
+                backoff: backoff.initial_backoff,
+                retry_at: None,
+                error: None,
+            }),
+            backoff: RefCell::new(backoff),
+            changed: Condition::new(),
+            opened: Cell::new(0),
+        }
+    }
+
+    /// Replace backoff parameters, backoff is restarted
+    pub(super) fn set_backoff(&self, backoff: ConnectBackoff) {
+        let mut state = self.state.borrow_mut();
+        state.backoff = backoff.initial_backoff;
+        state.retry_at = None;
+        *self.backoff.borrow_mut() = backoff;
+    }
+
+    /// Current connectivity state
+    pub(super) fn state(&self) -> ConnectivityState {
+        self.state.borrow().connectivity
+    }
+
+    /// Wait until connectivity state differs from `current`
+    pub(super) async fn watch(&self, current: ConnectivityState) -> ConnectivityState {
+        let waiter = self.changed.wait();
+        loop {
+            let state = self.state();
+            if state != current {
+                return state;
+            }
+            waiter.ready().await;
+        }
+    }
+
+    fn set_state(&self, connectivity: ConnectivityState) {
+        let mut state = self.state.borrow_mut();
+        if state.connectivity != connectivity && state.connectivity != ConnectivityState::Shutdown
+        {
+            log::trace!(
+                "Connectivity state changed {:?} -> {:?}",
+                state.connectivity,
+                connectivity
+            );
+            state.connectivity = connectivity;
+            drop(state);
+            self.changed.notify();
+        }
+    }
+
+    /// Wait until call can be started
+    ///
+    /// Call on idle connection starts new connection attempt, concurrent
+    /// calls wait for result of started attempt. During backoff call fails
+    /// with `Unavailable` status, unless it waits for ready.
+    pub(super) async fn ready(
+        &self,
+        wait_for_ready: bool,
+        deadline: Option<Instant>,
+    ) -> Result<Attempt<'_>, ClientError> {
+        let waiter = self.changed.wait();
+        loop {
+            match self.state() {
+                ConnectivityState::Shutdown => {
+                    return Err(Status::new(GrpcStatus::Unavailable, "Client is closed").into())
+                }
+                ConnectivityState::Ready => {
+                    return Ok(Attempt {
+                        conn: self,
+                        started: false,
+                    })
+                }
+                ConnectivityState::Idle => {
+                    self.set_state(ConnectivityState::Connecting);
+                    return Ok(Attempt {
+                        conn: self,
+                        started: true,
+                    });
+                }
+                ConnectivityState::TransientFailure if !wait_for_ready => {
+                    return Err(self.unavailable())
+                }
+                ConnectivityState::Connecting | ConnectivityState::TransientFailure => (),
+            }
+
+            if let Some(deadline) = deadline {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                if let Either::Left(_) = select(sleep(timeout), waiter.ready()).await {
+                    return Err(ClientError::deadline_exceeded());
+                }
+            } else {
+                waiter.ready().await;
+            }
+        }
+    }
+
+    /// Connection is established, reset backoff
+    pub(super) fn connected(&self) {
+        let mut state = self.state.borrow_mut();
+        state.backoff = self.backoff.borrow().initial_backoff;
+        state.retry_at = None;
+        state.error = None;
+        drop(state);
+        self.set_state(ConnectivityState::Ready);
+    }
+
+    /// Connection is opened, state follows its lifecycle
+    fn opened(self: &Rc<Self>, io: &IoBoxed) {
+        self.opened.set(self.opened.get() + 1);
+        self.connected();
+
+        let conn = Rc::downgrade(self);
+        let on_disconnect = io.on_disconnect();
+        ntex_net::spawn(async move {
+            on_disconnect.await;
+            if let Some(conn) = conn.upgrade() {
+                conn.opened.set(conn.opened.get() - 1);
+                if conn.opened.get() == 0 {
+                    log::trace!("Connection is closed");
+                    conn.disconnected();
+                }
+            }
+        });
+    }
+
+    /// Established connection is closed
+    pub(super) fn disconnected(&self) {
+        if self.state() == ConnectivityState::Ready {
+            self.set_state(ConnectivityState::Idle);
+        }
+    }
+
+    /// Close client, new calls fail with `Unavailable` status
+    pub(super) fn shutdown(&self) {
+        self.set_state(ConnectivityState::Shutdown);
+    }
+
+    /// Connection attempt failed, delay next attempt
+    ///
+    /// Backoff timer moves connection to `Idle` state, waiting calls
+    /// start next attempt.
+    pub(super) fn failed(self: &Rc<Self>, err: &ClientError) {
+        let now = Instant::now();
+        let mut state = self.state.borrow_mut();
+
+        // concurrent attempt already started backoff
+        if state.retry_at.map(|at| at > now).unwrap_or(false) {
+            return;
+        }
+
+        let backoff = self.backoff.borrow();
+        let jitter = 1.0 + backoff.jitter * (fastrand::f64() * 2.0 - 1.0);
+        let delay = state.backoff.mul_f64(jitter);
+        log::debug!("Connection failed, next attempt in {:?}: {}", delay, err);
+
+        let retry_at = now + delay;
+        state.retry_at = Some(retry_at);
+        state.error = Some(err.to_string());
+        state.backoff = state
+            .backoff
+            .mul_f64(backoff.multiplier)
+            .min(backoff.max_backoff);
+        drop(state);
+        self.set_state(ConnectivityState::TransientFailure);
+
+        let conn = Rc::downgrade(self);
+        ntex_net::spawn(async move {
+            sleep(delay).await;
+            if let Some(conn) = conn.upgrade() {
+                conn.backoff_elapsed(retry_at);
+            }
+        });
+    }
+
+    fn backoff_elapsed(&self, retry_at: Instant) {
+        let mut state = self.state.borrow_mut();
+        if state.retry_at == Some(retry_at) {
+            state.retry_at = None;
+            let failed = state.connectivity == ConnectivityState::TransientFailure;
+            drop(state);
+            if failed {
+                self.set_state(ConnectivityState::Idle);
+            }
+        }
+    }
+
+    fn unavailable(&self) -> ClientError {
+        let state = self.state.borrow();
+        let msg = match state.error {
+            Some(ref err) => format!("Connection is not available: {}", err),
+            None => "Connection is not available".to_string(),
+        };
+        Status::new(GrpcStatus::Unavailable, msg).into()
+    }
+}
+
+/// Connection attempt started by call
+///
+/// Attempt that is dropped before result is reported moves connection
+/// back to `Idle` state, so waiting calls could start next attempt.
+pub(super) struct Attempt<'a> {
+    conn: &'a Connection,
+    started: bool,
+}
+
+impl Drop for Attempt<'_> {
+    fn drop(&mut self) {
+        if self.started && self.conn.state() == ConnectivityState::Connecting {
+            self.conn.set_state(ConnectivityState::Idle);
+        }
+    }
+}
+
+impl fmt::Debug for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("Connection")
+            .field("state", &state.connectivity)
+            .field("backoff", &*self.backoff.borrow())
+            .field("retry_at", &state.retry_at)
+            .finish()
+    }
+}
+
+/// Connector that reports opened connections to client connection state
+pub(super) struct Monitor<T> {
+    connector: T,
+    conn: Rc<Connection>,
+}
+
+impl<T> Monitor<T> {
+    pub(super) fn new(connector: T, conn: Rc<Connection>) -> Self {
+        Self { connector, conn }
+    }
+}
+
+impl<A, T> Service<Connect<A>> for Monitor<T>
+where
+    A: Address,
+    T: Service<Connect<A>, Error = ConnectError>,
+    IoBoxed: From<T::Response>,
+{
+    type Response = IoBoxed;
+    type Error = ConnectError;
+
+    ntex_service::forward_poll_ready!(connector);
+    ntex_service::forward_poll_shutdown!(connector);
+
+    async fn call(
+        &self,
+        req: Connect<A>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<IoBoxed, ConnectError> {
+        let io = IoBoxed::from(ctx.call(&self.connector, req).await?);
+        self.conn.opened(&io);
+        Ok(io)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ntex_util::future::join;
+
+    #[ntex::test]
+    async fn backoff() {
+        let conn = Rc::new(Connection::new(
+            ConnectBackoff::new()
+                .initial_backoff(Duration::from_millis(100))
+                .max_backoff(Duration::from_millis(300))
+                .multiplier(2.0)
+                .jitter(0.0),
+        ));
+        let err = ClientError::from(Status::new(GrpcStatus::Unavailable, "down"));
+        conn.failed(&err);
+        let retry_at = conn.state.borrow().retry_at.unwrap();
+        assert!(retry_at - Instant::now() <= Duration::from_millis(100));
+        assert_eq!(conn.state.borrow().backoff, Duration::from_millis(200));
+
+        // backoff is started already
+        conn.failed(&err);
+        assert_eq!(conn.state.borrow().retry_at, Some(retry_at));
+
+        conn.state.borrow_mut().retry_at = None;
+        conn.failed(&err);
+        assert_eq!(conn.state.borrow().backoff, Duration::from_millis(300));
+
+        conn.connected();
+        assert_eq!(conn.state.borrow().backoff, Duration::from_millis(100));
+        assert!(conn.state.borrow().retry_at.is_none());
+    }
+
+    #[ntex::test]
+    async fn connectivity_state() {
+        let conn = Rc::new(Connection::new(
+            ConnectBackoff::new()
+                .initial_backoff(Duration::from_millis(50))
+                .jitter(0.0),
+        ));
+        assert_eq!(conn.state(), ConnectivityState::Idle);
+        let attempt = conn.ready(false, None).await.unwrap();
+        assert_eq!(conn.state(), ConnectivityState::Connecting);
+        conn.connected();
+        drop(attempt);
+        assert_eq!(conn.state(), ConnectivityState::Ready);
+        conn.disconnected();
+        assert_eq!(conn.state(), ConnectivityState::Idle);
+
+        let err = ClientError::from(Status::new(GrpcStatus::Unavailable, "down"));
+        conn.failed(&err);
+        assert_eq!(conn.state(), ConnectivityState::TransientFailure);
+        assert!(conn.ready(false, None).await.is_err());
+
+        // backoff timer moves connection to idle state
+        let start = Instant::now();
+        let state = conn.watch(ConnectivityState::TransientFailure).await;
+        assert_eq!(state, ConnectivityState::Idle);
+        assert!(start.elapsed() >= Duration::from_millis(40));
+
+        conn.shutdown();
+        assert_eq!(conn.state(), ConnectivityState::Shutdown);
+        conn.connected();
+        assert_eq!(conn.state(), ConnectivityState::Shutdown);
+        assert!(conn.ready(true, None).await.is_err());
+    }
+
+    #[ntex::test]
+    async fn shared_attempt() {
+        let conn = Rc::new(Connection::new(
+            ConnectBackoff::new()
+                .initial_backoff(Duration::from_millis(50))
+                .jitter(0.0),
+        ));
+        let err = ClientError::from(Status::new(GrpcStatus::Unavailable, "down"));
+        conn.failed(&err);
+
+        // wait for ready call starts attempt after backoff
+        let attempt = conn.ready(true, None).await.unwrap();
+        assert!(attempt.started);
+        assert_eq!(conn.state(), ConnectivityState::Connecting);
+
+        // concurrent call waits for started attempt
+        let (_, res) = join(
+            async {
+                sleep(Duration::from_millis(10)).await;
+                conn.failed(&err);
+            },
+            conn.ready(false, None),
+        )
+        .await;
+        assert!(res.is_err());
+        drop(attempt);
+        assert_eq!(conn.state(), ConnectivityState::TransientFailure);
+
+        let attempt = conn.ready(true, None).await.unwrap();
+        assert!(attempt.started);
+        let (_, res) = join(
+            async {
+                sleep(Duration::from_millis(10)).await;
+                conn.connected();
+            },
+            conn.ready(true, None),
+        )
+        .await;
+        assert!(!res.unwrap().started);
+        drop(attempt);
+        assert_eq!(conn.state(), ConnectivityState::Ready);
+
+        // dropped attempt lets waiting call start next one
+        conn.disconnected();
+        let attempt = conn.ready(true, None).await.unwrap();
+        let (_, res) = join(
+            async {
+                sleep(Duration::from_millis(10)).await;
+                drop(attempt);
+            },
+            conn.ready(true, None),
+        )
+        .await;
+        assert!(res.unwrap().started);
+
+        // waiting call fails after deadline
+        conn.connected();
+        conn.failed(&err);
+        let deadline = Instant::now() + Duration::from_millis(10);
+        let res = conn.ready(true, Some(deadline)).await;
+        assert!(res.is_err());
+        assert_eq!(conn.state(), ConnectivityState::TransientFailure);
+    }
+
+    #[ntex::test]
+    async fn jitter() {
+        let conn = Rc::new(Connection::new(
+            ConnectBackoff::new()
+                .initial_backoff(Duration::from_secs(10))
+                .jitter(0.2),
+        ));
+        let err = ClientError::from(Status::new(GrpcStatus::Unavailable, "down"));
+        let now = Instant::now();
+        conn.failed(&err);
+        let delay = conn.state.borrow().retry_at.unwrap() - now;
+        assert!(delay >= Duration::from_secs(8) && delay <= Duration::from_secs(13));
+    }
+}
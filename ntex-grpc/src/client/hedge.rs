@@ -0,0 +1,252 @@
+use std::task::{Context, Poll};
+use std::{fmt, future::poll_fn, rc::Rc, time::Duration};
+
+use ntex_bytes::ByteString;
+use ntex_util::{future::BoxFuture, time::Sleep, HashMap};
+
+use crate::{consts, service::MethodDef, GrpcStatus};
+
+use super::retry::parse_pushback;
+use super::{
+    ClientError, RequestContext, Response, Sender, Streaming, StreamingTransport, Transport,
+};
+
+/// Hedging policy for unary calls
+///
+/// Call is sent again after hedging delay until one of the calls
+/// succeeds or fails with fatal status code. Failure with non-fatal status
+/// code starts next call immediately.
+#[derive(Clone, Debug)]
+pub struct HedgingPolicy {
+    max_attempts: usize,
+    delay: Duration,
+    non_fatal_codes: Vec<GrpcStatus>,
+}
+
+impl Default for HedgingPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 2,
+            delay: Duration::from_millis(100),
+            non_fatal_codes: vec![GrpcStatus::Unavailable],
+        }
+    }
+}
+
+impl HedgingPolicy {
+    /// Create hedging policy
+    ///
+    /// By default call is sent twice with 100 millis delay,
+    /// only `Unavailable` status is non-fatal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set max number of sent calls, including original one
+    ///
+    /// Value is limited by 5 attempts.
+    pub fn max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.clamp(1, 5);
+        self
+    }
+
+    /// Set delay between hedged calls
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Set status codes that do not stop hedging
+    pub fn non_fatal_codes(mut self, codes: &[GrpcStatus]) -> Self {
+        self.non_fatal_codes = codes.to_vec();
+        self
+    }
+}
+
+/// Transport that hedges unary calls
+///
+/// Hedging is enabled per method, calls of other methods are passed
+/// to the wrapped transport as is. First successful response is used,
+/// other calls are cancelled.
+pub struct Hedge<T> {
+    transport: T,
+    methods: Rc<HashMap<ByteString, HedgingPolicy>>,
+}
+
+impl<T> Hedge<T> {
+    /// Wrap transport, hedging is not enabled for any method
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            methods: Rc::new(HashMap::default()),
+        }
+    }
+
+    /// Enable hedging for method with path
+    ///
+    /// ```rust,ignore
+    /// let transport = Hedge::new(client)
+    ///     .method(GreeterSayHelloMethod::PATH, HedgingPolicy::new());
+    /// ```
+    pub fn method(mut self, path: ByteString, policy: HedgingPolicy) -> Self {
+        Rc::make_mut(&mut self.methods).insert(path, policy);
+        self
+    }
+
+    #[inline]
+    /// Get reference to wrapped transport
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Clone> Clone for Hedge<T> {
+    fn clone(&self) -> Self {
+        Self {
+            transport: self.transport.clone(),
+            methods: self.methods.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Hedge<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hedge")
+            .field("transport", &self.transport)
+            .field("methods", &self.methods)
+            .finish()
+    }
+}
+
+impl<T, M> Transport<M> for Hedge<T>
+where
+    T: Transport<M, Error = ClientError>,
+    M: MethodDef + 'static,
+{
+    type Error = ClientError;
+
+    async fn request(
+        &self,
+        val: &M::Input,
+        ctx: RequestContext,
+    ) -> Result<Response<M>, Self::Error> {
+        if let Some(policy) = self.methods.get(&M::PATH) {
+            let mut hedging = Hedging {
+                policy,
+                val,
+                ctx,
+                transport: &self.transport,
+                calls: Vec::new(),
+                attempts: 0,
+                start_now: true,
+                stopped: false,
+                delay: Sleep::new(policy.delay.into()),
+                last_err: None,
+            };
+            poll_fn(|cx| hedging.poll(cx)).await
+        } else {
+            self.transport.request(val, ctx).await
+        }
+    }
+}
+
+impl<T, M> StreamingTransport<M> for Hedge<T>
+where
+    T: StreamingTransport<M>,
+    M: MethodDef,
+{
+    async fn streaming(
+        &self,
+        ctx: RequestContext,
+    ) -> Result<(Sender<M>, Streaming<M>), ClientError> {
+        self.transport.streaming(ctx).await
+    }
+}
+
+/// State of hedged call, dropping it cancels in-flight calls
+struct Hedging<'a, T, M: MethodDef> {
+    policy: &'a HedgingPolicy,
+    transport: &'a T,
+    val: &'a M::Input,
+    ctx: RequestContext,
+    calls: Vec<BoxFuture<'a, Result<Response<M>, ClientError>>>,
+    attempts: usize,
+    start_now: bool,
+    stopped: bool,
+    delay: Sleep,
+    last_err: Option<ClientError>,
+}
+
+impl<'a, T, M> Hedging<'a, T, M>
+where
+    T: Transport<M, Error = ClientError>,
+    M: MethodDef + 'a,
+{
+    /// Check if failed call does not stop hedging
+    ///
+    /// Connection failures are handled as `Unavailable` status.
+    fn is_non_fatal(&self, err: &ClientError) -> bool {
+        err.code()
+            .map(|code| self.policy.non_fatal_codes.contains(&code))
+            .unwrap_or(false)
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Result<Response<M>, ClientError>> {
+        loop {
+            let mut progress = false;
+
+            // start next call
+            if !self.stopped
+                && self.attempts < self.policy.max_attempts
+                && (self.start_now || self.delay.poll_elapsed(cx).is_ready())
+            {
+                let mut ctx = self.ctx.clone();
+                if self.attempts > 0 {
+                    log::debug!("Send hedged call {}, attempt {}", M::PATH, self.attempts);
+                    ctx.header(consts::GRPC_PREVIOUS_RPC_ATTEMPTS, self.attempts);
+                }
+                self.calls
+                    .push(Box::pin(self.transport.request(self.val, ctx)));
+                self.attempts += 1;
+                self.start_now = false;
+                self.delay.reset(self.policy.delay);
+                progress = true;
+            }
+
+            let mut idx = 0;
+            while idx < self.calls.len() {
+                match self.calls[idx].as_mut().poll(cx) {
+                    Poll::Ready(Ok(res)) => return Poll::Ready(Ok(res)),
+                    Poll::Ready(Err(err)) => {
+                        drop(self.calls.swap_remove(idx));
+                        let pushback = match err {
+                            ClientError::GrpcStatus(_, ref trailers)
+                                if self.is_non_fatal(&err) =>
+                            {
+                                parse_pushback(trailers)
+                            }
+                            _ if self.is_non_fatal(&err) => None,
+                            _ => return Poll::Ready(Err(err)),
+                        };
+                        match pushback {
+                            Some(Some(delay)) => self.delay.reset(delay),
+                            Some(None) => self.stopped = true,
+                            None => self.start_now = true,
+                        }
+                        self.last_err = Some(err);
+                        progress = true;
+                    }
+                    Poll::Pending => idx += 1,
+                }
+            }
+
+            if self.calls.is_empty() && (self.stopped || self.attempts >= self.policy.max_attempts)
+            {
+                return Poll::Ready(Err(self.last_err.take().unwrap()));
+            }
+            if !progress {
+                return Poll::Pending;
+            }
+        }
+    }
+}
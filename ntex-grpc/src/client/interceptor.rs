@@ -0,0 +1,138 @@
+use std::{fmt, rc::Rc};
+
+use crate::service::MethodDef;
+
+use super::{
+    ClientError, RequestContext, Response, Sender, Streaming, StreamingTransport, Transport,
+};
+
+/// Client call interceptor
+///
+/// Interceptor is called around every call of the wrapped transport.
+/// Use [`Intercepted`] transport to apply it.
+pub trait Interceptor {
+    /// Inspect or modify request context before call is started
+    ///
+    /// Returning error fails the call, transport is not called.
+    async fn request<M: MethodDef>(&self, ctx: &mut RequestContext) -> Result<(), ClientError> {
+        let _ = ctx;
+        Ok(())
+    }
+
+    /// Inspect unary call response
+    async fn response<M: MethodDef>(&self, res: &mut Response<M>) {
+        let _ = res;
+    }
+
+    /// Inspect or transform call error
+    ///
+    /// For streaming calls only errors of call start are intercepted.
+    async fn error<M: MethodDef>(&self, err: ClientError) -> ClientError {
+        err
+    }
+}
+
+/// Transport wrapped with interceptor
+///
+/// Interceptors are composable, interceptor applied last is called first.
+///
+/// ```rust,ignore
+/// let transport = Intercepted::new(client, Metrics).interceptor(Auth::new(token));
+/// let client = GreeterClient::new(transport);
+/// ```
+pub struct Intercepted<T, I> {
+    transport: T,
+    interceptor: Rc<I>,
+}
+
+impl<T, I> Intercepted<T, I> {
+    /// Wrap transport with interceptor
+    pub fn new(transport: T, interceptor: I) -> Self {
+        Self {
+            transport,
+            interceptor: Rc::new(interceptor),
+        }
+    }
+
+    /// Wrap with one more interceptor
+    pub fn interceptor<U>(self, interceptor: U) -> Intercepted<Self, U> {
+        Intercepted::new(self, interceptor)
+    }
+
+    #[inline]
+    /// Get reference to wrapped transport
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    #[inline]
+    /// Get reference to interceptor
+    pub fn get_interceptor(&self) -> &I {
+        &self.interceptor
+    }
+}
+
+impl<T: Clone, I> Clone for Intercepted<T, I> {
+    fn clone(&self) -> Self {
+        Self {
+            transport: self.transport.clone(),
+            interceptor: self.interceptor.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, I> fmt::Debug for Intercepted<T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Intercepted")
+            .field("transport", &self.transport)
+            .finish()
+    }
+}
+
+impl<T, I, M> Transport<M> for Intercepted<T, I>
+where
+    T: Transport<M, Error = ClientError>,
+    I: Interceptor,
+    M: MethodDef,
+{
+    type Error = ClientError;
+
+    async fn request(
+        &self,
+        val: &M::Input,
+        mut ctx: RequestContext,
+    ) -> Result<Response<M>, Self::Error> {
+        if let Err(err) = self.interceptor.request::<M>(&mut ctx).await {
+            return Err(self.interceptor.error::<M>(err).await);
+        }
+
+        match self.transport.request(val, ctx).await {
+            Ok(mut res) => {
+                self.interceptor.response(&mut res).await;
+                Ok(res)
+            }
+            Err(err) => Err(self.interceptor.error::<M>(err).await),
+        }
+    }
+}
+
+impl<T, I, M> StreamingTransport<M> for Intercepted<T, I>
+where
+    T: StreamingTransport<M>,
+    I: Interceptor,
+    M: MethodDef,
+{
+    async fn streaming(
+        &self,
+        mut ctx: RequestContext,
+    ) -> Result<(Sender<M>, Streaming<M>), ClientError> {
+        if let Err(err) = self.interceptor.request::<M>(&mut ctx).await {
+            return Err(self.interceptor.error::<M>(err).await);
+        }
+
+        match self.transport.streaming(ctx).await {
+            Ok(res) => Ok(res),
+            Err(err) => Err(self.interceptor.error::<M>(err).await),
+        }
+    }
+}
@@ -1,15 +1,47 @@
 #![allow(async_fn_in_trait)]
 
+use std::rc::Rc;
+
 use ntex_bytes::Bytes;
 use ntex_h2::{client, OperationError, StreamError};
 use ntex_http::{error::Error as HttpError, HeaderMap, StatusCode};
+use ntex_io::IoBoxed;
+use ntex_net::connect::{Address, Connect, ConnectError, Connector};
+use ntex_service::{IntoService, Service};
 
+mod balancer;
+mod channel;
+mod connection;
+mod hedge;
+mod interceptor;
 mod request;
+mod resolver;
+mod retry;
+mod streaming;
 mod transport;
+#[cfg(feature = "web")]
+mod web;
 
+pub use self::balancer::{Balancer, LoadBalancingPolicy};
+pub use self::channel::{Channel, ChannelBuilder};
+pub use self::connection::{ConnectBackoff, ConnectivityState};
+pub use self::hedge::{Hedge, HedgingPolicy};
+pub use self::interceptor::{Intercepted, Interceptor};
 pub use self::request::{Request, RequestContext, Response};
+pub use self::resolver::{DnsResolver, Resolver, Target};
+pub use self::retry::{Retry, RetryPolicy};
+pub use self::streaming::{
+    BidiStreamingRequest, ClientStreamingRequest, Sender, ServerStreamingRequest, Streaming,
+};
+#[cfg(feature = "web")]
+pub use self::web::WebClient;
+
+use crate::compression::{CompressionEncoding, Compressors};
+use crate::service::MethodDef;
+use crate::status::{GrpcStatus, Status};
+use crate::{consts, encoding::DecodeError};
 
-use crate::{encoding::DecodeError, service::MethodDef, status::GrpcStatus};
+use self::connection::{Connection, Monitor};
 
 pub trait Transport<T: MethodDef> {
     /// Errors produced by the transport.
@@ -22,6 +54,17 @@ pub trait Transport<T: MethodDef> {
     ) -> Result<Response<T>, Self::Error>;
 }
 
+/// Transport for streaming calls
+pub trait StreamingTransport<T: MethodDef> {
+    /// Start new call
+    ///
+    /// Returns sender for request messages and stream of response messages.
+    async fn streaming(
+        &self,
+        ctx: RequestContext,
+    ) -> Result<(Sender<T>, Streaming<T>), ClientError>;
+}
+
 /// Client utils methods
 pub trait ClientInformation<T> {
     /// Create new client instance
@@ -38,19 +81,159 @@ pub trait ClientInformation<T> {
 }
 
 #[derive(Clone)]
-pub struct Client(client::Client);
+pub struct Client {
+    client: client::Client,
+    compression: CompressionEncoding,
+    compressors: Compressors,
+    max_recv_message_size: usize,
+    max_send_message_size: usize,
+    connection: Rc<Connection>,
+}
 
 impl Client {
     #[inline]
     /// Get reference to h2 client
+    ///
+    /// Connections of h2 client are not visible to grpc client, connectivity
+    /// state is updated by results of calls. Use [`Client::with_default()`] or
+    /// [`Client::build()`] to track state of opened connections.
     pub fn new(client: client::Client) -> Self {
-        Self(client)
+        Self::with_connection(client, Rc::new(Connection::new(ConnectBackoff::default())))
+    }
+
+    /// Create client for address with default connector
+    ///
+    /// Connectivity state follows lifecycle of opened connections, client
+    /// moves to `Idle` state when connections are closed.
+    pub fn with_default<A, U>(addr: U) -> Self
+    where
+        A: Address + Clone,
+        Connect<A>: From<U>,
+    {
+        Self::build(addr, Connector::default(), |builder| builder)
+    }
+
+    /// Create client for address with custom connector
+    ///
+    /// h2 client is configured by `configure` function. Connectivity state
+    /// follows lifecycle of connections opened by `connector`.
+    pub fn build<A, U, T, F, C>(addr: U, connector: F, configure: C) -> Self
+    where
+        A: Address + Clone,
+        F: IntoService<T, Connect<A>>,
+        T: Service<Connect<A>, Error = ConnectError> + 'static,
+        IoBoxed: From<T::Response>,
+        Connect<A>: From<U>,
+        C: FnOnce(client::ClientBuilder) -> client::ClientBuilder,
+    {
+        let connection = Rc::new(Connection::new(ConnectBackoff::default()));
+        let connector = Monitor::new(connector.into_service(), connection.clone());
+        let builder = client::Client::build::<A, _, Monitor<T>, _>(addr, connector);
+        Self::with_connection(configure(builder).finish(), connection)
+    }
+
+    fn with_connection(client: client::Client, connection: Rc<Connection>) -> Self {
+        Self {
+            client,
+            compression: CompressionEncoding::Identity,
+            compressors: Compressors::default(),
+            max_recv_message_size: consts::DEFAULT_MAX_MESSAGE_SIZE,
+            max_send_message_size: consts::DEFAULT_MAX_MESSAGE_SIZE,
+            connection,
+        }
+    }
+
+    /// Set registry of supported compressors
+    ///
+    /// All registered encodings are advertised in `grpc-accept-encoding` header.
+    /// By default registry is empty and messages are not compressed.
+    pub fn compressors(mut self, compressors: Compressors) -> Self {
+        self.compressors = compressors;
+        self
+    }
+
+    /// Set compression encoding for request messages
+    ///
+    /// By default messages are not compressed.
+    pub fn compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.compression = encoding;
+        self
+    }
+
+    /// Set max size of received message
+    ///
+    /// Call fails with `ResourceExhausted` status if response message is larger.
+    /// By default max size is 4Mb.
+    pub fn max_recv_message_size(mut self, size: usize) -> Self {
+        self.max_recv_message_size = size;
+        self
+    }
+
+    /// Set max size of sent message
+    ///
+    /// Call fails with `ResourceExhausted` status if request message is larger.
+    /// By default max size is 4Mb.
+    pub fn max_send_message_size(mut self, size: usize) -> Self {
+        self.max_send_message_size = size;
+        self
+    }
+
+    /// Set connection backoff
+    ///
+    /// Failed connection attempt starts backoff, calls fail with `Unavailable`
+    /// status until backoff is elapsed. After backoff client moves to `Idle`
+    /// state and next call reconnects. Wait for ready calls are queued until
+    /// connection is established, queued calls share one connection attempt.
+    /// Backoff is shared by all clones of the client.
+    pub fn connect_backoff(self, backoff: ConnectBackoff) -> Self {
+        self.connection.set_backoff(backoff);
+        self
+    }
+
+    /// Wrap client with call interceptor
+    pub fn interceptor<I: Interceptor>(self, interceptor: I) -> Intercepted<Self, I> {
+        Intercepted::new(self, interceptor)
+    }
+
+    /// Wrap client with retry policy for unary calls
+    pub fn retry(self, policy: RetryPolicy) -> Retry<Self> {
+        Retry::new(self, policy)
+    }
+
+    #[inline]
+    /// Current connectivity state
+    ///
+    /// State is shared by all clones of the client.
+    pub fn state(&self) -> ConnectivityState {
+        self.connection.state()
+    }
+
+    /// Wait until connectivity state differs from `current` state
+    ///
+    /// Returns new state.
+    ///
+    /// ```rust,ignore
+    /// let mut state = client.state();
+    /// loop {
+    ///     state = client.watch_state(state).await;
+    ///     log::info!("Upstream connectivity: {:?}", state);
+    /// }
+    /// ```
+    pub async fn watch_state(&self, current: ConnectivityState) -> ConnectivityState {
+        self.connection.watch(current).await
+    }
+
+    /// Close client
+    ///
+    /// Client moves to `Shutdown` state, new calls fail with `Unavailable` status.
+    pub fn close(&self) {
+        self.connection.shutdown()
     }
 
     #[inline]
     /// Get reference to h2 client
     pub fn get_ref(&self) -> &client::Client {
-        &self.0
+        &self.client
     }
 }
 
@@ -70,8 +253,36 @@ pub enum ClientError {
     Response(Option<StatusCode>, HeaderMap, Bytes),
     #[error("Got eof without payload with {0:?}, headers: {1:?}")]
     UnexpectedEof(Option<StatusCode>, HeaderMap),
-    #[error("Grpc status {0:?}, headers: {1:?}")]
-    GrpcStatus(GrpcStatus, HeaderMap),
+    #[error("Grpc status {0}, headers: {1:?}")]
+    GrpcStatus(Status, HeaderMap),
+    #[error("Message compression error: {0}")]
+    Compression(String),
+    #[error("Invalid target {0}")]
+    Target(String),
+}
+
+impl ClientError {
+    pub(crate) fn deadline_exceeded() -> Self {
+        ClientError::GrpcStatus(
+            Status::new(GrpcStatus::DeadlineExceeded, "Deadline exceeded"),
+            HeaderMap::default(),
+        )
+    }
+
+    /// Status code of failed call, transport failures are reported as `Unavailable`
+    pub(crate) fn code(&self) -> Option<GrpcStatus> {
+        match self {
+            ClientError::GrpcStatus(st, _) => Some(st.code),
+            ClientError::Client(_) | ClientError::Operation(_) => Some(GrpcStatus::Unavailable),
+            _ => None,
+        }
+    }
+}
+
+impl From<Status> for ClientError {
+    fn from(st: Status) -> Self {
+        Self::GrpcStatus(st, HeaderMap::default())
+    }
 }
 
 impl From<HttpError> for ClientError {
@@ -92,7 +303,9 @@ impl Clone for ClientError {
                 Self::Response(*st, hdrs.clone(), payload.clone())
             }
             Self::UnexpectedEof(st, hdrs) => Self::UnexpectedEof(*st, hdrs.clone()),
-            Self::GrpcStatus(st, hdrs) => Self::GrpcStatus(*st, hdrs.clone()),
+            Self::GrpcStatus(st, hdrs) => Self::GrpcStatus(st.clone(), hdrs.clone()),
+            Self::Compression(err) => Self::Compression(err.clone()),
+            Self::Target(err) => Self::Target(err.clone()),
         }
     }
 }
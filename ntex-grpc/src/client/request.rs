@@ -1,25 +1,90 @@
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use std::{convert::TryFrom, fmt, future::Future, mem, ops, pin::Pin, rc::Rc};
 
 use ntex_http::{error::Error as HttpError, HeaderMap, HeaderName, HeaderValue};
 use ntex_util::future::BoxFuture;
 
 use crate::client::Transport;
-use crate::service::MethodDef;
+use crate::{compression::CompressionEncoding, service::MethodDef};
+
+/// Builder methods of a call, applied to request context until call is started
+macro_rules! request_context_methods {
+    ($name:ident<$lt:lifetime, $t:ident, $m:ident>, $($bounds:tt)+) => {
+        impl<$lt, $t, $m> $name<$lt, $t, $m>
+        where
+            $($bounds)+
+        {
+            /// Append a header to existing headers.
+            pub fn header<K, V>(&mut self, key: K, value: V) -> &mut Self
+            where
+                HeaderName: TryFrom<K>,
+                HeaderValue: TryFrom<V>,
+                <HeaderName as TryFrom<K>>::Error: Into<HttpError>,
+                <HeaderValue as TryFrom<V>>::Error: Into<HttpError>,
+            {
+                if let Some(ctx) = self.state.context() {
+                    ctx.header(key, value);
+                }
+                self
+            }
+
+            /// Set compression encoding for request messages.
+            pub fn compression(&mut self, encoding: CompressionEncoding) -> &mut Self {
+                if let Some(ctx) = self.state.context() {
+                    ctx.compression(encoding);
+                }
+                self
+            }
+
+            /// Set call timeout.
+            pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+                if let Some(ctx) = self.state.context() {
+                    ctx.timeout(timeout);
+                }
+                self
+            }
+
+            /// Set call deadline.
+            pub fn deadline(&mut self, deadline: Instant) -> &mut Self {
+                if let Some(ctx) = self.state.context() {
+                    ctx.deadline(deadline);
+                }
+                self
+            }
+
+            /// Wait for ready connection.
+            pub fn wait_for_ready(&mut self) -> &mut Self {
+                if let Some(ctx) = self.state.context() {
+                    ctx.wait_for_ready();
+                }
+                self
+            }
+        }
+    };
+}
+
+pub(super) use request_context_methods;
 
 pub struct RequestContext(Rc<RequestContextInner>);
 
 struct RequestContextInner {
     err: Option<HttpError>,
     headers: Vec<(HeaderName, HeaderValue)>,
+    compression: Option<CompressionEncoding>,
+    deadline: Option<Instant>,
+    wait_for_ready: bool,
 }
 
 impl RequestContext {
     /// Create new RequestContext instance
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self(Rc::new(RequestContextInner {
             err: None,
             headers: Vec::new(),
+            compression: None,
+            deadline: None,
+            wait_for_ready: false,
         }))
     }
 
@@ -43,9 +108,77 @@ impl RequestContext {
         self
     }
 
-    pub(crate) fn headers(&self) -> &[(HeaderName, HeaderValue)] {
+    /// Set compression encoding for request messages
+    ///
+    /// Overrides client's compression encoding.
+    pub fn compression(&mut self, encoding: CompressionEncoding) -> &mut Self {
+        if let Some(ctx) = ctx(self) {
+            ctx.compression = Some(encoding);
+        }
+        self
+    }
+
+    /// Set call timeout
+    ///
+    /// Timeout is sent to the server in `grpc-timeout` header, call fails
+    /// with `DeadlineExceeded` status if it is not complete in time.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    /// Set call deadline
+    ///
+    /// See [`RequestContext::timeout`]
+    pub fn deadline(&mut self, deadline: Instant) -> &mut Self {
+        if let Some(ctx) = ctx(self) {
+            ctx.deadline = Some(deadline);
+        }
+        self
+    }
+
+    /// Wait for ready connection
+    ///
+    /// By default call fails with `Unavailable` status if connection
+    /// is in transient failure. Wait for ready call is queued until
+    /// connection is established or deadline is exceeded.
+    pub fn wait_for_ready(&mut self) -> &mut Self {
+        if let Some(ctx) = ctx(self) {
+            ctx.wait_for_ready = true;
+        }
+        self
+    }
+
+    /// Request headers
+    pub fn headers(&self) -> &[(HeaderName, HeaderValue)] {
         &self.0.headers
     }
+
+    /// Add request headers to headers of the call
+    ///
+    /// First value replaces header set by transport, values
+    /// of repeated names are appended.
+    pub(crate) fn apply_headers(&self, hdrs: &mut HeaderMap) {
+        let headers = &self.0.headers;
+        for (idx, (key, val)) in headers.iter().enumerate() {
+            if headers[..idx].iter().any(|(k, _)| k == key) {
+                hdrs.append(key.clone(), val.clone());
+            } else {
+                hdrs.insert(key.clone(), val.clone());
+            }
+        }
+    }
+
+    pub(crate) fn get_compression(&self) -> Option<CompressionEncoding> {
+        self.0.compression
+    }
+
+    pub(crate) fn get_deadline(&self) -> Option<Instant> {
+        self.0.deadline
+    }
+
+    pub(crate) fn is_wait_for_ready(&self) -> bool {
+        self.0.wait_for_ready
+    }
 }
 
 impl Clone for RequestContext {
@@ -71,12 +204,18 @@ fn ctx(slf: &mut RequestContext) -> Option<&mut RequestContextInner> {
         slf.0 = Rc::new(RequestContextInner {
             err: None,
             headers: slf.0.headers.clone(),
+            compression: slf.0.compression,
+            deadline: slf.0.deadline,
+            wait_for_ready: slf.0.wait_for_ready,
         });
         Some(Rc::get_mut(&mut slf.0).unwrap())
     }
 }
 
 pin_project_lite::pin_project! {
+    /// Unary call
+    ///
+    /// Dropping request before it is complete resets the call with `CANCEL` reason.
     pub struct Request<'a, T, M>
     where T: Transport<M>,
           T: 'a,
@@ -117,41 +256,22 @@ where
             },
         }
     }
-
-    /// Append a header to existing headers.
-    ///
-    /// ```rust
-    /// use ntex::http::{header, Request, Response};
-    ///
-    /// fn index(req: Request) -> Response {
-    ///     Response::Ok()
-    ///         .header("X-TEST", "value")
-    ///         .header(header::CONTENT_TYPE, "application/json")
-    ///         .finish()
-    /// }
-    /// ```
-    pub fn header<K, V>(&mut self, key: K, value: V) -> &mut Self
-    where
-        HeaderName: TryFrom<K>,
-        HeaderValue: TryFrom<V>,
-        <HeaderName as TryFrom<K>>::Error: Into<HttpError>,
-        <HeaderValue as TryFrom<V>>::Error: Into<HttpError>,
-    {
-        if let Some(ctx) = parts(&mut self.state) {
-            ctx.header(key, value);
-        }
-        self
-    }
 }
 
-#[inline]
-fn parts<'a, 'b, T: Transport<M> + 'a, M: MethodDef>(
-    parts: &'b mut State<'a, T, M>,
-) -> Option<&'b mut RequestContext> {
-    if let State::Request { ref mut ctx, .. } = parts {
-        ctx.as_mut()
-    } else {
-        None
+request_context_methods!(Request<'a, T, M>, T: Transport<M>, M: MethodDef);
+
+impl<'a, T, M> State<'a, T, M>
+where
+    T: Transport<M> + 'a,
+    M: MethodDef,
+{
+    #[inline]
+    fn context(&mut self) -> Option<&mut RequestContext> {
+        if let State::Request { ref mut ctx, .. } = self {
+            ctx.as_mut()
+        } else {
+            None
+        }
     }
 }
 
@@ -0,0 +1,246 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::{io, path::PathBuf, str::FromStr};
+
+use ntex_net::connect::{Connect, ConnectError, Resolver as NetResolver};
+
+use super::ClientError;
+
+/// Default port of targets without port
+const DEFAULT_PORT: u16 = 443;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Parsed client target
+///
+/// Supported target formats:
+///
+/// * `dns:///host:port`, `dns:host:port` or `host:port`, host name is
+///   resolved with [`Resolver`]. Authority of dns server is ignored.
+/// * `ipv4:addr[:port][,addr[:port],...]`
+/// * `ipv6:addr`, `ipv6:[addr]:port`, comma separated list of addresses
+/// * `unix:path` or `unix:///absolute_path`
+///
+/// Default port is 443.
+pub enum Target {
+    /// Host name that requires resolution
+    Dns { host: String, port: u16 },
+    /// Static list of addresses
+    Ip(Vec<SocketAddr>),
+    /// Unix domain socket path
+    Unix(PathBuf),
+}
+
+impl Target {
+    /// Authority of the target
+    pub fn authority(&self) -> String {
+        match self {
+            Target::Dns { host, port } => format!("{}:{}", host, port),
+            Target::Ip(addrs) => addrs[0].to_string(),
+            Target::Unix(_) => "localhost".to_string(),
+        }
+    }
+}
+
+impl FromStr for Target {
+    type Err = ClientError;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| ClientError::Target(format!("{:?}, {}", target, reason));
+
+        if let Some(path) = target.strip_prefix("unix:") {
+            let path = path.strip_prefix("//").unwrap_or(path);
+            if path.is_empty() {
+                Err(invalid("path is empty"))
+            } else {
+                Ok(Target::Unix(PathBuf::from(path)))
+            }
+        } else if let Some(addrs) = target.strip_prefix("ipv4:") {
+            let addrs = addrs
+                .split(',')
+                .map(|addr| match addr.rsplit_once(':') {
+                    Some((ip, port)) => Some(SocketAddr::new(
+                        ip.parse::<Ipv4Addr>().ok()?.into(),
+                        port.parse().ok()?,
+                    )),
+                    None => Some(SocketAddr::new(
+                        addr.parse::<Ipv4Addr>().ok()?.into(),
+                        DEFAULT_PORT,
+                    )),
+                })
+                .collect::<Option<Vec<_>>>();
+            addrs
+                .filter(|addrs| !addrs.is_empty())
+                .map(Target::Ip)
+                .ok_or_else(|| invalid("expected list of ipv4 addresses"))
+        } else if let Some(addrs) = target.strip_prefix("ipv6:") {
+            let addrs = addrs
+                .split(',')
+                .map(|addr| {
+                    if addr.starts_with('[') {
+                        addr.parse::<SocketAddr>()
+                            .ok()
+                            .filter(|addr| addr.is_ipv6())
+                            .or_else(|| {
+                                let ip = addr.strip_prefix('[')?.strip_suffix(']')?;
+                                Some(SocketAddr::new(
+                                    ip.parse::<Ipv6Addr>().ok()?.into(),
+                                    DEFAULT_PORT,
+                                ))
+                            })
+                    } else {
+                        Some(SocketAddr::new(
+                            addr.parse::<Ipv6Addr>().ok()?.into(),
+                            DEFAULT_PORT,
+                        ))
+                    }
+                })
+                .collect::<Option<Vec<_>>>();
+            addrs
+                .filter(|addrs| !addrs.is_empty())
+                .map(Target::Ip)
+                .ok_or_else(|| invalid("expected list of ipv6 addresses"))
+        } else {
+            let name = if let Some(name) = target.strip_prefix("dns:") {
+                // skip dns server authority
+                match name.strip_prefix("//") {
+                    Some(name) => name.split_once('/').map(|(_, name)| name).unwrap_or(""),
+                    None => name,
+                }
+            } else {
+                target
+            };
+            let (host, port) = if let Some(name) = name.strip_prefix('[') {
+                match name.split_once(']') {
+                    Some((host, "")) => (host, None),
+                    Some((host, port)) => (host, Some(port.strip_prefix(':').unwrap_or("-"))),
+                    None => return Err(invalid("expected closing bracket")),
+                }
+            } else {
+                match name.rsplit_once(':') {
+                    Some((host, port)) => (host, Some(port)),
+                    None => (name, None),
+                }
+            };
+            if host.is_empty() {
+                return Err(invalid("host is empty"));
+            }
+            let port = match port {
+                Some(port) => port.parse().map_err(|_| invalid("port is not valid"))?,
+                None => DEFAULT_PORT,
+            };
+
+            // ip address does not require resolution
+            match host.parse::<IpAddr>() {
+                Ok(ip) => Ok(Target::Ip(vec![SocketAddr::new(ip, port)])),
+                Err(_) => Ok(Target::Dns {
+                    host: host.to_string(),
+                    port,
+                }),
+            }
+        }
+    }
+}
+
+/// Host name resolver
+pub trait Resolver {
+    /// Resolve host name to list of addresses
+    async fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>>;
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+/// Resolver that uses system dns resolver
+pub struct DnsResolver;
+
+impl Resolver for DnsResolver {
+    async fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        match NetResolver::<String>::new()
+            .lookup(Connect::new(host.to_string()).set_port(port))
+            .await
+        {
+            Ok(req) => Ok(req.addrs().collect()),
+            Err(ConnectError::Resolver(err)) | Err(ConnectError::Io(err)) => Err(err),
+            Err(err) => Err(io::Error::new(io::ErrorKind::NotFound, err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(target: &str) -> Target {
+        target.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_dns() {
+        let target = Target::Dns {
+            host: "example.com".to_string(),
+            port: 50051,
+        };
+        assert_eq!(parse("dns:///example.com:50051"), target);
+        assert_eq!(parse("dns://8.8.8.8/example.com:50051"), target);
+        assert_eq!(parse("dns:example.com:50051"), target);
+        assert_eq!(parse("example.com:50051"), target);
+        assert_eq!(target.authority(), "example.com:50051");
+        assert_eq!(
+            parse("dns:///example.com"),
+            Target::Dns {
+                host: "example.com".to_string(),
+                port: 443
+            }
+        );
+        assert_eq!(
+            parse("dns:///127.0.0.1:80"),
+            Target::Ip(vec!["127.0.0.1:80".parse().unwrap()])
+        );
+        assert_eq!(
+            parse("[::1]:80"),
+            Target::Ip(vec!["[::1]:80".parse().unwrap()])
+        );
+
+        assert!("dns:///".parse::<Target>().is_err());
+        assert!("dns:///example.com:port".parse::<Target>().is_err());
+        assert!("[::1".parse::<Target>().is_err());
+    }
+
+    #[test]
+    fn parse_ip() {
+        assert_eq!(
+            parse("ipv4:127.0.0.1:80,10.0.0.1"),
+            Target::Ip(vec![
+                "127.0.0.1:80".parse().unwrap(),
+                "10.0.0.1:443".parse().unwrap()
+            ])
+        );
+        assert_eq!(
+            parse("ipv6:::1,[::2]:80,[::3]"),
+            Target::Ip(vec![
+                "[::1]:443".parse().unwrap(),
+                "[::2]:80".parse().unwrap(),
+                "[::3]:443".parse().unwrap()
+            ])
+        );
+        assert_eq!(parse("ipv6:[::2]:80").authority(), "[::2]:80");
+
+        assert!("ipv4:".parse::<Target>().is_err());
+        assert!("ipv4:::1".parse::<Target>().is_err());
+        assert!("ipv6:127.0.0.1".parse::<Target>().is_err());
+    }
+
+    #[test]
+    fn parse_unix() {
+        assert_eq!(
+            parse("unix:/tmp/grpc.sock"),
+            Target::Unix(PathBuf::from("/tmp/grpc.sock"))
+        );
+        assert_eq!(
+            parse("unix:///tmp/grpc.sock"),
+            Target::Unix(PathBuf::from("/tmp/grpc.sock"))
+        );
+        assert_eq!(
+            parse("unix:grpc.sock"),
+            Target::Unix(PathBuf::from("grpc.sock"))
+        );
+        assert!("unix:".parse::<Target>().is_err());
+    }
+}
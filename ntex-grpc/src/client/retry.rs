@@ -0,0 +1,339 @@
+use std::time::{Duration, Instant};
+use std::{cell::Cell, fmt, rc::Rc};
+
+use ntex_http::HeaderMap;
+use ntex_util::time::sleep;
+
+use crate::{consts, service::MethodDef, GrpcStatus};
+
+use super::{
+    ClientError, RequestContext, Response, Sender, Streaming, StreamingTransport, Transport,
+};
+
+/// Retry policy for unary calls
+///
+/// Follows grpc retry design, failed call is retried if it fails with one
+/// of retryable status codes. Delay before next attempt is random value between
+/// zero and current backoff, backoff grows exponentially up to max backoff.
+///
+/// Connection failures and streams reset before response are treated
+/// as `Unavailable` status.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    backoff_multiplier: f64,
+    retryable_codes: Vec<GrpcStatus>,
+    throttling: Option<RetryThrottling>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct RetryThrottling {
+    max_tokens: f64,
+    token_ratio: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            retryable_codes: vec![GrpcStatus::Unavailable],
+            throttling: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create retry policy
+    ///
+    /// By default call is attempted 3 times, backoff starts at 100 millis and
+    /// is limited by 1 second, only `Unavailable` status is retried.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set max number of call attempts, including original one
+    ///
+    /// Value is limited by 5 attempts.
+    pub fn max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.clamp(1, 5);
+        self
+    }
+
+    /// Set backoff of the first retry
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set max backoff
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Set multiplier that backoff is increased by after each retry
+    pub fn backoff_multiplier(mut self, multiplier: f64) -> Self {
+        self.backoff_multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Set status codes that could be retried
+    pub fn retryable_codes(mut self, codes: &[GrpcStatus]) -> Self {
+        self.retryable_codes = codes.to_vec();
+        self
+    }
+
+    /// Enable retry throttling
+    ///
+    /// Client keeps token count that starts at `max_tokens`. Each failed
+    /// call decreases it by 1, each successful call increases it by `token_ratio`.
+    /// Calls are not retried while token count is below half of `max_tokens`.
+    pub fn throttling(mut self, max_tokens: u32, token_ratio: f64) -> Self {
+        self.throttling = Some(RetryThrottling {
+            max_tokens: f64::from(max_tokens),
+            token_ratio,
+        });
+        self
+    }
+
+    /// Backoff for specified retry attempt, starting from 1
+    fn backoff(&self, retry: usize) -> Duration {
+        let backoff =
+            self.initial_backoff.as_secs_f64() * self.backoff_multiplier.powi(retry as i32 - 1);
+        Duration::from_secs_f64(backoff.min(self.max_backoff.as_secs_f64()))
+    }
+}
+
+/// Transport that retries failed unary calls
+///
+/// Streaming calls are not retried. Retry throttling state is shared
+/// by all clones of the transport.
+pub struct Retry<T> {
+    transport: T,
+    policy: Rc<RetryPolicy>,
+    tokens: Rc<Cell<f64>>,
+}
+
+impl<T> Retry<T> {
+    /// Wrap transport with retry policy
+    pub fn new(transport: T, policy: RetryPolicy) -> Self {
+        let tokens = policy.throttling.map(|t| t.max_tokens).unwrap_or(0.0);
+        Self {
+            transport,
+            policy: Rc::new(policy),
+            tokens: Rc::new(Cell::new(tokens)),
+        }
+    }
+
+    #[inline]
+    /// Get reference to wrapped transport
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    /// Update throttling state with call result
+    fn record(&self, success: bool) {
+        if let Some(throttling) = self.policy.throttling {
+            let tokens = if success {
+                (self.tokens.get() + throttling.token_ratio).min(throttling.max_tokens)
+            } else {
+                (self.tokens.get() - 1.0).max(0.0)
+            };
+            self.tokens.set(tokens);
+        }
+    }
+
+    /// Check if failed call could be retried
+    ///
+    /// Connection failures are retried as `Unavailable` status.
+    fn is_retryable(&self, err: &ClientError) -> bool {
+        err.code()
+            .map(|code| self.policy.retryable_codes.contains(&code))
+            .unwrap_or(false)
+    }
+
+    /// Check if retries are not throttled
+    fn is_allowed(&self) -> bool {
+        self.policy
+            .throttling
+            .map(|t| self.tokens.get() > t.max_tokens / 2.0)
+            .unwrap_or(true)
+    }
+}
+
+impl<T: Clone> Clone for Retry<T> {
+    fn clone(&self) -> Self {
+        Self {
+            transport: self.transport.clone(),
+            policy: self.policy.clone(),
+            tokens: self.tokens.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Retry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Retry")
+            .field("transport", &self.transport)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<T, M> Transport<M> for Retry<T>
+where
+    T: Transport<M, Error = ClientError>,
+    M: MethodDef,
+{
+    type Error = ClientError;
+
+    async fn request(
+        &self,
+        val: &M::Input,
+        ctx: RequestContext,
+    ) -> Result<Response<M>, Self::Error> {
+        let mut attempt = 1;
+        let mut retry = 1;
+        loop {
+            let mut attempt_ctx = ctx.clone();
+            if attempt > 1 {
+                attempt_ctx.header(consts::GRPC_PREVIOUS_RPC_ATTEMPTS, attempt - 1);
+            }
+
+            let err = match self.transport.request(val, attempt_ctx).await {
+                Ok(res) => {
+                    self.record(true);
+                    return Ok(res);
+                }
+                Err(err) => err,
+            };
+            let pushback = match err {
+                ClientError::GrpcStatus(_, ref trailers) if self.is_retryable(&err) => {
+                    parse_pushback(trailers)
+                }
+                _ if self.is_retryable(&err) => None,
+                _ => return Err(err),
+            };
+            self.record(false);
+
+            if attempt >= self.policy.max_attempts || !self.is_allowed() {
+                return Err(err);
+            }
+
+            // server pushback overrides backoff and resets it
+            let delay = match pushback {
+                Some(Some(delay)) => {
+                    retry = 1;
+                    delay
+                }
+                Some(None) => return Err(err),
+                None => {
+                    let backoff = self.policy.backoff(retry);
+                    retry += 1;
+                    backoff.mul_f64(fastrand::f64())
+                }
+            };
+            if let Some(deadline) = ctx.get_deadline() {
+                if Instant::now() + delay >= deadline {
+                    return Err(err);
+                }
+            }
+
+            log::debug!(
+                "Retry call {} after {:?}, attempt {}: {}",
+                M::PATH,
+                delay,
+                attempt,
+                err
+            );
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl<T, M> StreamingTransport<M> for Retry<T>
+where
+    T: StreamingTransport<M>,
+    M: MethodDef,
+{
+    async fn streaming(
+        &self,
+        ctx: RequestContext,
+    ) -> Result<(Sender<M>, Streaming<M>), ClientError> {
+        self.transport.streaming(ctx).await
+    }
+}
+
+/// Parse `grpc-retry-pushback-ms` trailer
+///
+/// Returns `Some(None)` if server asks to not retry call.
+pub(super) fn parse_pushback(trailers: &HeaderMap) -> Option<Option<Duration>> {
+    trailers.get(consts::GRPC_RETRY_PUSHBACK_MS).map(|val| {
+        val.to_str()
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_millis)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ntex_http::HeaderValue;
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(500))
+            .backoff_multiplier(2.0);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(RetryPolicy::new().max_attempts(10).max_attempts, 5);
+    }
+
+    #[test]
+    fn pushback() {
+        let mut hdrs = HeaderMap::default();
+        assert_eq!(parse_pushback(&hdrs), None);
+
+        hdrs.insert(
+            consts::GRPC_RETRY_PUSHBACK_MS,
+            HeaderValue::from_static("250"),
+        );
+        assert_eq!(
+            parse_pushback(&hdrs),
+            Some(Some(Duration::from_millis(250)))
+        );
+
+        hdrs.insert(
+            consts::GRPC_RETRY_PUSHBACK_MS,
+            HeaderValue::from_static("-1"),
+        );
+        assert_eq!(parse_pushback(&hdrs), Some(None));
+    }
+
+    #[test]
+    fn throttling() {
+        let retry = Retry::new((), RetryPolicy::new().throttling(4, 0.5));
+        assert!(retry.is_allowed());
+        retry.record(false);
+        assert!(retry.is_allowed());
+        retry.record(false);
+        assert!(!retry.is_allowed());
+        retry.record(true);
+        assert!(retry.is_allowed());
+        for _ in 0..10 {
+            retry.record(true);
+        }
+        assert_eq!(retry.tokens.get(), 4.0);
+    }
+}
@@ -0,0 +1,581 @@
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use std::{
+    convert::TryFrom, future::poll_fn, future::Future, marker, mem, pin::Pin, rc::Rc, str::FromStr,
+};
+
+use ntex_bytes::{Bytes, BytesMut};
+use ntex_h2::{self as h2, client::RecvStream, client::SendStream, frame::Reason};
+use ntex_http::{error::Error as HttpError, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use ntex_util::future::{select, stream_recv, BoxFuture, Either};
+use ntex_util::time::Sleep;
+use ntex_util::Stream;
+
+use crate::compression::{CompressionEncoding, Compressors, Encoder};
+use crate::utils::{self, Data};
+use crate::{consts, service::MethodDef};
+use crate::{DecodeError, GrpcStatus, Message, Status};
+
+use super::request::request_context_methods;
+use super::{connection::Connection, ClientError, RequestContext, Response, StreamingTransport};
+
+/// Sending part of the streaming call
+///
+/// Dropping sender without closing it resets the call.
+pub struct Sender<T: MethodDef> {
+    stream: SendStream,
+    encoder: Encoder,
+    max_size: usize,
+    _t: marker::PhantomData<T>,
+}
+
+impl<T: MethodDef> Sender<T> {
+    pub(crate) fn new(stream: SendStream, encoder: Encoder, max_size: usize) -> Self {
+        Self {
+            stream,
+            encoder,
+            max_size,
+            _t: marker::PhantomData,
+        }
+    }
+
+    /// Send request message
+    pub async fn send(&self, msg: &T::Input) -> Result<(), ClientError> {
+        self.send_message(msg, false).await.map(|_| ())
+    }
+
+    /// Close sending part of the call
+    pub async fn close(&self) -> Result<(), ClientError> {
+        Ok(self.stream.send_payload(Bytes::new(), true).await?)
+    }
+
+    /// Send request message, close sending part of the call if `eof` is set
+    ///
+    /// Returns size of the encoded message.
+    pub(crate) async fn send_message(
+        &self,
+        msg: &T::Input,
+        eof: bool,
+    ) -> Result<usize, ClientError> {
+        let mut buf = BytesMut::with_capacity(msg.encoded_len());
+        msg.write(&mut buf);
+        if buf.len() > self.max_size {
+            return Err(utils::send_size_exceeded(buf.len(), self.max_size).into());
+        }
+
+        let mut data = BytesMut::new();
+        self.encoder
+            .encode(&buf, &mut data)
+            .map_err(|e| ClientError::Compression(e.to_string()))?;
+        let size = data.len();
+
+        self.stream.send_payload(data.freeze(), eof).await?;
+        Ok(size)
+    }
+}
+
+/// Stream of response messages
+///
+/// Dropping stream before it is complete resets the call.
+pub struct Streaming<T: MethodDef> {
+    stream: RecvStream,
+    connection: Rc<Connection>,
+    payload: Data,
+    compressors: Compressors,
+    encoder: Option<Encoder>,
+    status: Option<StatusCode>,
+    headers: HeaderMap,
+    trailers: HeaderMap,
+    res_size: usize,
+    max_size: usize,
+    eof: bool,
+    deadline: Option<Sleep>,
+    _t: marker::PhantomData<T>,
+}
+
+impl<T: MethodDef> Streaming<T> {
+    pub(crate) fn new(
+        stream: RecvStream,
+        connection: Rc<Connection>,
+        compressors: Compressors,
+        deadline: Option<Instant>,
+        max_size: usize,
+    ) -> Self {
+        Self {
+            stream,
+            connection,
+            compressors,
+            max_size,
+            deadline: deadline
+                .map(|d| Sleep::new(d.saturating_duration_since(Instant::now()).into())),
+            payload: Data::Empty,
+            encoder: Some(Encoder::identity()),
+            status: None,
+            headers: HeaderMap::default(),
+            trailers: HeaderMap::default(),
+            res_size: 0,
+            eof: false,
+            _t: marker::PhantomData,
+        }
+    }
+
+    #[inline]
+    /// Response headers
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    #[inline]
+    /// Response trailers
+    ///
+    /// Trailers are available after stream is complete.
+    pub fn trailers(&self) -> &HeaderMap {
+        &self.trailers
+    }
+
+    /// Receive next response message
+    pub async fn recv(&mut self) -> Option<Result<T::Output, ClientError>> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Receive next response message
+    pub fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<T::Output, ClientError>>> {
+        if !self.eof {
+            if let Some(ref deadline) = self.deadline {
+                if deadline.poll_elapsed(cx).is_ready() {
+                    self.eof = true;
+                    self.payload = Data::Empty;
+                    self.stream.stream().reset(Reason::CANCEL);
+                    return Poll::Ready(Some(Err(ClientError::deadline_exceeded())));
+                }
+            }
+        }
+
+        match self.poll_message(cx) {
+            Poll::Ready(Some(Err(err))) => {
+                self.eof = true;
+                self.payload = Data::Empty;
+                Poll::Ready(Some(Err(err)))
+            }
+            res => res,
+        }
+    }
+
+    fn poll_message(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<T::Output, ClientError>>> {
+        loop {
+            if self.status.map(|st| st.is_success()).unwrap_or(false) {
+                let next = self.payload.next_message(self.max_size).map_err(|st| {
+                    self.stream.stream().reset(Reason::CANCEL);
+                    ClientError::from(st)
+                })?;
+                if let Some((compressed, msg)) = next {
+                    self.res_size += msg.len() + 5;
+                    let mut msg =
+                        decode_message(compressed, msg, self.encoder.as_ref(), self.max_size)?;
+                    if msg.len() > self.max_size {
+                        self.stream.stream().reset(Reason::CANCEL);
+                        return Poll::Ready(Some(Err(utils::decompressed_size_exceeded(
+                            self.max_size,
+                        )
+                        .into())));
+                    }
+                    return Poll::Ready(Some(
+                        <T::Output as Message>::read(&mut msg).map_err(ClientError::Decode),
+                    ));
+                }
+            }
+
+            if self.eof {
+                return if self.payload.len() == 0 {
+                    Poll::Ready(None)
+                } else if self.status.map(|st| st.is_success()).unwrap_or(false) {
+                    Poll::Ready(Some(Err(ClientError::UnexpectedEof(
+                        self.status,
+                        mem::take(&mut self.headers),
+                    ))))
+                } else {
+                    Poll::Ready(Some(Err(ClientError::Response(
+                        self.status,
+                        mem::take(&mut self.headers),
+                        self.payload.get(),
+                    ))))
+                };
+            }
+
+            let msg = if let Some(msg) = ntex_util::ready!(self.stream.poll_recv(cx)) {
+                msg
+            } else {
+                self.eof = true;
+                return Poll::Ready(Some(Err(ClientError::UnexpectedEof(
+                    self.status,
+                    mem::take(&mut self.headers),
+                ))));
+            };
+
+            match msg.kind {
+                h2::MessageKind::Headers {
+                    headers,
+                    pseudo,
+                    eof,
+                } => {
+                    if eof {
+                        // trailers-only response
+                        self.eof = true;
+                        check_grpc_status(&headers)?;
+                        self.status = pseudo.status;
+                        self.headers = headers.clone();
+                        self.trailers = headers;
+                        if !self.status.map(|st| st.is_success()).unwrap_or(false) {
+                            return Poll::Ready(Some(Err(ClientError::Response(
+                                self.status,
+                                mem::take(&mut self.headers),
+                                Bytes::new(),
+                            ))));
+                        }
+                    } else {
+                        self.encoder =
+                            self.compressors.decoder(headers.get(consts::GRPC_ENCODING));
+                        self.headers = headers;
+                        self.status = pseudo.status;
+                    }
+                }
+                h2::MessageKind::Data(data, _cap) => {
+                    self.payload.push(data);
+                }
+                h2::MessageKind::Eof(data) => {
+                    self.eof = true;
+                    match data {
+                        h2::StreamEof::Data(data) => {
+                            self.payload.push(data);
+                        }
+                        h2::StreamEof::Trailers(hdrs) => {
+                            check_grpc_status(&hdrs)?;
+                            self.trailers = hdrs;
+                        }
+                        h2::StreamEof::Error(err) if self.status.is_none() => {
+                            // request is not processed, call could be retried
+                            return Poll::Ready(Some(Err(Status::new(
+                                GrpcStatus::Unavailable,
+                                format!("Stream is reset before response: {}", err),
+                            )
+                            .into())));
+                        }
+                        h2::StreamEof::Error(err) => {
+                            return Poll::Ready(Some(Err(ClientError::Stream(err))))
+                        }
+                    }
+                }
+                h2::MessageKind::Disconnect(err) => {
+                    self.connection.disconnected();
+                    return Poll::Ready(Some(Err(ClientError::Operation(err))));
+                }
+            }
+        }
+    }
+
+    /// Read single response message and wait for the end of the stream
+    pub(crate) async fn response(mut self, req_size: usize) -> Result<Response<T>, ClientError> {
+        let output = match self.recv().await {
+            Some(Ok(output)) => output,
+            Some(Err(err)) => return Err(err),
+            None => {
+                return Err(ClientError::UnexpectedEof(
+                    self.status,
+                    mem::take(&mut self.headers),
+                ))
+            }
+        };
+        while let Some(item) = self.recv().await {
+            item?;
+        }
+
+        Ok(Response {
+            output,
+            req_size,
+            headers: self.headers,
+            trailers: self.trailers,
+            res_size: self.res_size,
+        })
+    }
+}
+
+impl<T: MethodDef> Stream for Streaming<T> {
+    type Item = Result<T::Output, ClientError>;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T: MethodDef> Unpin for Streaming<T> {}
+
+/// Decompress message, `None` encoder means unsupported encoding
+pub(super) fn decode_message(
+    compressed: bool,
+    msg: Bytes,
+    encoder: Option<&Encoder>,
+    max_size: usize,
+) -> Result<Bytes, DecodeError> {
+    if let Some(encoder) = encoder {
+        encoder.decode(compressed, msg, max_size)
+    } else if compressed {
+        Err(DecodeError::new("Unsupported grpc-encoding"))
+    } else {
+        Ok(msg)
+    }
+}
+
+/// Check grpc status, returns error for non-ok status
+pub(super) fn check_grpc_status(hdrs: &HeaderMap) -> Result<(), ClientError> {
+    if let Some(val) = hdrs.get(consts::GRPC_STATUS) {
+        if let Ok(status) = val
+            .to_str()
+            .map_err(|_| ())
+            .and_then(|v| u8::from_str(v).map_err(|_| ()))
+            .and_then(GrpcStatus::try_from)
+        {
+            if status != GrpcStatus::Ok {
+                return Err(ClientError::GrpcStatus(
+                    Status::from_headers(status, hdrs),
+                    hdrs.clone(),
+                ));
+            }
+        } else {
+            return Err(ClientError::Decode(DecodeError::new(
+                "Cannot parse grpc status",
+            )));
+        }
+    }
+    Ok(())
+}
+
+pin_project_lite::pin_project! {
+    /// Server streaming call
+    ///
+    /// Resolves to the stream of response messages.
+    pub struct ServerStreamingRequest<'a, T, M>
+    where T: StreamingTransport<M>,
+          T: 'a,
+          M: MethodDef
+    {
+        transport: &'a T,
+        state: State<'a, &'a M::Input, Streaming<M>>,
+    }
+}
+
+enum State<'a, I, O> {
+    Call {
+        fut: BoxFuture<'a, Result<O, ClientError>>,
+    },
+    Request {
+        input: I,
+        ctx: RequestContext,
+    },
+    None,
+}
+
+impl<'a, I, O> State<'a, I, O> {
+    #[inline]
+    fn context(&mut self) -> Option<&mut RequestContext> {
+        if let State::Request { ref mut ctx, .. } = self {
+            Some(ctx)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, M> ServerStreamingRequest<'a, T, M>
+where
+    T: StreamingTransport<M>,
+    M: MethodDef,
+{
+    pub fn new(transport: &'a T, input: &'a M::Input) -> Self {
+        Self {
+            transport,
+            state: State::Request {
+                input,
+                ctx: RequestContext::new(),
+            },
+        }
+    }
+}
+
+request_context_methods!(ServerStreamingRequest<'a, T, M>, T: StreamingTransport<M>, M: MethodDef);
+
+impl<'a, T, M: 'a> Future for ServerStreamingRequest<'a, T, M>
+where
+    T: StreamingTransport<M>,
+    M: MethodDef,
+{
+    type Output = Result<Streaming<M>, ClientError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        loop {
+            if let State::Call { ref mut fut } = this.state {
+                return Pin::new(fut).poll(cx);
+            }
+
+            if let State::Request { input, ctx } = mem::replace(this.state, State::None) {
+                let transport = *this.transport;
+                *this.state = State::Call {
+                    fut: Box::pin(async move {
+                        let (tx, rx) = transport.streaming(ctx).await?;
+                        tx.send_message(input, true).await?;
+                        Ok(rx)
+                    }),
+                };
+            }
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Bidirectional streaming call
+    ///
+    /// Resolves to the sender of request messages and the stream of response messages.
+    pub struct BidiStreamingRequest<'a, T, M>
+    where T: StreamingTransport<M>,
+          T: 'a,
+          M: MethodDef
+    {
+        transport: &'a T,
+        state: State<'a, (), (Sender<M>, Streaming<M>)>,
+    }
+}
+
+impl<'a, T, M> BidiStreamingRequest<'a, T, M>
+where
+    T: StreamingTransport<M>,
+    M: MethodDef,
+{
+    pub fn new(transport: &'a T) -> Self {
+        Self {
+            transport,
+            state: State::Request {
+                input: (),
+                ctx: RequestContext::new(),
+            },
+        }
+    }
+}
+
+request_context_methods!(BidiStreamingRequest<'a, T, M>, T: StreamingTransport<M>, M: MethodDef);
+
+impl<'a, T, M: 'a> Future for BidiStreamingRequest<'a, T, M>
+where
+    T: StreamingTransport<M>,
+    M: MethodDef,
+{
+    type Output = Result<(Sender<M>, Streaming<M>), ClientError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        loop {
+            if let State::Call { ref mut fut } = this.state {
+                return Pin::new(fut).poll(cx);
+            }
+
+            if let State::Request { ctx, .. } = mem::replace(this.state, State::None) {
+                *this.state = State::Call {
+                    fut: Box::pin(this.transport.streaming(ctx)),
+                };
+            }
+        }
+    }
+}
+
+type InputStream<'a, M> = Pin<Box<dyn Stream<Item = <M as MethodDef>::Input> + 'a>>;
+
+pin_project_lite::pin_project! {
+    /// Client streaming call
+    ///
+    /// Sends all messages from the input stream and resolves to the single response message.
+    pub struct ClientStreamingRequest<'a, T, M>
+    where T: StreamingTransport<M>,
+          T: 'a,
+          M: MethodDef
+    {
+        transport: &'a T,
+        state: State<'a, InputStream<'a, M>, Response<M>>,
+    }
+}
+
+impl<'a, T, M> ClientStreamingRequest<'a, T, M>
+where
+    T: StreamingTransport<M>,
+    M: MethodDef,
+{
+    pub fn new<S>(transport: &'a T, input: S) -> Self
+    where
+        S: Stream<Item = M::Input> + 'a,
+    {
+        Self {
+            transport,
+            state: State::Request {
+                input: Box::pin(input),
+                ctx: RequestContext::new(),
+            },
+        }
+    }
+}
+
+request_context_methods!(ClientStreamingRequest<'a, T, M>, T: StreamingTransport<M>, M: MethodDef);
+
+impl<'a, T, M: 'a> Future for ClientStreamingRequest<'a, T, M>
+where
+    T: StreamingTransport<M>,
+    M: MethodDef,
+{
+    type Output = Result<Response<M>, ClientError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        loop {
+            if let State::Call { ref mut fut } = this.state {
+                return Pin::new(fut).poll(cx);
+            }
+
+            if let State::Request { mut input, ctx } = mem::replace(this.state, State::None) {
+                let transport = *this.transport;
+                *this.state = State::Call {
+                    fut: Box::pin(async move {
+                        let (tx, rx) = transport.streaming(ctx).await?;
+
+                        let send = Box::pin(async move {
+                            let mut req_size = 0;
+                            while let Some(msg) = stream_recv(&mut input).await {
+                                req_size += tx.send_message(&msg, false).await?;
+                            }
+                            tx.close().await?;
+                            Ok::<_, ClientError>(req_size)
+                        });
+                        let mut response = Box::pin(rx.response(0));
+
+                        // server could respond before all request messages are sent
+                        match select(send, &mut response).await {
+                            Either::Left(Ok(req_size)) => {
+                                let mut res = response.await?;
+                                res.req_size = req_size;
+                                Ok(res)
+                            }
+                            Either::Left(Err(err)) => match response.await {
+                                Err(res_err) => Err(res_err),
+                                Ok(_) => Err(err),
+                            },
+                            Either::Right(res) => res,
+                        }
+                    }),
+                };
+            }
+        }
+    }
+}
@@ -1,13 +1,12 @@
-use std::{convert::TryFrom, str::FromStr};
+use std::time::Instant;
 
-use ntex_bytes::{Buf, BufMut, BytesMut};
-use ntex_h2::{self as h2};
 use ntex_http::{header, HeaderMap, Method};
 
-use crate::{consts, service::MethodDef, utils::Data, DecodeError, GrpcStatus, Message};
+use crate::{consts, service::MethodDef, utils};
 
 use super::request::{RequestContext, Response};
-use super::{Client, ClientError, Transport};
+use super::streaming::{Sender, Streaming};
+use super::{Client, ClientError, StreamingTransport, Transport};
 
 impl<T: MethodDef> Transport<T> for Client {
     type Error = ClientError;
@@ -17,146 +16,78 @@ impl<T: MethodDef> Transport<T> for Client {
         val: &T::Input,
         ctx: RequestContext,
     ) -> Result<Response<T>, Self::Error> {
-        let len = val.encoded_len();
-        let mut buf = BytesMut::with_capacity(len + 5);
-        buf.put_u8(0); // compression
-        buf.put_u32(len as u32); // length
-        val.write(&mut buf);
-        let req_size = buf.len();
+        let (snd_stream, rcv_stream) = StreamingTransport::<T>::streaming(self, ctx).await?;
 
+        // send request
+        let req_size = snd_stream.send_message(val, true).await?;
+
+        // read response
+        rcv_stream.response(req_size).await
+    }
+}
+
+impl<T: MethodDef> StreamingTransport<T> for Client {
+    async fn streaming(
+        &self,
+        ctx: RequestContext,
+    ) -> Result<(Sender<T>, Streaming<T>), ClientError> {
         let mut hdrs = HeaderMap::new();
         hdrs.append(header::CONTENT_TYPE, consts::HDRV_CT_GRPC);
         hdrs.append(header::USER_AGENT, consts::HDRV_USER_AGENT);
         hdrs.insert(header::TE, consts::HDRV_TRAILERS);
-        hdrs.insert(consts::GRPC_ENCODING, consts::IDENTITY);
-        hdrs.insert(consts::GRPC_ACCEPT_ENCODING, consts::IDENTITY);
-        for (key, val) in ctx.headers() {
-            hdrs.insert(key.clone(), val.clone())
+        let encoding = ctx.get_compression().unwrap_or(self.compression);
+        let encoder = self.compressors.get(encoding).ok_or_else(|| {
+            ClientError::Compression(format!(
+                "Compression encoding is not registered: {}",
+                encoding.as_str()
+            ))
+        })?;
+        hdrs.insert(consts::GRPC_ENCODING, encoding.to_header());
+        hdrs.insert(
+            consts::GRPC_ACCEPT_ENCODING,
+            self.compressors.accept_encoding(),
+        );
+        let deadline = ctx.get_deadline();
+        if let Some(deadline) = deadline {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                return Err(ClientError::deadline_exceeded());
+            }
+            hdrs.insert(consts::GRPC_TIMEOUT, utils::encode_timeout(timeout));
         }
+        ctx.apply_headers(&mut hdrs);
 
-        // send request
-        let (snd_stream, rcv_stream) = self.0.send(Method::POST, T::PATH, hdrs, false).await?;
-        snd_stream.send_payload(buf.freeze(), true).await?;
-
-        // read response
-        let mut status = None;
-        let mut hdrs = HeaderMap::default();
-        let mut trailers = HeaderMap::default();
-        let mut payload = Data::Empty;
-
-        loop {
-            let msg = if let Some(msg) = rcv_stream.recv().await {
-                msg
-            } else {
-                return Err(ClientError::UnexpectedEof(status, hdrs));
-            };
+        let wait_for_ready = ctx.is_wait_for_ready();
+        let (snd_stream, rcv_stream) = loop {
+            let _attempt = self.connection.ready(wait_for_ready, deadline).await?;
 
-            match msg.kind {
-                h2::MessageKind::Headers {
-                    headers,
-                    pseudo,
-                    eof,
-                } => {
-                    if eof {
-                        // check grpc status
-                        match check_grpc_status(&headers) {
-                            Some(Ok(status)) => {
-                                if status != GrpcStatus::Ok {
-                                    return Err(ClientError::GrpcStatus(status, headers));
-                                }
-                            }
-                            Some(Err(())) => {
-                                return Err(ClientError::Decode(DecodeError::new(
-                                    "Cannot parse grpc status",
-                                )));
-                            }
-                            None => {}
-                        }
-
-                        return Err(ClientError::UnexpectedEof(pseudo.status, headers));
-                    } else {
-                        hdrs = headers;
-                        status = pseudo.status;
-                    }
-                    continue;
-                }
-                h2::MessageKind::Data(data, _cap) => {
-                    payload.push(data);
-                    continue;
+            match self
+                .client
+                .send(Method::POST, T::PATH, hdrs.clone(), false)
+                .await
+            {
+                Ok(res) => {
+                    self.connection.connected();
+                    break res;
                 }
-                h2::MessageKind::Eof(data) => {
-                    match data {
-                        h2::StreamEof::Data(data) => {
-                            payload.push(data);
-                        }
-                        h2::StreamEof::Trailers(hdrs) => {
-                            // check grpc status
-                            match check_grpc_status(&hdrs) {
-                                Some(Ok(status)) => {
-                                    if status != GrpcStatus::Ok {
-                                        return Err(ClientError::GrpcStatus(status, hdrs));
-                                    }
-                                }
-                                Some(Err(())) => {
-                                    return Err(ClientError::Decode(DecodeError::new(
-                                        "Cannot parse grpc status",
-                                    )));
-                                }
-                                None => {}
-                            }
-                            trailers = hdrs;
-                        }
-                        h2::StreamEof::Error(err) => return Err(ClientError::Stream(err)),
-                    };
-                }
-                h2::MessageKind::Disconnect(err) => return Err(ClientError::Operation(err)),
-            }
-
-            let mut data = payload.get();
-            match status {
-                Some(st) => {
-                    if !st.is_success() {
-                        return Err(ClientError::Response(Some(st), hdrs, data));
+                Err(err) => {
+                    let err = ClientError::from(err);
+                    self.connection.failed(&err);
+                    if !wait_for_ready {
+                        return Err(err);
                     }
                 }
-                None => return Err(ClientError::Response(None, hdrs, data)),
             }
-            let _compressed = data.get_u8();
-            let len = data.get_u32();
-            let mut block = if let Some(b) = data.split_to_checked(len as usize) {
-                b
-            } else {
-                return Err(ClientError::UnexpectedEof(None, hdrs));
-            };
-
-            return match <T::Output as Message>::read(&mut block) {
-                Ok(output) => Ok(Response {
-                    output,
-                    trailers,
-                    req_size,
-                    headers: hdrs,
-                    res_size: data.len(),
-                }),
-                Err(e) => Err(ClientError::Decode(e)),
-            };
-        }
-    }
-}
-
-fn check_grpc_status(hdrs: &HeaderMap) -> Option<Result<GrpcStatus, ()>> {
-    // check grpc status
-    if let Some(val) = hdrs.get(consts::GRPC_STATUS) {
-        if let Ok(status) = val
-            .to_str()
-            .map_err(|_| ())
-            .and_then(|v| u8::from_str(v).map_err(|_| ()))
-            .and_then(GrpcStatus::try_from)
-        {
-            Some(Ok(status))
-        } else {
-            Some(Err(()))
-        }
-    } else {
-        None
+        };
+        Ok((
+            Sender::new(snd_stream, encoder, self.max_send_message_size),
+            Streaming::new(
+                rcv_stream,
+                self.connection.clone(),
+                self.compressors.clone(),
+                deadline,
+                self.max_recv_message_size,
+            ),
+        ))
     }
 }
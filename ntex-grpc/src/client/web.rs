@@ -0,0 +1,305 @@
+use std::{fmt, time::Instant};
+
+use ntex::http::client::{error::SendRequestError, Client as HttpClient, ClientResponse};
+use ntex::http::Uri;
+use ntex_bytes::BytesMut;
+use ntex_http::header;
+use ntex_util::{future::stream_recv, time};
+
+use crate::compression::{CompressionEncoding, Compressors};
+use crate::status::{GrpcStatus, Status};
+use crate::web::{self, TextDecoder, HDRV_CT_GRPC_WEB, HDRV_CT_GRPC_WEB_TEXT};
+use crate::{consts, service::MethodDef, utils, utils::Data, Message};
+
+use super::request::{RequestContext, Response};
+use super::streaming::{check_grpc_status, decode_message};
+use super::{ClientError, Transport};
+
+/// gRPC-Web transport
+///
+/// Sends unary calls as `application/grpc-web` requests over ntex http client,
+/// trailers are decoded from the last frame of response body. Can be used with
+/// gRPC-Web gateways and with servers behind HTTP/1 proxies.
+///
+/// Streaming calls are not supported. Client-wide timeout of http client applies
+/// to calls without deadline.
+///
+/// ```rust,ignore
+/// let client = WebClient::new(HttpClient::new(), "http://127.0.0.1:8080")?;
+/// let res = GreeterClient::new(client).say_hello(&req).await?;
+/// ```
+#[derive(Clone)]
+pub struct WebClient {
+    client: HttpClient,
+    address: String,
+    text: bool,
+    compression: CompressionEncoding,
+    compressors: Compressors,
+    max_recv_message_size: usize,
+    max_send_message_size: usize,
+}
+
+impl WebClient {
+    /// Create gRPC-Web transport for server address, `http://host:port`
+    pub fn new(client: HttpClient, address: &str) -> Result<Self, ClientError> {
+        let uri = address
+            .parse::<Uri>()
+            .map_err(|e| ClientError::Target(format!("{}: {}", address, e)))?;
+        if uri.scheme().is_none() || uri.host().is_none() {
+            return Err(ClientError::Target(format!(
+                "{}: scheme and host are required",
+                address
+            )));
+        }
+
+        Ok(Self {
+            client,
+            address: address.trim_end_matches('/').to_string(),
+            text: false,
+            compression: CompressionEncoding::Identity,
+            compressors: Compressors::default(),
+            max_recv_message_size: consts::DEFAULT_MAX_MESSAGE_SIZE,
+            max_send_message_size: consts::DEFAULT_MAX_MESSAGE_SIZE,
+        })
+    }
+
+    /// Use `application/grpc-web-text` content type
+    ///
+    /// Request and response bodies are base64 encoded.
+    pub fn text(mut self) -> Self {
+        self.text = true;
+        self
+    }
+
+    /// Set registry of supported compressors
+    ///
+    /// All registered encodings are advertised in `grpc-accept-encoding` header.
+    /// By default registry is empty and messages are not compressed.
+    pub fn compressors(mut self, compressors: Compressors) -> Self {
+        self.compressors = compressors;
+        self
+    }
+
+    /// Set compression encoding for request messages
+    ///
+    /// By default messages are not compressed.
+    pub fn compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.compression = encoding;
+        self
+    }
+
+    /// Set max size of received message
+    ///
+    /// Call fails with `ResourceExhausted` status if response message is larger.
+    /// By default max size is 4Mb.
+    pub fn max_recv_message_size(mut self, size: usize) -> Self {
+        self.max_recv_message_size = size;
+        self
+    }
+
+    /// Set max size of sent message
+    ///
+    /// Call fails with `ResourceExhausted` status if request message is larger.
+    /// By default max size is 4Mb.
+    pub fn max_send_message_size(mut self, size: usize) -> Self {
+        self.max_send_message_size = size;
+        self
+    }
+
+    /// Get reference to http client
+    pub fn get_ref(&self) -> &HttpClient {
+        &self.client
+    }
+
+    async fn call<T: MethodDef>(
+        &self,
+        val: &T::Input,
+        ctx: RequestContext,
+    ) -> Result<Response<T>, ClientError> {
+        let encoding = ctx.get_compression().unwrap_or(self.compression);
+        let encoder = self.compressors.get(encoding).ok_or_else(|| {
+            ClientError::Compression(format!(
+                "Compression encoding is not registered: {}",
+                encoding.as_str()
+            ))
+        })?;
+
+        // encode request
+        let mut buf = BytesMut::with_capacity(val.encoded_len());
+        val.write(&mut buf);
+        if buf.len() > self.max_send_message_size {
+            return Err(utils::send_size_exceeded(buf.len(), self.max_send_message_size).into());
+        }
+        let mut data = BytesMut::new();
+        encoder
+            .encode(&buf, &mut data)
+            .map_err(|e| ClientError::Compression(e.to_string()))?;
+        let req_size = data.len();
+        let body = if self.text {
+            web::encode_text(&data)
+        } else {
+            data.freeze()
+        };
+
+        let mut req = self.client.post(format!("{}{}", self.address, T::PATH));
+        let hdrs = req.headers_mut();
+        let ct = if self.text {
+            HDRV_CT_GRPC_WEB_TEXT
+        } else {
+            HDRV_CT_GRPC_WEB
+        };
+        hdrs.insert(header::CONTENT_TYPE, ct.clone());
+        hdrs.insert(header::ACCEPT, ct);
+        hdrs.insert(header::USER_AGENT, consts::HDRV_USER_AGENT);
+        hdrs.insert(consts::GRPC_ENCODING, encoding.to_header());
+        hdrs.insert(
+            consts::GRPC_ACCEPT_ENCODING,
+            self.compressors.accept_encoding(),
+        );
+        let timeout = if let Some(deadline) = ctx.get_deadline() {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                return Err(ClientError::deadline_exceeded());
+            }
+            hdrs.insert(consts::GRPC_TIMEOUT, utils::encode_timeout(timeout));
+            Some(timeout)
+        } else {
+            None
+        };
+        ctx.apply_headers(hdrs);
+
+        if let Some(timeout) = timeout {
+            // request timeout overrides client-wide timeout
+            let fut = async {
+                match req.timeout(timeout).send_body(body).await {
+                    Ok(res) => self.response(res, req_size).await,
+                    Err(SendRequestError::Timeout) => Err(ClientError::deadline_exceeded()),
+                    Err(err) => Err(unavailable(err)),
+                }
+            };
+            time::timeout(timeout, fut)
+                .await
+                .unwrap_or_else(|_| Err(ClientError::deadline_exceeded()))
+        } else {
+            let res = req.send_body(body).await.map_err(unavailable)?;
+            self.response(res, req_size).await
+        }
+    }
+
+    /// Read response messages and trailers frame
+    async fn response<T: MethodDef>(
+        &self,
+        mut res: ClientResponse,
+        req_size: usize,
+    ) -> Result<Response<T>, ClientError> {
+        let status = res.status();
+        let headers = res.headers().clone();
+        if !status.is_success() {
+            let body = res
+                .body()
+                .limit(self.max_recv_message_size)
+                .await
+                .unwrap_or_default();
+            return Err(ClientError::Response(Some(status), headers, body));
+        }
+
+        // trailers-only response
+        check_grpc_status(&headers)?;
+
+        let mut text = web::is_grpc_web(headers.get(header::CONTENT_TYPE))
+            .unwrap_or(self.text)
+            .then(TextDecoder::default);
+        let decoder = self.compressors.decoder(headers.get(consts::GRPC_ENCODING));
+        let max_size = self.max_recv_message_size;
+
+        let mut output = None;
+        let mut trailers = None;
+        let mut res_size = 0;
+        let mut data = Data::Empty;
+        let mut payload = res.take_payload();
+        while let Some(chunk) = stream_recv(&mut payload).await {
+            let chunk = chunk.map_err(unavailable)?;
+            let chunk = if let Some(ref mut text) = text {
+                text.decode(&chunk)?
+            } else {
+                chunk
+            };
+            data.push(chunk);
+
+            while let Some((flags, msg)) = data.next_frame(max_size)? {
+                match flags {
+                    web::TRAILERS_FLAG => trailers = Some(web::decode_trailers(&msg)?),
+                    0 | 1 if output.is_some() => {
+                        return Err(Status::new(
+                            GrpcStatus::Internal,
+                            "Too many response messages for unary call",
+                        )
+                        .into())
+                    }
+                    0 | 1 => {
+                        res_size += msg.len() + 5;
+                        let mut msg = decode_message(flags == 1, msg, decoder.as_ref(), max_size)?;
+                        if msg.len() > max_size {
+                            return Err(utils::decompressed_size_exceeded(max_size).into());
+                        }
+                        output = Some(<T::Output as Message>::read(&mut msg)?);
+                    }
+                    _ => {
+                        return Err(Status::new(
+                            GrpcStatus::Internal,
+                            format!("Unsupported frame flags: {:#04x}", flags),
+                        )
+                        .into())
+                    }
+                }
+            }
+        }
+
+        let incomplete = data.len() != 0 || text.map(|t| !t.is_empty()).unwrap_or(false);
+        let trailers = match trailers {
+            Some(trailers) if !incomplete => trailers,
+            _ => return Err(ClientError::UnexpectedEof(Some(status), headers)),
+        };
+        check_grpc_status(&trailers)?;
+
+        if let Some(output) = output {
+            Ok(Response {
+                output,
+                headers,
+                trailers,
+                req_size,
+                res_size,
+            })
+        } else {
+            Err(ClientError::UnexpectedEof(Some(status), headers))
+        }
+    }
+}
+
+impl fmt::Debug for WebClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebClient")
+            .field("address", &self.address)
+            .field("text", &self.text)
+            .field("compression", &self.compression)
+            .finish()
+    }
+}
+
+impl<T: MethodDef> Transport<T> for WebClient {
+    type Error = ClientError;
+
+    #[inline]
+    async fn request(
+        &self,
+        val: &T::Input,
+        ctx: RequestContext,
+    ) -> Result<Response<T>, Self::Error> {
+        self.call(val, ctx).await
+    }
+}
+
+/// Transport failures are reported with `Unavailable` status, calls could be retried
+fn unavailable<E: fmt::Display>(err: E) -> ClientError {
+    Status::new(GrpcStatus::Unavailable, err.to_string()).into()
+}
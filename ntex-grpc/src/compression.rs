@@ -0,0 +1,358 @@
+//! Message compression
+use std::{fmt, io, io::Read, io::Write, rc::Rc};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use ntex_bytes::{BufMut, Bytes, BytesMut};
+use ntex_http::HeaderValue;
+
+use crate::DecodeError;
+
+/// Message compression encoding
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CompressionEncoding {
+    /// Messages are not compressed
+    #[default]
+    Identity,
+    /// Gzip compression
+    Gzip,
+    /// Deflate (zlib) compression
+    Deflate,
+    /// Zstd compression
+    Zstd,
+    /// Custom compression encoding
+    Custom(&'static str),
+}
+
+impl CompressionEncoding {
+    /// Encoding name, as used in `grpc-encoding` header
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompressionEncoding::Identity => "identity",
+            CompressionEncoding::Gzip => "gzip",
+            CompressionEncoding::Deflate => "deflate",
+            CompressionEncoding::Zstd => "zstd",
+            CompressionEncoding::Custom(name) => name,
+        }
+    }
+
+    /// Header value of the encoding
+    ///
+    /// Custom encoding names are validated by [`Compressors::register`].
+    pub(crate) fn to_header(self) -> HeaderValue {
+        HeaderValue::from_static(self.as_str())
+    }
+
+    /// Check that custom encoding name could be used in headers
+    fn validate(&self) -> Result<(), InvalidEncoding> {
+        if let CompressionEncoding::Custom(name) = *self {
+            let reserved = [
+                CompressionEncoding::Identity,
+                CompressionEncoding::Gzip,
+                CompressionEncoding::Deflate,
+                CompressionEncoding::Zstd,
+            ];
+            if name.is_empty()
+                || !name.bytes().all(is_token_char)
+                || reserved.iter().any(|enc| enc.as_str() == name)
+            {
+                return Err(InvalidEncoding(name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Header token character, as defined by RFC 9110
+fn is_token_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+/// Invalid compression encoding name
+///
+/// Custom encoding name must be a valid header token and must not be
+/// the name of a built-in encoding.
+#[derive(thiserror::Error, Copy, Clone, Debug, PartialEq, Eq)]
+#[error("Invalid compression encoding name: {0:?}")]
+pub struct InvalidEncoding(pub &'static str);
+
+/// Message compressor
+pub trait Compressor {
+    /// Compression encoding
+    fn encoding(&self) -> CompressionEncoding;
+
+    /// Compress message
+    fn compress(&self, src: &[u8], dst: &mut BytesMut) -> io::Result<()>;
+
+    /// Decompress message
+    ///
+    /// Decompressed message must not be larger than `limit`, implementation
+    /// stops decompression as soon as output exceeds the limit. Message
+    /// that exceeds the limit is rejected with `ResourceExhausted` status.
+    fn decompress(&self, src: &[u8], dst: &mut BytesMut, limit: usize) -> io::Result<()>;
+}
+
+/// Gzip compressor
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GzipCompressor;
+
+impl Compressor for GzipCompressor {
+    fn encoding(&self) -> CompressionEncoding {
+        CompressionEncoding::Gzip
+    }
+
+    fn compress(&self, src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        let mut enc = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        enc.write_all(src)?;
+        dst.extend_from_slice(&enc.finish()?);
+        Ok(())
+    }
+
+    fn decompress(&self, src: &[u8], dst: &mut BytesMut, limit: usize) -> io::Result<()> {
+        read_limited(GzDecoder::new(src), dst, limit)
+    }
+}
+
+/// Deflate compressor
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DeflateCompressor;
+
+impl Compressor for DeflateCompressor {
+    fn encoding(&self) -> CompressionEncoding {
+        CompressionEncoding::Deflate
+    }
+
+    fn compress(&self, src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        let mut enc = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        enc.write_all(src)?;
+        dst.extend_from_slice(&enc.finish()?);
+        Ok(())
+    }
+
+    fn decompress(&self, src: &[u8], dst: &mut BytesMut, limit: usize) -> io::Result<()> {
+        read_limited(ZlibDecoder::new(src), dst, limit)
+    }
+}
+
+#[cfg(feature = "zstd")]
+/// Zstd compressor
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ZstdCompressor;
+
+#[cfg(feature = "zstd")]
+impl Compressor for ZstdCompressor {
+    fn encoding(&self) -> CompressionEncoding {
+        CompressionEncoding::Zstd
+    }
+
+    fn compress(&self, src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        dst.extend_from_slice(&zstd::stream::encode_all(src, 0)?);
+        Ok(())
+    }
+
+    fn decompress(&self, src: &[u8], dst: &mut BytesMut, limit: usize) -> io::Result<()> {
+        read_limited(zstd::stream::read::Decoder::new(src)?, dst, limit)
+    }
+}
+
+/// Read decompressed data, stops after `limit + 1` bytes
+///
+/// Extra byte lets the caller detect message that exceeds the limit.
+fn read_limited<R: Read>(reader: R, dst: &mut BytesMut, limit: usize) -> io::Result<()> {
+    let mut buf = Vec::new();
+    reader.take(limit as u64 + 1).read_to_end(&mut buf)?;
+    dst.extend_from_slice(&buf);
+    Ok(())
+}
+
+/// Registry of supported compressors
+///
+/// Default registry is empty, messages are not compressed and compressed
+/// messages are rejected. Compressors have to be registered explicitly.
+///
+/// ```rust
+/// use ntex_grpc::compression::{Compressors, DeflateCompressor, GzipCompressor};
+///
+/// # fn main() -> Result<(), ntex_grpc::compression::InvalidEncoding> {
+/// let compressors = Compressors::new()
+///     .register(GzipCompressor)?
+///     .register(DeflateCompressor)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Compressors {
+    items: Vec<Rc<dyn Compressor>>,
+    accept: HeaderValue,
+    min_size: usize,
+}
+
+impl Compressors {
+    /// Create empty registry, messages are not compressed
+    pub fn new() -> Self {
+        Compressors {
+            items: Vec::new(),
+            accept: CompressionEncoding::Identity.to_header(),
+            min_size: 0,
+        }
+    }
+
+    /// Register compressor
+    ///
+    /// Replaces compressor with the same encoding. Fails if name of
+    /// custom encoding is not valid.
+    pub fn register<C: Compressor + 'static>(
+        mut self,
+        compressor: C,
+    ) -> Result<Self, InvalidEncoding> {
+        let encoding = compressor.encoding();
+        encoding.validate()?;
+
+        self.items.retain(|c| c.encoding() != encoding);
+        self.items.push(Rc::new(compressor));
+
+        let mut accept: Vec<_> = self.items.iter().map(|c| c.encoding().as_str()).collect();
+        accept.push(CompressionEncoding::Identity.as_str());
+        self.accept = HeaderValue::from_str(&accept.join(",")).unwrap();
+        Ok(self)
+    }
+
+    /// Set minimum message size for compression
+    ///
+    /// Messages smaller than `size` are sent uncompressed. By default is 0.
+    pub fn min_size(mut self, size: usize) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    /// Value for `grpc-accept-encoding` header
+    pub(crate) fn accept_encoding(&self) -> HeaderValue {
+        self.accept.clone()
+    }
+
+    /// Get encoder for encoding, returns `None` if encoding is not registered
+    pub(crate) fn get(&self, encoding: CompressionEncoding) -> Option<Encoder> {
+        self.find(encoding.as_str().as_bytes())
+    }
+
+    /// Get encoder for `grpc-encoding` header value
+    pub(crate) fn decoder(&self, val: Option<&HeaderValue>) -> Option<Encoder> {
+        match val {
+            Some(val) => self.find(val.as_bytes()),
+            None => Some(Encoder::identity()),
+        }
+    }
+
+    /// Select encoder from peer's `grpc-accept-encoding` list
+    pub(crate) fn select(&self, accept: Option<&HeaderValue>) -> Encoder {
+        accept
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| {
+                val.split(',')
+                    .filter_map(|name| self.find(name.trim().as_bytes()))
+                    .next()
+            })
+            .unwrap_or_else(Encoder::identity)
+    }
+
+    fn find(&self, name: &[u8]) -> Option<Encoder> {
+        if name == CompressionEncoding::Identity.as_str().as_bytes() {
+            Some(Encoder::identity())
+        } else {
+            self.items
+                .iter()
+                .find(|c| c.encoding().as_str().as_bytes() == name)
+                .map(|c| Encoder {
+                    compressor: Some(c.clone()),
+                    min_size: self.min_size,
+                })
+        }
+    }
+}
+
+impl Default for Compressors {
+    fn default() -> Self {
+        Compressors::new()
+    }
+}
+
+impl fmt::Debug for Compressors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Compressors")
+            .field("accept", &self.accept)
+            .field("min_size", &self.min_size)
+            .finish()
+    }
+}
+
+/// Message encoder for selected compression encoding
+#[derive(Clone)]
+pub(crate) struct Encoder {
+    compressor: Option<Rc<dyn Compressor>>,
+    min_size: usize,
+}
+
+impl Encoder {
+    pub(crate) fn identity() -> Self {
+        Encoder {
+            compressor: None,
+            min_size: 0,
+        }
+    }
+
+    pub(crate) fn encoding(&self) -> CompressionEncoding {
+        self.compressor
+            .as_ref()
+            .map(|c| c.encoding())
+            .unwrap_or_default()
+    }
+
+    /// Write length-prefixed message to the buffer
+    pub(crate) fn encode(&self, payload: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+        match self.compressor {
+            Some(ref compressor) if payload.len() >= self.min_size => {
+                let start = buf.len();
+                buf.put_u8(1); // compression
+                buf.put_u32(0); // length
+                compressor.compress(payload, buf)?;
+
+                let len = (buf.len() - start - 5) as u32;
+                buf[start + 1..start + 5].copy_from_slice(&len.to_be_bytes());
+            }
+            _ => {
+                buf.reserve(payload.len() + 5);
+                buf.put_u8(0); // compression
+                buf.put_u32(payload.len() as u32); // length
+                buf.extend_from_slice(payload);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decompress message if compressed flag is set
+    ///
+    /// Decompression stops once message exceeds `max_size`, caller
+    /// rejects such message.
+    pub(crate) fn decode(
+        &self,
+        compressed: bool,
+        msg: Bytes,
+        max_size: usize,
+    ) -> Result<Bytes, DecodeError> {
+        if !compressed {
+            return Ok(msg);
+        }
+
+        if let Some(ref compressor) = self.compressor {
+            let mut buf = BytesMut::new();
+            compressor
+                .decompress(&msg, &mut buf, max_size)
+                .map_err(|e| DecodeError::new(format!("Cannot decompress message: {}", e)))?;
+            Ok(buf.freeze())
+        } else {
+            Err(DecodeError::new(
+                "Compressed message received without grpc-encoding",
+            ))
+        }
+    }
+}
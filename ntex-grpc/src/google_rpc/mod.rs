@@ -0,0 +1,34 @@
+//! Error details types of `google.rpc` package
+mod rpc;
+
+pub use self::rpc::*;
+pub use crate::google_types::Any;
+
+/// Message that could be used as status detail
+pub trait StatusDetail: crate::Message {
+    /// Type url of the message
+    const TYPE_URL: &'static str;
+}
+
+macro_rules! status_detail {
+    ($($name:ident),+) => {
+        $(
+            impl StatusDetail for $name {
+                const TYPE_URL: &'static str = concat!("type.googleapis.com/google.rpc.", stringify!($name));
+            }
+        )+
+    };
+}
+
+status_detail!(
+    ErrorInfo,
+    RetryInfo,
+    DebugInfo,
+    QuotaFailure,
+    PreconditionFailure,
+    BadRequest,
+    RequestInfo,
+    ResourceInfo,
+    Help,
+    LocalizedMessage
+);
@@ -0,0 +1,1025 @@
+#![allow(
+    dead_code,
+    unused_mut,
+    unused_variables,
+    clippy::identity_op,
+    clippy::derivable_impls,
+    clippy::unit_arg,
+    clippy::derive_partial_eq_without_eq,
+    clippy::manual_range_patterns
+)]
+// Types of `google/rpc/status.proto` and `google/rpc/error_details.proto`
+// from googleapis repository, code follows output of ntex-grpc code generator.
+
+///  The `Status` type defines a logical error model that is suitable for
+///  different programming environments, including REST APIs and RPC APIs.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Status {
+    ///  The status code, which should be an enum value of `google.rpc.Code`.
+    pub code: i32,
+    ///  A developer-facing error message, which should be in English.
+    pub message: crate::ByteString,
+    ///  A list of messages that carry the error details.
+    pub details: Vec<crate::google_types::Any>,
+}
+
+///  Describes the cause of the error with structured details.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ErrorInfo {
+    ///  The reason of the error.
+    pub reason: crate::ByteString,
+    ///  The logical grouping to which the "reason" belongs.
+    pub domain: crate::ByteString,
+    ///  Additional structured details about this error.
+    pub metadata: crate::HashMap<crate::ByteString, crate::ByteString>,
+}
+
+///  Describes when the clients can retry a failed request.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RetryInfo {
+    ///  Clients should wait at least this long between retrying the same request.
+    pub retry_delay: crate::google_types::Duration,
+}
+
+///  Describes additional debugging info.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DebugInfo {
+    ///  The stack trace entries indicating where the error occurred.
+    pub stack_entries: Vec<crate::ByteString>,
+    ///  Additional debugging information provided by the server.
+    pub detail: crate::ByteString,
+}
+
+///  Describes how a quota check failed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct QuotaFailure {
+    ///  Describes all quota violations.
+    pub violations: Vec<quota_failure::Violation>,
+}
+
+/// Nested message and enum types in `QuotaFailure`.
+pub mod quota_failure {
+    ///  A message type used to describe a single quota violation.
+    #[derive(Clone, PartialEq, Debug)]
+    pub struct Violation {
+        ///  The subject on which the quota check failed.
+        pub subject: crate::ByteString,
+        ///  A description of how the quota check failed.
+        pub description: crate::ByteString,
+    }
+}
+
+///  Describes what preconditions have failed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PreconditionFailure {
+    ///  Describes all precondition violations.
+    pub violations: Vec<precondition_failure::Violation>,
+}
+
+/// Nested message and enum types in `PreconditionFailure`.
+pub mod precondition_failure {
+    ///  A message type used to describe a single precondition failure.
+    #[derive(Clone, PartialEq, Debug)]
+    pub struct Violation {
+        ///  The type of PreconditionFailure.
+        pub r#type: crate::ByteString,
+        ///  The subject, relative to the type, that failed.
+        pub subject: crate::ByteString,
+        ///  A description of how the precondition failed.
+        pub description: crate::ByteString,
+    }
+}
+
+///  Describes violations in a client request.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BadRequest {
+    ///  Describes all violations in a client request.
+    pub field_violations: Vec<bad_request::FieldViolation>,
+}
+
+/// Nested message and enum types in `BadRequest`.
+pub mod bad_request {
+    ///  A message type used to describe a single bad request field.
+    #[derive(Clone, PartialEq, Debug)]
+    pub struct FieldViolation {
+        ///  A path that leads to a field in the request body.
+        pub field: crate::ByteString,
+        ///  A description of why the request element is bad.
+        pub description: crate::ByteString,
+    }
+}
+
+///  Contains metadata about the request that clients can attach when filing a bug
+///  or providing other forms of feedback.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RequestInfo {
+    ///  An opaque string that should only be interpreted by the service generating
+    ///  it.
+    pub request_id: crate::ByteString,
+    ///  Any data that was used to serve this request.
+    pub serving_data: crate::ByteString,
+}
+
+///  Describes the resource that is being accessed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ResourceInfo {
+    ///  A name for the type of resource being accessed.
+    pub resource_type: crate::ByteString,
+    ///  The name of the resource being accessed.
+    pub resource_name: crate::ByteString,
+    ///  The owner of the resource (optional).
+    pub owner: crate::ByteString,
+    ///  Describes what error is encountered when accessing this resource.
+    pub description: crate::ByteString,
+}
+
+///  Provides links to documentation or for performing an out of band action.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Help {
+    ///  URL(s) pointing to additional information on handling the current error.
+    pub links: Vec<help::Link>,
+}
+
+/// Nested message and enum types in `Help`.
+pub mod help {
+    ///  Describes a URL link.
+    #[derive(Clone, PartialEq, Debug)]
+    pub struct Link {
+        ///  Describes what the link offers.
+        pub description: crate::ByteString,
+        ///  The URL of the link.
+        pub url: crate::ByteString,
+    }
+}
+
+///  Provides a localized error message that is safe to return to the user.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LocalizedMessage {
+    ///  The locale used following the specification defined at
+    ///  <https://www.rfc-editor.org/rfc/rfc5646.>
+    pub locale: crate::ByteString,
+    ///  The localized error message in the above locale.
+    pub message: crate::ByteString,
+}
+
+mod _priv_impl {
+    use super::*;
+
+    impl crate::Message for Status {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.code, 1, crate::types::DefaultValue::Default, dst);
+            crate::NativeType::serialize(
+                &self.message,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.details,
+                3,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "Status";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.code, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "code"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.message, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "message"))?,
+                    3 => crate::NativeType::deserialize(&mut msg.details, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "details"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.code,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.message,
+                2,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.details,
+                3,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for Status {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                code: ::core::default::Default::default(),
+                message: ::core::default::Default::default(),
+                details: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for ErrorInfo {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.reason,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.domain,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.metadata,
+                3,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ErrorInfo";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.reason, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "reason"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.domain, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "domain"))?,
+                    3 => crate::NativeType::deserialize(&mut msg.metadata, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "metadata"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.reason,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.domain,
+                2,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.metadata,
+                3,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ErrorInfo {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                reason: ::core::default::Default::default(),
+                domain: ::core::default::Default::default(),
+                metadata: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for RetryInfo {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.retry_delay,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "RetryInfo";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.retry_delay, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "retry_delay"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.retry_delay,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for RetryInfo {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                retry_delay: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for DebugInfo {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.stack_entries,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.detail,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "DebugInfo";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => {
+                        crate::NativeType::deserialize(&mut msg.stack_entries, tag, wire_type, src)
+                            .map_err(|err| err.push(STRUCT_NAME, "stack_entries"))?
+                    }
+                    2 => crate::NativeType::deserialize(&mut msg.detail, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "detail"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.stack_entries,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.detail,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for DebugInfo {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                stack_entries: ::core::default::Default::default(),
+                detail: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for QuotaFailure {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.violations,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "QuotaFailure";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.violations, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "violations"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.violations,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for QuotaFailure {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                violations: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for quota_failure::Violation {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.subject,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.description,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "Violation";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.subject, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "subject"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.description, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "description"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.subject,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.description,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for quota_failure::Violation {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                subject: ::core::default::Default::default(),
+                description: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for PreconditionFailure {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.violations,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "PreconditionFailure";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.violations, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "violations"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.violations,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for PreconditionFailure {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                violations: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for precondition_failure::Violation {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.r#type,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.subject,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.description,
+                3,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "Violation";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.r#type, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "r#type"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.subject, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "subject"))?,
+                    3 => crate::NativeType::deserialize(&mut msg.description, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "description"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.r#type,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.subject,
+                2,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.description,
+                3,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for precondition_failure::Violation {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                r#type: ::core::default::Default::default(),
+                subject: ::core::default::Default::default(),
+                description: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for BadRequest {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.field_violations,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "BadRequest";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(
+                        &mut msg.field_violations,
+                        tag,
+                        wire_type,
+                        src,
+                    )
+                    .map_err(|err| err.push(STRUCT_NAME, "field_violations"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.field_violations,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for BadRequest {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                field_violations: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for bad_request::FieldViolation {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.field, 1, crate::types::DefaultValue::Default, dst);
+            crate::NativeType::serialize(
+                &self.description,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "FieldViolation";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.field, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "field"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.description, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "description"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.field,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.description,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for bad_request::FieldViolation {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                field: ::core::default::Default::default(),
+                description: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for RequestInfo {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.request_id,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.serving_data,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "RequestInfo";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.request_id, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "request_id"))?,
+                    2 => {
+                        crate::NativeType::deserialize(&mut msg.serving_data, tag, wire_type, src)
+                            .map_err(|err| err.push(STRUCT_NAME, "serving_data"))?
+                    }
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.request_id,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.serving_data,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for RequestInfo {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                request_id: ::core::default::Default::default(),
+                serving_data: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for ResourceInfo {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.resource_type,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.resource_name,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(&self.owner, 3, crate::types::DefaultValue::Default, dst);
+            crate::NativeType::serialize(
+                &self.description,
+                4,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ResourceInfo";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => {
+                        crate::NativeType::deserialize(&mut msg.resource_type, tag, wire_type, src)
+                            .map_err(|err| err.push(STRUCT_NAME, "resource_type"))?
+                    }
+                    2 => {
+                        crate::NativeType::deserialize(&mut msg.resource_name, tag, wire_type, src)
+                            .map_err(|err| err.push(STRUCT_NAME, "resource_name"))?
+                    }
+                    3 => crate::NativeType::deserialize(&mut msg.owner, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "owner"))?,
+                    4 => crate::NativeType::deserialize(&mut msg.description, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "description"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.resource_type,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.resource_name,
+                2,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.owner,
+                3,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.description,
+                4,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ResourceInfo {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                resource_type: ::core::default::Default::default(),
+                resource_name: ::core::default::Default::default(),
+                owner: ::core::default::Default::default(),
+                description: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for Help {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.links, 1, crate::types::DefaultValue::Default, dst);
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "Help";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.links, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "links"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.links,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for Help {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                links: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for help::Link {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.description,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(&self.url, 2, crate::types::DefaultValue::Default, dst);
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "Link";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.description, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "description"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.url, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "url"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.description,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.url,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for help::Link {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                description: ::core::default::Default::default(),
+                url: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for LocalizedMessage {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.locale,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.message,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "LocalizedMessage";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.locale, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "locale"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.message, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "message"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.locale,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.message,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for LocalizedMessage {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                locale: ::core::default::Default::default(),
+                message: ::core::default::Default::default(),
+            }
+        }
+    }
+}
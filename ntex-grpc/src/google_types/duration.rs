@@ -7,7 +7,7 @@
     clippy::unit_arg,
     clippy::derive_partial_eq_without_eq
 )]
-/// DO NOT MODIFY. Auto-generated file
+// DO NOT MODIFY. Auto-generated file
 
 ///  A Duration represents a signed, fixed-length span of time represented
 ///  as a count of seconds and fractions of seconds at nanosecond
@@ -20,6 +20,7 @@
 ///
 ///  Example 1: Compute Duration from two Timestamps in pseudo code.
 ///
+/// ```text
 ///      Timestamp start = ...;
 ///      Timestamp end = ...;
 ///      Duration duration = ...;
@@ -34,9 +35,11 @@
 ///        duration.seconds -= 1;
 ///        duration.nanos += 1000000000;
 ///      }
+/// ```
 ///
 ///  Example 2: Compute Timestamp from Timestamp + Duration in pseudo code.
 ///
+/// ```text
 ///      Timestamp start = ...;
 ///      Duration duration = ...;
 ///      Timestamp end = ...;
@@ -51,12 +54,15 @@
 ///        end.seconds += 1;
 ///        end.nanos -= 1000000000;
 ///      }
+/// ```
 ///
 ///  Example 3: Compute Duration from datetime.timedelta in Python.
 ///
+/// ```text
 ///      td = datetime.timedelta(days=3, minutes=10)
 ///      duration = Duration()
 ///      duration.FromTimedelta(td)
+/// ```
 ///
 ///  # JSON Mapping
 ///
@@ -1,9 +1,11 @@
+mod any;
 mod duration;
 mod duration_impl;
 mod timestamp;
 mod timestamp_impl;
 mod wrappers;
 
+pub use self::any::Any;
 pub use self::duration::Duration;
 pub use self::timestamp::Timestamp;
 pub use self::wrappers::*;
@@ -0,0 +1,29 @@
+//! Health checking service of `grpc.health.v1` package
+//!
+//! [`HealthServer`] serves serving statuses that application sets with
+//! [`HealthReporter`]. Register it in `Router` to serve it next to
+//! application services.
+//!
+//! ```rust,ignore
+//! let reporter = HealthReporter::new();
+//! reporter.set_serving::<Greeter>();
+//!
+//! Server::build()
+//!     .bind("grpc", "0.0.0.0:50051", move |_| {
+//!         GrpcServer::new(
+//!             Router::new()
+//!                 .service(GreeterServer)
+//!                 .service(HealthServer::new(reporter.clone())),
+//!         )
+//!     })?
+//!     .run()
+//!     .await
+//! ```
+mod reporter;
+mod server;
+mod v1;
+
+pub use self::reporter::HealthReporter;
+pub use self::server::HealthServer;
+pub use self::v1::health_check_response::ServingStatus;
+pub use self::v1::*;
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::{fmt, task::Context, task::Poll, task::Waker};
+
+use crate::ServiceDef;
+
+use super::ServingStatus;
+
+/// Registry of serving statuses
+///
+/// Status is set per service name, empty name is the status of the whole
+/// server. Reporter is cheap to clone and could be shared between server
+/// workers, watch calls are notified about every status change.
+#[derive(Clone)]
+pub struct HealthReporter(Arc<Mutex<Inner>>);
+
+struct Inner {
+    statuses: HashMap<String, ServingStatus>,
+    shutdown: bool,
+    watchers: HashMap<usize, Waker>,
+    next_watcher: usize,
+}
+
+impl Default for HealthReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthReporter {
+    /// Create reporter, server status is set to `Serving`
+    pub fn new() -> Self {
+        let mut statuses = HashMap::new();
+        statuses.insert(String::new(), ServingStatus::Serving);
+
+        Self(Arc::new(Mutex::new(Inner {
+            statuses,
+            shutdown: false,
+            watchers: HashMap::new(),
+            next_watcher: 0,
+        })))
+    }
+
+    fn inner(&self) -> MutexGuard<'_, Inner> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Set serving status of the service
+    pub fn set_serving_status(&self, service: &str, status: ServingStatus) {
+        let mut inner = self.inner();
+        if inner.shutdown {
+            return;
+        }
+        if inner.statuses.get(service) != Some(&status) {
+            log::debug!("Serving status of {:?} is changed to {:?}", service, status);
+            inner.statuses.insert(service.to_string(), status);
+            inner.notify();
+        }
+    }
+
+    /// Set status of service definition to `Serving`
+    pub fn set_serving<S: ServiceDef>(&self) {
+        self.set_serving_status(S::NAME, ServingStatus::Serving);
+    }
+
+    /// Set status of service definition to `NotServing`
+    pub fn set_not_serving<S: ServiceDef>(&self) {
+        self.set_serving_status(S::NAME, ServingStatus::NotServing);
+    }
+
+    /// Remove service, service is reported as unknown
+    pub fn clear_serving_status(&self, service: &str) {
+        let mut inner = self.inner();
+        if !inner.shutdown && inner.statuses.remove(service).is_some() {
+            inner.notify();
+        }
+    }
+
+    /// Get serving status of the service
+    pub fn serving_status(&self, service: &str) -> Option<ServingStatus> {
+        self.inner().statuses.get(service).copied()
+    }
+
+    /// Set status of all services to `NotServing`
+    ///
+    /// Use it before graceful shutdown, later status updates are ignored.
+    pub fn shutdown(&self) {
+        let mut inner = self.inner();
+        if !inner.shutdown {
+            inner.shutdown = true;
+            inner
+                .statuses
+                .values_mut()
+                .for_each(|status| *status = ServingStatus::NotServing);
+            inner.notify();
+        }
+    }
+
+    /// Register watch call
+    pub(super) fn watcher(&self) -> Watcher {
+        let mut inner = self.inner();
+        let id = inner.next_watcher;
+        inner.next_watcher = inner.next_watcher.wrapping_add(1);
+        Watcher {
+            id,
+            reporter: self.clone(),
+        }
+    }
+}
+
+/// Registration of watch call
+///
+/// Waker of watch call is removed from reporter on drop.
+pub(super) struct Watcher {
+    id: usize,
+    reporter: HealthReporter,
+}
+
+impl Watcher {
+    /// Poll for status of the service that differs from `last`
+    ///
+    /// Unknown service is reported with `ServiceUnknown` status.
+    pub(super) fn poll_status(
+        &self,
+        service: &str,
+        last: Option<ServingStatus>,
+        cx: &mut Context<'_>,
+    ) -> Poll<ServingStatus> {
+        let mut inner = self.reporter.inner();
+        let status = inner
+            .statuses
+            .get(service)
+            .copied()
+            .unwrap_or(ServingStatus::ServiceUnknown);
+
+        if last == Some(status) {
+            inner.watchers.insert(self.id, cx.waker().clone());
+            Poll::Pending
+        } else {
+            Poll::Ready(status)
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.reporter.inner().watchers.remove(&self.id);
+    }
+}
+
+impl Inner {
+    fn notify(&mut self) {
+        self.watchers.drain().for_each(|(_, waker)| waker.wake());
+    }
+}
+
+impl fmt::Debug for HealthReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner();
+        f.debug_struct("HealthReporter")
+            .field("statuses", &inner.statuses)
+            .field("shutdown", &inner.shutdown)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::Health;
+    use std::future::poll_fn;
+
+    #[ntex::test]
+    async fn reporter() {
+        let reporter = HealthReporter::new();
+        assert_eq!(reporter.serving_status(""), Some(ServingStatus::Serving));
+        assert_eq!(reporter.serving_status(Health::NAME), None);
+
+        let watch = |last| {
+            let watcher = reporter.watcher();
+            poll_fn(move |cx| watcher.poll_status(Health::NAME, last, cx))
+        };
+        assert_eq!(watch(None).await, ServingStatus::ServiceUnknown);
+
+        reporter.set_serving::<Health>();
+        assert_eq!(watch(None).await, ServingStatus::Serving);
+        let (status, _) = ntex_util::future::join(watch(Some(ServingStatus::Serving)), async {
+            reporter.set_not_serving::<Health>();
+        })
+        .await;
+        assert_eq!(status, ServingStatus::NotServing);
+
+        // cancelled watch call is removed from reporter
+        let watcher = reporter.watcher();
+        poll_fn(|cx| {
+            let status = Some(ServingStatus::NotServing);
+            assert!(watcher.poll_status(Health::NAME, status, cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(reporter.inner().watchers.len(), 1);
+        drop(watcher);
+        assert!(reporter.inner().watchers.is_empty());
+
+        reporter.clear_serving_status(Health::NAME);
+        assert_eq!(reporter.serving_status(Health::NAME), None);
+
+        reporter.set_serving::<Health>();
+        reporter.shutdown();
+        assert_eq!(reporter.serving_status(""), Some(ServingStatus::NotServing));
+        assert_eq!(
+            reporter.serving_status(Health::NAME),
+            Some(ServingStatus::NotServing)
+        );
+        reporter.set_serving::<Health>();
+        assert_eq!(
+            reporter.serving_status(Health::NAME),
+            Some(ServingStatus::NotServing)
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::{pin::Pin, task::Context, task::Poll};
+
+use ntex_bytes::BytesMut;
+use ntex_service::{Service, ServiceCtx, ServiceFactory};
+use ntex_util::Stream;
+
+use crate::server::{NamedService, ResponseStream, ServerError, ServerRequest, ServerResponse};
+use crate::{GrpcStatus, MethodDef, ServiceDef, Status};
+
+use super::reporter::Watcher;
+use super::{Health, HealthCheckResponse, HealthMethods, HealthReporter, ServingStatus};
+
+/// `grpc.health.v1.Health` service
+///
+/// Serves statuses of [`HealthReporter`]. Service is registered in
+/// [`Router`](crate::server::Router) next to application services.
+#[derive(Clone, Debug)]
+pub struct HealthServer {
+    reporter: HealthReporter,
+}
+
+impl HealthServer {
+    /// Create health service
+    pub fn new(reporter: HealthReporter) -> Self {
+        Self { reporter }
+    }
+
+    #[inline]
+    /// Get reference to status registry
+    pub fn reporter(&self) -> &HealthReporter {
+        &self.reporter
+    }
+}
+
+impl Service<ServerRequest> for HealthServer {
+    type Response = ServerResponse;
+    type Error = ServerError;
+
+    async fn call(
+        &self,
+        mut req: ServerRequest,
+        _: ServiceCtx<'_, Self>,
+    ) -> Result<ServerResponse, ServerError> {
+        if req.service != Health::NAME {
+            return Err(ServerError::NotFound(
+                format!("/{}/{}", req.service, req.name).into(),
+            ));
+        }
+
+        match Health::method_by_name(&req.name) {
+            Some(HealthMethods::Check(method)) => {
+                let msg = method.decode(&mut req.payload.message().await?)?;
+                let status = self.reporter.serving_status(&msg.service).ok_or_else(|| {
+                    Status::new(
+                        GrpcStatus::NotFound,
+                        format!("Service is unknown: {:?}", msg.service),
+                    )
+                })?;
+
+                let mut buf = BytesMut::new();
+                method.encode(HealthCheckResponse { status }, &mut buf);
+                Ok(ServerResponse::new(buf.freeze()))
+            }
+            Some(HealthMethods::Watch(method)) => {
+                let msg = method.decode(&mut req.payload.message().await?)?;
+                let stream = WatchStream {
+                    watcher: self.reporter.watcher(),
+                    service: msg.service.to_string(),
+                    last: None,
+                };
+                Ok(ServerResponse::streaming(
+                    method,
+                    ResponseStream::new(stream),
+                    Vec::new(),
+                ))
+            }
+            None => Err(ServerError::NotFound(req.name)),
+        }
+    }
+}
+
+/// Sends current status of the service and then every change
+struct WatchStream {
+    watcher: Watcher,
+    service: String,
+    last: Option<ServingStatus>,
+}
+
+impl Stream for WatchStream {
+    type Item = Result<HealthCheckResponse, ServerError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.watcher.poll_status(&this.service, this.last, cx) {
+            Poll::Ready(status) => {
+                this.last = Some(status);
+                Poll::Ready(Some(Ok(HealthCheckResponse { status })))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl NamedService for HealthServer {
+    const NAME: &'static str = Health::NAME;
+}
+
+impl ServiceFactory<ServerRequest> for HealthServer {
+    type Response = ServerResponse;
+    type Error = ServerError;
+    type Service = HealthServer;
+    type InitError = ();
+
+    async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        Ok(self.clone())
+    }
+}
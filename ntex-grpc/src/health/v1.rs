@@ -0,0 +1,315 @@
+#![allow(
+    dead_code,
+    unused_mut,
+    unused_variables,
+    clippy::identity_op,
+    clippy::derivable_impls,
+    clippy::unit_arg,
+    clippy::derive_partial_eq_without_eq,
+    clippy::manual_range_patterns
+)]
+// Types of `grpc/health/v1/health.proto` from grpc repository,
+// code follows output of ntex-grpc code generator.
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HealthCheckRequest {
+    pub service: crate::ByteString,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HealthCheckResponse {
+    pub status: health_check_response::ServingStatus,
+}
+
+/// Nested message and enum types in `HealthCheckResponse`.
+pub mod health_check_response {
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    #[repr(i32)]
+    pub enum ServingStatus {
+        Unknown = 0,
+        Serving = 1,
+        NotServing = 2,
+        ///  Used only by the Watch method.
+        ServiceUnknown = 3,
+    }
+
+    impl ServingStatus {
+        /// String value of the enum field names used in the ProtoBuf definition with stripped prefix.
+        pub fn to_str_name(self) -> &'static str {
+            match self {
+                ServingStatus::Unknown => "UNKNOWN",
+                ServingStatus::Serving => "SERVING",
+                ServingStatus::NotServing => "NOT_SERVING",
+                ServingStatus::ServiceUnknown => "SERVICE_UNKNOWN",
+            }
+        }
+
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn to_origin_name(self) -> &'static str {
+            match self {
+                ServingStatus::Unknown => "UNKNOWN",
+                ServingStatus::Serving => "SERVING",
+                ServingStatus::NotServing => "NOT_SERVING",
+                ServingStatus::ServiceUnknown => "SERVICE_UNKNOWN",
+            }
+        }
+
+        pub fn from_i32(value: i32) -> ::std::option::Option<Self> {
+            match value {
+                0 => Some(ServingStatus::Unknown),
+                1 => Some(ServingStatus::Serving),
+                2 => Some(ServingStatus::NotServing),
+                3 => Some(ServingStatus::ServiceUnknown),
+                _ => ::std::option::Option::None,
+            }
+        }
+    }
+}
+
+/// `Health` service definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthMethods {
+    Check(HealthCheckMethod),
+    Watch(HealthWatchMethod),
+}
+
+#[derive(Debug, Clone)]
+pub struct HealthClient<T>(T);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HealthCheckMethod;
+
+impl crate::MethodDef for HealthCheckMethod {
+    const NAME: &'static str = "Check";
+    const PATH: crate::ByteString = crate::ByteString::from_static("/grpc.health.v1.Health/Check");
+    type Input = HealthCheckRequest;
+    type Output = HealthCheckResponse;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HealthWatchMethod;
+
+impl crate::MethodDef for HealthWatchMethod {
+    const NAME: &'static str = "Watch";
+    const PATH: crate::ByteString = crate::ByteString::from_static("/grpc.health.v1.Health/Watch");
+    const SERVER_STREAMING: bool = true;
+    type Input = HealthCheckRequest;
+    type Output = HealthCheckResponse;
+}
+
+mod _priv_impl {
+    use super::*;
+
+    impl crate::Message for HealthCheckRequest {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.service,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "HealthCheckRequest";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.service, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "service"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.service,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for HealthCheckRequest {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                service: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for HealthCheckResponse {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.status,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "HealthCheckResponse";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.status, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "status"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.status,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for HealthCheckResponse {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                status: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::NativeType for health_check_response::ServingStatus {
+        const TYPE: crate::WireType = crate::WireType::Varint;
+
+        #[inline]
+        fn merge(
+            &mut self,
+            src: &mut crate::Bytes,
+        ) -> ::std::result::Result<(), crate::DecodeError> {
+            *self = crate::encoding::decode_varint(src)
+                .map(|val| Self::from_i32(val as i32).unwrap_or_default())?;
+            Ok(())
+        }
+
+        #[inline]
+        fn encode_value(&self, dst: &mut crate::BytesMut) {
+            crate::encoding::encode_varint(*self as i32 as u64, dst);
+        }
+
+        #[inline]
+        fn encoded_len(&self, tag: u32) -> usize {
+            crate::encoding::key_len(tag)
+                + crate::encoding::encoded_len_varint(*self as i32 as u64)
+        }
+
+        #[inline]
+        fn value_len(&self) -> usize {
+            crate::encoding::encoded_len_varint(*self as i32 as u64)
+        }
+
+        #[inline]
+        fn is_default(&self) -> bool {
+            self == &health_check_response::ServingStatus::Unknown
+        }
+    }
+
+    impl ::std::default::Default for health_check_response::ServingStatus {
+        #[inline]
+        fn default() -> Self {
+            health_check_response::ServingStatus::Unknown
+        }
+    }
+
+    impl crate::ServiceDef for Health {
+        const NAME: &'static str = "grpc.health.v1.Health";
+        const FILE_DESCRIPTORS: &'static [&'static [u8]] = &[b"\n\x1bgrpc/health/v1/health.proto\x12\x0egrpc.health.v1\".\n\x12HealthCheckRequest\x12\x18\n\x07service\x18\x01 \x01(\tR\x07service\"\xb1\x01\n\x13HealthCheckResponse\x12I\n\x06status\x18\x01 \x01(\x0e21.grpc.health.v1.HealthCheckResponse.ServingStatusR\x06status\"O\n\rServingStatus\x12\x0b\n\x07UNKNOWN\x10\x00\x12\x0b\n\x07SERVING\x10\x01\x12\x0f\n\x0bNOT_SERVING\x10\x02\x12\x13\n\x0fSERVICE_UNKNOWN\x10\x032\xae\x01\n\x06Health\x12P\n\x05Check\x12\".grpc.health.v1.HealthCheckRequest\x1a#.grpc.health.v1.HealthCheckResponse\x12R\n\x05Watch\x12\".grpc.health.v1.HealthCheckRequest\x1a#.grpc.health.v1.HealthCheckResponse0\x01Ba\n\x11io.grpc.health.v1B\x0bHealthProtoP\x01Z,google.golang.org/grpc/health/grpc_health_v1\xaa\x02\x0eGrpc.Health.V1b\x06proto3"];
+        type Methods = HealthMethods;
+
+        #[inline]
+        fn method_by_name(name: &str) -> Option<Self::Methods> {
+            use crate::MethodDef;
+            match name {
+                HealthCheckMethod::NAME => Some(HealthMethods::Check(HealthCheckMethod)),
+                HealthWatchMethod::NAME => Some(HealthMethods::Watch(HealthWatchMethod)),
+                _ => None,
+            }
+        }
+    }
+
+    impl<T> HealthClient<T> {
+        #[inline]
+        /// Create new client instance
+        pub fn new(transport: T) -> Self {
+            Self(transport)
+        }
+    }
+
+    impl<T> crate::client::ClientInformation<T> for HealthClient<T> {
+        #[inline]
+        /// Create new client instance
+        fn create(transport: T) -> Self {
+            Self(transport)
+        }
+
+        #[inline]
+        /// Get referece to underlying transport
+        fn transport(&self) -> &T {
+            &self.0
+        }
+
+        #[inline]
+        /// Get mut referece to underlying transport
+        fn transport_mut(&mut self) -> &mut T {
+            &mut self.0
+        }
+
+        #[inline]
+        /// Consume client and return inner transport
+        fn into_inner(self) -> T {
+            self.0
+        }
+    }
+
+    impl<T: crate::client::Transport<HealthCheckMethod>> HealthClient<T> {
+        ///  If the requested service is unknown, the call will fail with status
+        ///  NOT_FOUND.
+        pub fn check<'a>(
+            &'a self,
+            req: &'a super::HealthCheckRequest,
+        ) -> crate::client::Request<'a, T, HealthCheckMethod> {
+            crate::client::Request::new(&self.0, req)
+        }
+    }
+
+    impl<T: crate::client::StreamingTransport<HealthWatchMethod>> HealthClient<T> {
+        ///  Performs a watch for the serving status of the requested service.
+        ///  The server will immediately send back a message indicating the current
+        ///  serving status.  It will then subsequently send a new message whenever
+        ///  the service's serving status changes.
+        pub fn watch<'a>(
+            &'a self,
+            req: &'a super::HealthCheckRequest,
+        ) -> crate::client::ServerStreamingRequest<'a, T, HealthWatchMethod> {
+            crate::client::ServerStreamingRequest::new(&self.0, req)
+        }
+    }
+}
@@ -2,16 +2,24 @@ mod consts;
 mod service;
 mod status;
 mod utils;
+#[cfg(feature = "web")]
+mod web;
 
 pub mod client;
+pub mod compression;
+pub mod google_rpc;
+pub mod health;
+pub mod reflection;
 pub mod server;
 pub mod types;
 
+pub use crate::compression::{CompressionEncoding, Compressor, Compressors};
 pub use crate::encoding::DecodeError;
 
 pub use crate::service::{MethodDef, ServiceDef};
-pub use crate::status::GrpcStatus;
+pub use crate::status::{GrpcStatus, Status};
 pub use crate::types::{Message, NativeType};
+pub use ntex_util::Stream;
 
 #[doc(hidden)]
 pub mod google_types;
@@ -0,0 +1,469 @@
+#![allow(
+    dead_code,
+    unused_mut,
+    unused_variables,
+    clippy::identity_op,
+    clippy::derivable_impls,
+    clippy::unit_arg,
+    clippy::derive_partial_eq_without_eq,
+    clippy::manual_range_patterns
+)]
+// Subset of `google/protobuf/descriptor.proto` with fields required to index
+// symbols of file descriptors, other fields are skipped. Code follows
+// output of ntex-grpc code generator.
+
+///  Describes a complete .proto file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FileDescriptorProto {
+    ///  file name, relative to root of source tree
+    pub name: crate::ByteString,
+    ///  e.g. "foo", "foo.bar", etc.
+    pub package: crate::ByteString,
+    ///  Names of files imported by this file.
+    pub dependency: Vec<crate::ByteString>,
+    ///  All top-level definitions in this file.
+    pub message_type: Vec<DescriptorProto>,
+    pub enum_type: Vec<EnumDescriptorProto>,
+    pub service: Vec<ServiceDescriptorProto>,
+    pub extension: Vec<FieldDescriptorProto>,
+}
+
+///  Describes a message type.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DescriptorProto {
+    pub name: crate::ByteString,
+    pub nested_type: Vec<DescriptorProto>,
+    pub enum_type: Vec<EnumDescriptorProto>,
+    pub extension: Vec<FieldDescriptorProto>,
+}
+
+///  Describes a field within a message.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FieldDescriptorProto {
+    pub name: crate::ByteString,
+    ///  For extensions, this is the name of the type being extended.  It is
+    ///  resolved in the same manner as type_name.
+    pub extendee: crate::ByteString,
+    pub number: i32,
+}
+
+///  Describes an enum type.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EnumDescriptorProto {
+    pub name: crate::ByteString,
+}
+
+///  Describes a service.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ServiceDescriptorProto {
+    pub name: crate::ByteString,
+    pub method: Vec<MethodDescriptorProto>,
+}
+
+///  Describes a method of a service.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MethodDescriptorProto {
+    pub name: crate::ByteString,
+}
+
+mod _priv_impl {
+    use super::*;
+
+    impl crate::Message for FileDescriptorProto {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.name, 1, crate::types::DefaultValue::Default, dst);
+            crate::NativeType::serialize(
+                &self.package,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.dependency,
+                3,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.message_type,
+                4,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.enum_type,
+                5,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.service,
+                6,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.extension,
+                7,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "FileDescriptorProto";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.name, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "name"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.package, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "package"))?,
+                    3 => crate::NativeType::deserialize(&mut msg.dependency, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "dependency"))?,
+                    4 => {
+                        crate::NativeType::deserialize(&mut msg.message_type, tag, wire_type, src)
+                            .map_err(|err| err.push(STRUCT_NAME, "message_type"))?
+                    }
+                    5 => crate::NativeType::deserialize(&mut msg.enum_type, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "enum_type"))?,
+                    6 => crate::NativeType::deserialize(&mut msg.service, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "service"))?,
+                    7 => crate::NativeType::deserialize(&mut msg.extension, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "extension"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.name,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.package,
+                2,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.dependency,
+                3,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.message_type,
+                4,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.enum_type,
+                5,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.service,
+                6,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.extension,
+                7,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for FileDescriptorProto {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                name: ::core::default::Default::default(),
+                package: ::core::default::Default::default(),
+                dependency: ::core::default::Default::default(),
+                message_type: ::core::default::Default::default(),
+                enum_type: ::core::default::Default::default(),
+                service: ::core::default::Default::default(),
+                extension: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for DescriptorProto {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.name, 1, crate::types::DefaultValue::Default, dst);
+            crate::NativeType::serialize(
+                &self.nested_type,
+                3,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.enum_type,
+                4,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.extension,
+                6,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "DescriptorProto";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.name, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "name"))?,
+                    3 => crate::NativeType::deserialize(&mut msg.nested_type, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "nested_type"))?,
+                    4 => crate::NativeType::deserialize(&mut msg.enum_type, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "enum_type"))?,
+                    6 => crate::NativeType::deserialize(&mut msg.extension, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "extension"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.name,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.nested_type,
+                3,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.enum_type,
+                4,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.extension,
+                6,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for DescriptorProto {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                name: ::core::default::Default::default(),
+                nested_type: ::core::default::Default::default(),
+                enum_type: ::core::default::Default::default(),
+                extension: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for FieldDescriptorProto {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.name, 1, crate::types::DefaultValue::Default, dst);
+            crate::NativeType::serialize(
+                &self.extendee,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.number,
+                3,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "FieldDescriptorProto";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.name, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "name"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.extendee, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "extendee"))?,
+                    3 => crate::NativeType::deserialize(&mut msg.number, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "number"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.name,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.extendee,
+                2,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.number,
+                3,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for FieldDescriptorProto {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                name: ::core::default::Default::default(),
+                extendee: ::core::default::Default::default(),
+                number: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for EnumDescriptorProto {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.name, 1, crate::types::DefaultValue::Default, dst);
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "EnumDescriptorProto";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.name, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "name"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.name,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for EnumDescriptorProto {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                name: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for ServiceDescriptorProto {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.name, 1, crate::types::DefaultValue::Default, dst);
+            crate::NativeType::serialize(
+                &self.method,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ServiceDescriptorProto";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.name, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "name"))?,
+                    2 => crate::NativeType::deserialize(&mut msg.method, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "method"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.name,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.method,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ServiceDescriptorProto {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                name: ::core::default::Default::default(),
+                method: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for MethodDescriptorProto {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.name, 1, crate::types::DefaultValue::Default, dst);
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "MethodDescriptorProto";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.name, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "name"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.name,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for MethodDescriptorProto {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                name: ::core::default::Default::default(),
+            }
+        }
+    }
+}
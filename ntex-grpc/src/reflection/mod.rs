@@ -0,0 +1,27 @@
+//! Server reflection service of `grpc.reflection.v1` package
+//!
+//! [`ReflectionServer`] lets tools like `grpcurl` list services of the server
+//! and fetch their descriptors. Descriptors are embedded to generated code by
+//! `ntex-grpc-codegen`. Register it in `Router` to serve it next to
+//! application services.
+//!
+//! ```rust,ignore
+//! let reflection = ReflectionServer::new().register::<Greeter>();
+//!
+//! Server::build()
+//!     .bind("grpc", "0.0.0.0:50051", move |_| {
+//!         GrpcServer::new(
+//!             Router::new()
+//!                 .service(GreeterServer)
+//!                 .service(reflection.clone()),
+//!         )
+//!     })?
+//!     .run()
+//!     .await
+//! ```
+mod descriptor;
+mod server;
+mod v1;
+
+pub use self::server::ReflectionServer;
+pub use self::v1::*;
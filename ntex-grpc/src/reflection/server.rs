@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+use std::{fmt, pin::Pin, sync::Arc, task::Context, task::Poll};
+
+use ntex_bytes::{ByteString, Bytes};
+use ntex_service::{Service, ServiceCtx, ServiceFactory};
+use ntex_util::Stream;
+
+use crate::server::{
+    NamedService, ResponseStream, ServerError, ServerRequest, ServerResponse, Streaming,
+};
+use crate::{GrpcStatus, Message, ServiceDef};
+
+use super::descriptor::{DescriptorProto, FieldDescriptorProto, FileDescriptorProto};
+use super::server_reflection_request::MessageRequest;
+use super::server_reflection_response::MessageResponse;
+use super::{ErrorResponse, ExtensionNumberResponse, FileDescriptorResponse};
+use super::{ListServiceResponse, ServerReflection, ServerReflectionMethods};
+use super::{ServerReflectionRequest, ServerReflectionResponse, ServiceResponse};
+
+/// `grpc.reflection.v1.ServerReflection` service
+///
+/// Describes services registered with [`ReflectionServer::register()`].
+/// Service is registered in [`Router`](crate::server::Router) next to
+/// application services.
+#[derive(Clone)]
+pub struct ReflectionServer {
+    registry: Arc<Registry>,
+}
+
+/// Registered services and index of their file descriptors
+#[derive(Clone, Default)]
+struct Registry {
+    services: Vec<ByteString>,
+    /// File name to descriptor and names of its dependencies
+    files: HashMap<String, (Bytes, Vec<String>)>,
+    /// Fully-qualified symbol name to file name
+    symbols: HashMap<String, String>,
+    /// Extended type name to extension numbers and file names
+    extensions: HashMap<String, Vec<(i32, String)>>,
+}
+
+impl Default for ReflectionServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReflectionServer {
+    /// Create reflection service
+    ///
+    /// Reflection service itself is registered.
+    pub fn new() -> Self {
+        Self {
+            registry: Arc::new(Registry::default()),
+        }
+        .register::<ServerReflection>()
+    }
+
+    /// Register service definition
+    ///
+    /// File descriptors of the service are generated by `ntex-grpc-codegen`,
+    /// service without descriptors is listed but cannot be described.
+    pub fn register<S: ServiceDef>(mut self) -> Self {
+        let registry = Arc::make_mut(&mut self.registry);
+        if !registry.services.iter().any(|name| name == S::NAME) {
+            registry.services.push(ByteString::from_static(S::NAME));
+        }
+        if S::FILE_DESCRIPTORS.is_empty() {
+            log::warn!("Service {} does not provide file descriptors", S::NAME);
+        }
+        for buf in S::FILE_DESCRIPTORS {
+            registry.add_file(Bytes::from_static(buf));
+        }
+        self
+    }
+}
+
+impl Registry {
+    fn add_file(&mut self, buf: Bytes) {
+        let file = match FileDescriptorProto::read(&mut buf.clone()) {
+            Ok(file) => file,
+            Err(err) => {
+                log::error!("Cannot decode file descriptor: {}", err);
+                return;
+            }
+        };
+        if self.files.contains_key(file.name.as_str()) {
+            return;
+        }
+        log::trace!("Register file descriptor {:?}", file.name);
+
+        let name = file.name.to_string();
+        for msg in &file.message_type {
+            self.add_message(&name, &file.package, msg);
+        }
+        for item in &file.enum_type {
+            self.add_symbol(&name, &file.package, &item.name);
+        }
+        for srv in &file.service {
+            let scope = self.add_symbol(&name, &file.package, &srv.name);
+            for method in &srv.method {
+                self.add_symbol(&name, &scope, &method.name);
+            }
+        }
+        for ext in &file.extension {
+            self.add_extension(&name, &file.package, ext);
+        }
+
+        let deps = file.dependency.iter().map(|dep| dep.to_string()).collect();
+        self.files.insert(name, (buf, deps));
+    }
+
+    fn add_message(&mut self, file: &str, scope: &str, msg: &DescriptorProto) {
+        let scope = self.add_symbol(file, scope, &msg.name);
+        for nested in &msg.nested_type {
+            self.add_message(file, &scope, nested);
+        }
+        for item in &msg.enum_type {
+            self.add_symbol(file, &scope, &item.name);
+        }
+        for ext in &msg.extension {
+            self.add_extension(file, &scope, ext);
+        }
+    }
+
+    fn add_extension(&mut self, file: &str, scope: &str, ext: &FieldDescriptorProto) {
+        self.add_symbol(file, scope, &ext.name);
+        self.extensions
+            .entry(ext.extendee.trim_start_matches('.').to_string())
+            .or_default()
+            .push((ext.number, file.to_string()));
+    }
+
+    /// Add fully-qualified symbol, returns symbol name
+    fn add_symbol(&mut self, file: &str, scope: &str, name: &str) -> String {
+        let symbol = if scope.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", scope, name)
+        };
+        self.symbols.insert(symbol.clone(), file.to_string());
+        symbol
+    }
+
+    fn respond(&self, req: ServerReflectionRequest) -> ServerReflectionResponse {
+        let response = match req.message_request {
+            Some(MessageRequest::FileByFilename(ref name)) => self.file_response(name),
+            Some(MessageRequest::FileContainingSymbol(ref symbol)) => {
+                match self.symbols.get(symbol.as_str()) {
+                    Some(file) => self.file_response(file),
+                    None => error_response(
+                        GrpcStatus::NotFound,
+                        format!("Symbol is not found: {}", symbol),
+                    ),
+                }
+            }
+            Some(MessageRequest::FileContainingExtension(ref ext)) => {
+                match self
+                    .extensions
+                    .get(ext.containing_type.as_str())
+                    .and_then(|exts| exts.iter().find(|(num, _)| *num == ext.extension_number))
+                {
+                    Some((_, file)) => self.file_response(file),
+                    None => error_response(
+                        GrpcStatus::NotFound,
+                        format!(
+                            "Extension is not found: {}({})",
+                            ext.containing_type, ext.extension_number
+                        ),
+                    ),
+                }
+            }
+            Some(MessageRequest::AllExtensionNumbersOfType(ref name)) => {
+                if self.symbols.contains_key(name.as_str()) {
+                    MessageResponse::AllExtensionNumbersResponse(ExtensionNumberResponse {
+                        base_type_name: name.clone(),
+                        extension_number: self
+                            .extensions
+                            .get(name.as_str())
+                            .map(|exts| exts.iter().map(|(num, _)| *num).collect())
+                            .unwrap_or_default(),
+                    })
+                } else {
+                    error_response(GrpcStatus::NotFound, format!("Type is not found: {}", name))
+                }
+            }
+            Some(MessageRequest::ListServices(_)) => {
+                MessageResponse::ListServicesResponse(ListServiceResponse {
+                    service: self
+                        .services
+                        .iter()
+                        .map(|name| ServiceResponse { name: name.clone() })
+                        .collect(),
+                })
+            }
+            None => error_response(GrpcStatus::InvalidArgument, "Request is empty".to_string()),
+        };
+
+        ServerReflectionResponse {
+            valid_host: req.host.clone(),
+            original_request: req,
+            message_response: Some(response),
+        }
+    }
+
+    /// Descriptor of the file followed by descriptors of its transitive dependencies
+    fn file_response(&self, name: &str) -> MessageResponse {
+        if !self.files.contains_key(name) {
+            return error_response(GrpcStatus::NotFound, format!("File is not found: {}", name));
+        }
+
+        let mut names = vec![name];
+        let mut idx = 0;
+        while idx < names.len() {
+            if let Some((_, deps)) = self.files.get(names[idx]) {
+                for dep in deps {
+                    if !names.contains(&dep.as_str()) {
+                        names.push(dep);
+                    }
+                }
+            }
+            idx += 1;
+        }
+
+        MessageResponse::FileDescriptorResponse(FileDescriptorResponse {
+            file_descriptor_proto: names
+                .into_iter()
+                .filter_map(|name| self.files.get(name).map(|(buf, _)| buf.clone()))
+                .collect(),
+        })
+    }
+}
+
+fn error_response(code: GrpcStatus, msg: String) -> MessageResponse {
+    MessageResponse::ErrorResponse(ErrorResponse {
+        error_code: code as i32,
+        error_message: msg.into(),
+    })
+}
+
+impl fmt::Debug for ReflectionServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReflectionServer")
+            .field("services", &self.registry.services)
+            .finish()
+    }
+}
+
+impl Service<ServerRequest> for ReflectionServer {
+    type Response = ServerResponse;
+    type Error = ServerError;
+
+    async fn call(
+        &self,
+        req: ServerRequest,
+        _: ServiceCtx<'_, Self>,
+    ) -> Result<ServerResponse, ServerError> {
+        if req.service != ServerReflection::NAME {
+            return Err(ServerError::NotFound(
+                format!("/{}/{}", req.service, req.name).into(),
+            ));
+        }
+
+        match ServerReflection::method_by_name(&req.name) {
+            Some(ServerReflectionMethods::ServerReflectionInfo(method)) => {
+                let stream = ReflectionStream {
+                    requests: Streaming::new(method, req.payload),
+                    registry: self.registry.clone(),
+                };
+                Ok(ServerResponse::streaming(
+                    method,
+                    ResponseStream::new(stream),
+                    Vec::new(),
+                ))
+            }
+            None => Err(ServerError::NotFound(req.name)),
+        }
+    }
+}
+
+/// Responds to every reflection request of the call
+struct ReflectionStream {
+    requests: Streaming<ServerReflectionRequest>,
+    registry: Arc<Registry>,
+}
+
+impl Stream for ReflectionStream {
+    type Item = Result<ServerReflectionResponse, ServerError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.requests.poll_recv(cx) {
+            Poll::Ready(Some(Ok(req))) => Poll::Ready(Some(Ok(this.registry.respond(req)))),
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl NamedService for ReflectionServer {
+    const NAME: &'static str = ServerReflection::NAME;
+}
+
+impl ServiceFactory<ServerRequest> for ReflectionServer {
+    type Response = ServerResponse;
+    type Error = ServerError;
+    type Service = ReflectionServer;
+    type InitError = ();
+
+    async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        Ok(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::Health;
+
+    fn request(msg: MessageRequest) -> ServerReflectionRequest {
+        ServerReflectionRequest {
+            host: ByteString::new(),
+            message_request: Some(msg),
+        }
+    }
+
+    fn files(res: ServerReflectionResponse) -> Vec<Bytes> {
+        match res.message_response {
+            Some(MessageResponse::FileDescriptorResponse(res)) => res.file_descriptor_proto,
+            res => panic!("unexpected response: {:?}", res),
+        }
+    }
+
+    fn error_code(res: ServerReflectionResponse) -> i32 {
+        match res.message_response {
+            Some(MessageResponse::ErrorResponse(res)) => res.error_code,
+            res => panic!("unexpected response: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn registry() {
+        let srv = ReflectionServer::new().register::<Health>();
+        let registry = &srv.registry;
+        let health = Bytes::from_static(Health::FILE_DESCRIPTORS[0]);
+
+        let res = registry.respond(request(MessageRequest::ListServices("*".into())));
+        match res.message_response {
+            Some(MessageResponse::ListServicesResponse(res)) => assert_eq!(
+                res.service
+                    .iter()
+                    .map(|srv| srv.name.as_str())
+                    .collect::<Vec<_>>(),
+                vec![ServerReflection::NAME, Health::NAME]
+            ),
+            res => panic!("unexpected response: {:?}", res),
+        }
+
+        for symbol in [
+            "grpc.health.v1.Health",
+            "grpc.health.v1.Health.Watch",
+            "grpc.health.v1.HealthCheckRequest",
+            "grpc.health.v1.HealthCheckResponse.ServingStatus",
+        ] {
+            let res =
+                registry.respond(request(MessageRequest::FileContainingSymbol(symbol.into())));
+            assert_eq!(
+                res.original_request.message_request,
+                Some(MessageRequest::FileContainingSymbol(symbol.into()))
+            );
+            assert_eq!(files(res), vec![health.clone()]);
+        }
+        let res = registry.respond(request(MessageRequest::FileByFilename(
+            "grpc/health/v1/health.proto".into(),
+        )));
+        assert_eq!(files(res), vec![health]);
+
+        let res = registry.respond(request(MessageRequest::FileContainingSymbol(
+            "grpc.health.v1.Unknown".into(),
+        )));
+        assert_eq!(error_code(res), GrpcStatus::NotFound as i32);
+        let res = registry.respond(request(MessageRequest::AllExtensionNumbersOfType(
+            "grpc.health.v1.HealthCheckRequest".into(),
+        )));
+        match res.message_response {
+            Some(MessageResponse::AllExtensionNumbersResponse(res)) => {
+                assert!(res.extension_number.is_empty())
+            }
+            res => panic!("unexpected response: {:?}", res),
+        }
+        let res = registry.respond(ServerReflectionRequest::default());
+        assert_eq!(error_code(res), GrpcStatus::InvalidArgument as i32);
+    }
+
+    #[test]
+    fn dependencies() {
+        let mut registry = Registry::default();
+        let file = |name: &str, package: &str, deps: &[&str], msg: &str| {
+            let file = FileDescriptorProto {
+                name: name.into(),
+                package: package.into(),
+                dependency: deps.iter().map(|dep| (*dep).into()).collect(),
+                message_type: vec![DescriptorProto {
+                    name: msg.into(),
+                    nested_type: Vec::new(),
+                    enum_type: Vec::new(),
+                    extension: Vec::new(),
+                }],
+                ..Default::default()
+            };
+            let mut buf = ntex_bytes::BytesMut::new();
+            file.write(&mut buf);
+            buf.freeze()
+        };
+        let a = file("a.proto", "pkg", &["b.proto"], "A");
+        let b = file("b.proto", "pkg", &["c.proto"], "B");
+        let c = file("c.proto", "pkg.c", &[], "C");
+        registry.add_file(a.clone());
+        registry.add_file(b.clone());
+        registry.add_file(c.clone());
+
+        let res = registry.respond(request(MessageRequest::FileContainingSymbol(
+            "pkg.c.C".into(),
+        )));
+        assert_eq!(files(res), vec![c.clone()]);
+        let res = registry.respond(request(MessageRequest::FileContainingSymbol(
+            "pkg.A".into(),
+        )));
+        assert_eq!(files(res), vec![a, b, c]);
+    }
+}
@@ -0,0 +1,982 @@
+#![allow(
+    dead_code,
+    unused_mut,
+    unused_variables,
+    clippy::identity_op,
+    clippy::derivable_impls,
+    clippy::unit_arg,
+    clippy::derive_partial_eq_without_eq,
+    clippy::manual_range_patterns
+)]
+// Types of `grpc/reflection/v1/reflection.proto` from grpc repository,
+// code follows output of ntex-grpc code generator.
+
+///  The message sent by the client when calling ServerReflectionInfo method.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ServerReflectionRequest {
+    pub host: crate::ByteString,
+    ///  To use reflection service, the client should set one of the following
+    ///  fields in message_request. The server distinguishes requests by their
+    ///  defined field and then handles them using corresponding methods.
+    pub message_request: Option<server_reflection_request::MessageRequest>,
+}
+
+/// Nested message and enum types in `ServerReflectionRequest`.
+pub mod server_reflection_request {
+    ///  To use reflection service, the client should set one of the following
+    ///  fields in message_request. The server distinguishes requests by their
+    ///  defined field and then handles them using corresponding methods.
+    #[derive(Clone, PartialEq, Debug)]
+    pub enum MessageRequest {
+        ///  Find a proto file by the file name.
+        FileByFilename(crate::ByteString),
+        ///  Find the proto file that declares the given fully-qualified symbol name.
+        ///  This field should be a fully-qualified symbol name
+        ///  (e.g. <package>.<service>\[.<method>\] or <package>.<type>).
+        FileContainingSymbol(crate::ByteString),
+        ///  Find the proto file which defines an extension extending the given
+        ///  message type with the given field number.
+        FileContainingExtension(super::ExtensionRequest),
+        ///  Finds the tag numbers used by all known extensions of the given message
+        ///  type, and appends them to ExtensionNumberResponse in an undefined order.
+        ///  Its corresponding method is best-effort: it's not guaranteed that the
+        ///  reflection service will implement this method, and it's not guaranteed
+        ///  that this method will provide all extensions. Returns
+        ///  StatusCode::UNIMPLEMENTED if it's not implemented.
+        ///  This field should be a fully-qualified type name. The format is
+        ///  <package>.<type>
+        AllExtensionNumbersOfType(crate::ByteString),
+        ///  List the full names of registered services. The content will not be
+        ///  checked.
+        ListServices(crate::ByteString),
+    }
+}
+
+///  The type name and extension number sent by the client when requesting
+///  file_containing_extension.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ExtensionRequest {
+    ///  Fully-qualified type name. The format should be <package>.<type>
+    pub containing_type: crate::ByteString,
+    pub extension_number: i32,
+}
+
+///  The message sent by the server to answer ServerReflectionInfo method.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ServerReflectionResponse {
+    pub valid_host: crate::ByteString,
+    pub original_request: ServerReflectionRequest,
+    ///  The server sets one of the following fields according to the message_request
+    ///  in the request.
+    pub message_response: Option<server_reflection_response::MessageResponse>,
+}
+
+/// Nested message and enum types in `ServerReflectionResponse`.
+pub mod server_reflection_response {
+    ///  The server sets one of the following fields according to the message_request
+    ///  in the request.
+    #[derive(Clone, PartialEq, Debug)]
+    pub enum MessageResponse {
+        ///  This message is used to answer file_by_filename, file_containing_symbol,
+        ///  file_containing_extension requests with transitive dependencies.
+        ///  As the repeated label is not allowed in oneof fields, we use a
+        ///  FileDescriptorResponse message to encapsulate the repeated fields.
+        ///  The reflection service is allowed to avoid sending FileDescriptorProtos
+        ///  that were previously sent in response to earlier requests in the stream.
+        FileDescriptorResponse(super::FileDescriptorResponse),
+        ///  This message is used to answer all_extension_numbers_of_type requests.
+        AllExtensionNumbersResponse(super::ExtensionNumberResponse),
+        ///  This message is used to answer list_services requests.
+        ListServicesResponse(super::ListServiceResponse),
+        ///  This message is used when an error occurs.
+        ErrorResponse(super::ErrorResponse),
+    }
+}
+
+///  Serialized FileDescriptorProto messages sent by the server answering
+///  a file_by_filename, file_containing_symbol, or file_containing_extension
+///  request.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FileDescriptorResponse {
+    ///  Serialized FileDescriptorProto messages. We avoid taking a dependency on
+    ///  descriptor.proto, which uses proto2 only features, by making them opaque
+    ///  bytes instead.
+    pub file_descriptor_proto: Vec<crate::Bytes>,
+}
+
+///  A list of extension numbers sent by the server answering
+///  all_extension_numbers_of_type request.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ExtensionNumberResponse {
+    ///  Full name of the base type, including the package name. The format
+    ///  is <package>.<type>
+    pub base_type_name: crate::ByteString,
+    pub extension_number: Vec<i32>,
+}
+
+///  A list of ServiceResponse sent by the server answering list_services request.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ListServiceResponse {
+    ///  The information of each service may be expanded in the future, so we use
+    ///  ServiceResponse message to encapsulate it.
+    pub service: Vec<ServiceResponse>,
+}
+
+///  The information of a single service used by ListServiceResponse to answer
+///  list_services request.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ServiceResponse {
+    ///  Full name of a registered service, including its package name. The format
+    ///  is <package>.<service>
+    pub name: crate::ByteString,
+}
+
+///  The error code and error message sent by the server when an error occurs.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ErrorResponse {
+    ///  This field uses the error codes defined in grpc::StatusCode.
+    pub error_code: i32,
+    pub error_message: crate::ByteString,
+}
+
+/// `ServerReflection` service definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerReflection;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerReflectionMethods {
+    ServerReflectionInfo(ServerReflectionServerReflectionInfoMethod),
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerReflectionClient<T>(T);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ServerReflectionServerReflectionInfoMethod;
+
+impl crate::MethodDef for ServerReflectionServerReflectionInfoMethod {
+    const NAME: &'static str = "ServerReflectionInfo";
+    const PATH: crate::ByteString = crate::ByteString::from_static(
+        "/grpc.reflection.v1.ServerReflection/ServerReflectionInfo",
+    );
+    const CLIENT_STREAMING: bool = true;
+    const SERVER_STREAMING: bool = true;
+    type Input = ServerReflectionRequest;
+    type Output = ServerReflectionResponse;
+}
+
+mod _priv_impl {
+    use super::*;
+
+    impl crate::Message for ServerReflectionRequest {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.host, 1, crate::types::DefaultValue::Default, dst);
+            crate::NativeType::serialize(
+                &self.message_request,
+                0,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ServerReflectionRequest";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.host, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "host"))?,
+                    3 | 4 | 5 | 6 | 7 => crate::NativeType::deserialize(
+                        &mut msg.message_request,
+                        tag,
+                        wire_type,
+                        src,
+                    )
+                    .map_err(|err| err.push(STRUCT_NAME, "message_request"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.host,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.message_request,
+                0,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ServerReflectionRequest {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                host: ::core::default::Default::default(),
+                message_request: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::NativeType for server_reflection_request::MessageRequest {
+        const TYPE: crate::WireType = crate::WireType::LengthDelimited;
+
+        fn merge(
+            &mut self,
+            _: &mut crate::Bytes,
+        ) -> ::std::result::Result<(), crate::DecodeError> {
+            panic!("Not supported")
+        }
+
+        fn encode_value(&self, _: &mut crate::BytesMut) {
+            panic!("Not supported")
+        }
+
+        #[inline]
+        /// Encodes the message to a buffer.
+        fn serialize(
+            &self,
+            _: u32,
+            _: crate::types::DefaultValue<&Self>,
+            dst: &mut crate::BytesMut,
+        ) {
+            match *self {
+                server_reflection_request::MessageRequest::FileByFilename(ref value) => {
+                    crate::NativeType::serialize(
+                        value,
+                        3,
+                        crate::types::DefaultValue::Unknown,
+                        dst,
+                    )
+                }
+                server_reflection_request::MessageRequest::FileContainingSymbol(ref value) => {
+                    crate::NativeType::serialize(
+                        value,
+                        4,
+                        crate::types::DefaultValue::Unknown,
+                        dst,
+                    )
+                }
+                server_reflection_request::MessageRequest::FileContainingExtension(ref value) => {
+                    crate::NativeType::serialize(
+                        value,
+                        5,
+                        crate::types::DefaultValue::Unknown,
+                        dst,
+                    )
+                }
+                server_reflection_request::MessageRequest::AllExtensionNumbersOfType(
+                    ref value,
+                ) => crate::NativeType::serialize(
+                    value,
+                    6,
+                    crate::types::DefaultValue::Unknown,
+                    dst,
+                ),
+                server_reflection_request::MessageRequest::ListServices(ref value) => {
+                    crate::NativeType::serialize(
+                        value,
+                        7,
+                        crate::types::DefaultValue::Unknown,
+                        dst,
+                    )
+                }
+            }
+        }
+
+        #[inline]
+        /// Decodes an instance of the message from a buffer, and merges it into self.
+        fn deserialize(
+            &mut self,
+            tag: u32,
+            wire_type: crate::WireType,
+            src: &mut crate::Bytes,
+        ) -> ::std::result::Result<(), crate::DecodeError> {
+            *self = match tag {
+                3 => server_reflection_request::MessageRequest::FileByFilename(
+                    crate::NativeType::deserialize_default(3, wire_type, src)?,
+                ),
+                4 => server_reflection_request::MessageRequest::FileContainingSymbol(
+                    crate::NativeType::deserialize_default(4, wire_type, src)?,
+                ),
+                5 => server_reflection_request::MessageRequest::FileContainingExtension(
+                    crate::NativeType::deserialize_default(5, wire_type, src)?,
+                ),
+                6 => server_reflection_request::MessageRequest::AllExtensionNumbersOfType(
+                    crate::NativeType::deserialize_default(6, wire_type, src)?,
+                ),
+                7 => server_reflection_request::MessageRequest::ListServices(
+                    crate::NativeType::deserialize_default(7, wire_type, src)?,
+                ),
+                _ => unreachable!("invalid MessageRequest, tag: {}", tag),
+            };
+            Ok(())
+        }
+
+        #[inline]
+        /// Returns the encoded length of the message without a length delimiter.
+        fn serialized_len(&self, _: u32, _: crate::types::DefaultValue<&Self>) -> usize {
+            match *self {
+                server_reflection_request::MessageRequest::FileByFilename(ref value) => {
+                    crate::NativeType::serialized_len(
+                        value,
+                        3,
+                        crate::types::DefaultValue::Unknown,
+                    )
+                }
+                server_reflection_request::MessageRequest::FileContainingSymbol(ref value) => {
+                    crate::NativeType::serialized_len(
+                        value,
+                        4,
+                        crate::types::DefaultValue::Unknown,
+                    )
+                }
+                server_reflection_request::MessageRequest::FileContainingExtension(ref value) => {
+                    crate::NativeType::serialized_len(
+                        value,
+                        5,
+                        crate::types::DefaultValue::Unknown,
+                    )
+                }
+                server_reflection_request::MessageRequest::AllExtensionNumbersOfType(
+                    ref value,
+                ) => crate::NativeType::serialized_len(
+                    value,
+                    6,
+                    crate::types::DefaultValue::Unknown,
+                ),
+                server_reflection_request::MessageRequest::ListServices(ref value) => {
+                    crate::NativeType::serialized_len(
+                        value,
+                        7,
+                        crate::types::DefaultValue::Unknown,
+                    )
+                }
+            }
+        }
+    }
+
+    impl ::std::default::Default for server_reflection_request::MessageRequest {
+        #[inline]
+        fn default() -> Self {
+            server_reflection_request::MessageRequest::FileByFilename(
+                ::std::default::Default::default(),
+            )
+        }
+    }
+
+    impl crate::Message for ExtensionRequest {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.containing_type,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.extension_number,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ExtensionRequest";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(
+                        &mut msg.containing_type,
+                        tag,
+                        wire_type,
+                        src,
+                    )
+                    .map_err(|err| err.push(STRUCT_NAME, "containing_type"))?,
+                    2 => crate::NativeType::deserialize(
+                        &mut msg.extension_number,
+                        tag,
+                        wire_type,
+                        src,
+                    )
+                    .map_err(|err| err.push(STRUCT_NAME, "extension_number"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.containing_type,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.extension_number,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ExtensionRequest {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                containing_type: ::core::default::Default::default(),
+                extension_number: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for ServerReflectionResponse {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.valid_host,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.original_request,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.message_response,
+                0,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ServerReflectionResponse";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.valid_host, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "valid_host"))?,
+                    2 => crate::NativeType::deserialize(
+                        &mut msg.original_request,
+                        tag,
+                        wire_type,
+                        src,
+                    )
+                    .map_err(|err| err.push(STRUCT_NAME, "original_request"))?,
+                    4 | 5 | 6 | 7 => crate::NativeType::deserialize(
+                        &mut msg.message_response,
+                        tag,
+                        wire_type,
+                        src,
+                    )
+                    .map_err(|err| err.push(STRUCT_NAME, "message_response"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.valid_host,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.original_request,
+                2,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.message_response,
+                0,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ServerReflectionResponse {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                valid_host: ::core::default::Default::default(),
+                original_request: ::core::default::Default::default(),
+                message_response: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::NativeType for server_reflection_response::MessageResponse {
+        const TYPE: crate::WireType = crate::WireType::LengthDelimited;
+
+        fn merge(
+            &mut self,
+            _: &mut crate::Bytes,
+        ) -> ::std::result::Result<(), crate::DecodeError> {
+            panic!("Not supported")
+        }
+
+        fn encode_value(&self, _: &mut crate::BytesMut) {
+            panic!("Not supported")
+        }
+
+        #[inline]
+        /// Encodes the message to a buffer.
+        fn serialize(
+            &self,
+            _: u32,
+            _: crate::types::DefaultValue<&Self>,
+            dst: &mut crate::BytesMut,
+        ) {
+            match *self {
+                server_reflection_response::MessageResponse::FileDescriptorResponse(ref value) => {
+                    crate::NativeType::serialize(
+                        value,
+                        4,
+                        crate::types::DefaultValue::Unknown,
+                        dst,
+                    )
+                }
+                server_reflection_response::MessageResponse::AllExtensionNumbersResponse(
+                    ref value,
+                ) => crate::NativeType::serialize(
+                    value,
+                    5,
+                    crate::types::DefaultValue::Unknown,
+                    dst,
+                ),
+                server_reflection_response::MessageResponse::ListServicesResponse(ref value) => {
+                    crate::NativeType::serialize(
+                        value,
+                        6,
+                        crate::types::DefaultValue::Unknown,
+                        dst,
+                    )
+                }
+                server_reflection_response::MessageResponse::ErrorResponse(ref value) => {
+                    crate::NativeType::serialize(
+                        value,
+                        7,
+                        crate::types::DefaultValue::Unknown,
+                        dst,
+                    )
+                }
+            }
+        }
+
+        #[inline]
+        /// Decodes an instance of the message from a buffer, and merges it into self.
+        fn deserialize(
+            &mut self,
+            tag: u32,
+            wire_type: crate::WireType,
+            src: &mut crate::Bytes,
+        ) -> ::std::result::Result<(), crate::DecodeError> {
+            *self = match tag {
+                4 => server_reflection_response::MessageResponse::FileDescriptorResponse(
+                    crate::NativeType::deserialize_default(4, wire_type, src)?,
+                ),
+                5 => server_reflection_response::MessageResponse::AllExtensionNumbersResponse(
+                    crate::NativeType::deserialize_default(5, wire_type, src)?,
+                ),
+                6 => server_reflection_response::MessageResponse::ListServicesResponse(
+                    crate::NativeType::deserialize_default(6, wire_type, src)?,
+                ),
+                7 => server_reflection_response::MessageResponse::ErrorResponse(
+                    crate::NativeType::deserialize_default(7, wire_type, src)?,
+                ),
+                _ => unreachable!("invalid MessageResponse, tag: {}", tag),
+            };
+            Ok(())
+        }
+
+        #[inline]
+        /// Returns the encoded length of the message without a length delimiter.
+        fn serialized_len(&self, _: u32, _: crate::types::DefaultValue<&Self>) -> usize {
+            match *self {
+                server_reflection_response::MessageResponse::FileDescriptorResponse(ref value) => {
+                    crate::NativeType::serialized_len(
+                        value,
+                        4,
+                        crate::types::DefaultValue::Unknown,
+                    )
+                }
+                server_reflection_response::MessageResponse::AllExtensionNumbersResponse(
+                    ref value,
+                ) => crate::NativeType::serialized_len(
+                    value,
+                    5,
+                    crate::types::DefaultValue::Unknown,
+                ),
+                server_reflection_response::MessageResponse::ListServicesResponse(ref value) => {
+                    crate::NativeType::serialized_len(
+                        value,
+                        6,
+                        crate::types::DefaultValue::Unknown,
+                    )
+                }
+                server_reflection_response::MessageResponse::ErrorResponse(ref value) => {
+                    crate::NativeType::serialized_len(
+                        value,
+                        7,
+                        crate::types::DefaultValue::Unknown,
+                    )
+                }
+            }
+        }
+    }
+
+    impl ::std::default::Default for server_reflection_response::MessageResponse {
+        #[inline]
+        fn default() -> Self {
+            server_reflection_response::MessageResponse::FileDescriptorResponse(
+                ::std::default::Default::default(),
+            )
+        }
+    }
+
+    impl crate::Message for FileDescriptorResponse {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.file_descriptor_proto,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "FileDescriptorResponse";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(
+                        &mut msg.file_descriptor_proto,
+                        tag,
+                        wire_type,
+                        src,
+                    )
+                    .map_err(|err| err.push(STRUCT_NAME, "file_descriptor_proto"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.file_descriptor_proto,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for FileDescriptorResponse {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                file_descriptor_proto: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for ExtensionNumberResponse {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.base_type_name,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.extension_number,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ExtensionNumberResponse";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(
+                        &mut msg.base_type_name,
+                        tag,
+                        wire_type,
+                        src,
+                    )
+                    .map_err(|err| err.push(STRUCT_NAME, "base_type_name"))?,
+                    2 => crate::NativeType::deserialize(
+                        &mut msg.extension_number,
+                        tag,
+                        wire_type,
+                        src,
+                    )
+                    .map_err(|err| err.push(STRUCT_NAME, "extension_number"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.base_type_name,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.extension_number,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ExtensionNumberResponse {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                base_type_name: ::core::default::Default::default(),
+                extension_number: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for ListServiceResponse {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.service,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ListServiceResponse";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.service, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "service"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.service,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ListServiceResponse {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                service: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for ServiceResponse {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(&self.name, 1, crate::types::DefaultValue::Default, dst);
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ServiceResponse";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.name, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "name"))?,
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.name,
+                1,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ServiceResponse {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                name: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::Message for ErrorResponse {
+        #[inline]
+        fn write(&self, dst: &mut crate::BytesMut) {
+            crate::NativeType::serialize(
+                &self.error_code,
+                1,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+            crate::NativeType::serialize(
+                &self.error_message,
+                2,
+                crate::types::DefaultValue::Default,
+                dst,
+            );
+        }
+
+        #[inline]
+        fn read(src: &mut crate::Bytes) -> ::std::result::Result<Self, crate::DecodeError> {
+            const STRUCT_NAME: &str = "ErrorResponse";
+            let mut msg = Self::default();
+            while !src.is_empty() {
+                let (tag, wire_type) = crate::encoding::decode_key(src)?;
+                match tag {
+                    1 => crate::NativeType::deserialize(&mut msg.error_code, tag, wire_type, src)
+                        .map_err(|err| err.push(STRUCT_NAME, "error_code"))?,
+                    2 => {
+                        crate::NativeType::deserialize(&mut msg.error_message, tag, wire_type, src)
+                            .map_err(|err| err.push(STRUCT_NAME, "error_message"))?
+                    }
+                    _ => crate::encoding::skip_field(wire_type, tag, src)?,
+                }
+            }
+            Ok(msg)
+        }
+
+        #[inline]
+        fn encoded_len(&self) -> usize {
+            0 + crate::NativeType::serialized_len(
+                &self.error_code,
+                1,
+                crate::types::DefaultValue::Default,
+            ) + crate::NativeType::serialized_len(
+                &self.error_message,
+                2,
+                crate::types::DefaultValue::Default,
+            )
+        }
+    }
+
+    impl ::std::default::Default for ErrorResponse {
+        #[inline]
+        fn default() -> Self {
+            Self {
+                error_code: ::core::default::Default::default(),
+                error_message: ::core::default::Default::default(),
+            }
+        }
+    }
+
+    impl crate::ServiceDef for ServerReflection {
+        const NAME: &'static str = "grpc.reflection.v1.ServerReflection";
+        const FILE_DESCRIPTORS: &'static [&'static [u8]] = &[b"\n#grpc/reflection/v1/reflection.proto\x12\x12grpc.reflection.v1\"\xf3\x02\n\x17ServerReflectionRequest\x12\x12\n\x04host\x18\x01 \x01(\tR\x04host\x12*\n\x10file_by_filename\x18\x03 \x01(\tH\x00R\x0efileByFilename\x126\n\x16file_containing_symbol\x18\x04 \x01(\tH\x00R\x14fileContainingSymbol\x12b\n\x19file_containing_extension\x18\x05 \x01(\x0b2$.grpc.reflection.v1.ExtensionRequestH\x00R\x17fileContainingExtension\x12B\n\x1dall_extension_numbers_of_type\x18\x06 \x01(\tH\x00R\x19allExtensionNumbersOfType\x12%\n\rlist_services\x18\x07 \x01(\tH\x00R\x0clistServicesB\x11\n\x0fmessage_request\"f\n\x10ExtensionRequest\x12\'\n\x0fcontaining_type\x18\x01 \x01(\tR\x0econtainingType\x12)\n\x10extension_number\x18\x02 \x01(\x05R\x0fextensionNumber\"\xae\x04\n\x18ServerReflectionResponse\x12\x1d\n\nvalid_host\x18\x01 \x01(\tR\tvalidHost\x12V\n\x10original_request\x18\x02 \x01(\x0b2+.grpc.reflection.v1.ServerReflectionRequestR\x0foriginalRequest\x12f\n\x18file_descriptor_response\x18\x04 \x01(\x0b2*.grpc.reflection.v1.FileDescriptorResponseH\x00R\x16fileDescriptorResponse\x12r\n\x1eall_extension_numbers_response\x18\x05 \x01(\x0b2+.grpc.reflection.v1.ExtensionNumberResponseH\x00R\x1ballExtensionNumbersResponse\x12_\n\x16list_services_response\x18\x06 \x01(\x0b2\'.grpc.reflection.v1.ListServiceResponseH\x00R\x14listServicesResponse\x12J\n\x0eerror_response\x18\x07 \x01(\x0b2!.grpc.reflection.v1.ErrorResponseH\x00R\rerrorResponseB\x12\n\x10message_response\"L\n\x16FileDescriptorResponse\x122\n\x15file_descriptor_proto\x18\x01 \x03(\x0cR\x13fileDescriptorProto\"j\n\x17ExtensionNumberResponse\x12$\n\x0ebase_type_name\x18\x01 \x01(\tR\x0cbaseTypeName\x12)\n\x10extension_number\x18\x02 \x03(\x05R\x0fextensionNumber\"T\n\x13ListServiceResponse\x12=\n\x07service\x18\x01 \x03(\x0b2#.grpc.reflection.v1.ServiceResponseR\x07service\"%\n\x0fServiceResponse\x12\x12\n\x04name\x18\x01 \x01(\tR\x04name\"S\n\rErrorResponse\x12\x1d\n\nerror_code\x18\x01 \x01(\x05R\terrorCode\x12#\n\rerror_message\x18\x02 \x01(\tR\x0cerrorMessage2\x89\x01\n\x10ServerReflection\x12u\n\x14ServerReflectionInfo\x12+.grpc.reflection.v1.ServerReflectionRequest\x1a,.grpc.reflection.v1.ServerReflectionResponse(\x010\x01Bf\n\x15io.grpc.reflection.v1B\x15ServerReflectionProtoP\x01Z4google.golang.org/grpc/reflection/grpc_reflection_v1b\x06proto3"];
+        type Methods = ServerReflectionMethods;
+
+        #[inline]
+        fn method_by_name(name: &str) -> Option<Self::Methods> {
+            use crate::MethodDef;
+            match name {
+                ServerReflectionServerReflectionInfoMethod::NAME => {
+                    Some(ServerReflectionMethods::ServerReflectionInfo(
+                        ServerReflectionServerReflectionInfoMethod,
+                    ))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    impl<T> ServerReflectionClient<T> {
+        #[inline]
+        /// Create new client instance
+        pub fn new(transport: T) -> Self {
+            Self(transport)
+        }
+    }
+
+    impl<T> crate::client::ClientInformation<T> for ServerReflectionClient<T> {
+        #[inline]
+        /// Create new client instance
+        fn create(transport: T) -> Self {
+            Self(transport)
+        }
+
+        #[inline]
+        /// Get referece to underlying transport
+        fn transport(&self) -> &T {
+            &self.0
+        }
+
+        #[inline]
+        /// Get mut referece to underlying transport
+        fn transport_mut(&mut self) -> &mut T {
+            &mut self.0
+        }
+
+        #[inline]
+        /// Consume client and return inner transport
+        fn into_inner(self) -> T {
+            self.0
+        }
+    }
+
+    impl<T: crate::client::StreamingTransport<ServerReflectionServerReflectionInfoMethod>>
+        ServerReflectionClient<T>
+    {
+        ///  The reflection service is structured as a bidirectional stream, ensuring
+        ///  all related requests go to a single server.
+        pub fn server_reflection_info(
+            &self,
+        ) -> crate::client::BidiStreamingRequest<'_, T, ServerReflectionServerReflectionInfoMethod>
+        {
+            crate::client::BidiStreamingRequest::new(&self.0)
+        }
+    }
+}
@@ -0,0 +1,157 @@
+#![allow(async_fn_in_trait)]
+
+use std::{fmt, rc::Rc, task::Context, task::Poll};
+
+use ntex_bytes::ByteString;
+use ntex_service::{Middleware, Service, ServiceCtx, ServiceFactory};
+
+use crate::Status;
+
+use super::{ServerError, ServerRequest, ServerResponse};
+
+/// Server call interceptor
+///
+/// Interceptor sees the call before it is dispatched to the service
+/// and the service result after. Use `GrpcServer::interceptor()` to apply it.
+pub trait Interceptor {
+    /// Inspect or modify request before it is dispatched to the service
+    ///
+    /// Returning error status short-circuits the call, service is not called.
+    async fn request(&self, req: &mut ServerRequest) -> Result<(), Status> {
+        let _ = req;
+        Ok(())
+    }
+
+    /// Inspect or modify service response or error
+    async fn response(
+        &self,
+        name: &ByteString,
+        res: Result<ServerResponse, ServerError>,
+    ) -> Result<ServerResponse, ServerError> {
+        let _ = name;
+        res
+    }
+}
+
+/// Middleware that wraps service with interceptor
+pub struct Intercept<I>(Rc<I>);
+
+impl<I> Intercept<I> {
+    /// Create interceptor middleware
+    pub fn new(interceptor: I) -> Self {
+        Self(Rc::new(interceptor))
+    }
+}
+
+impl<I> Clone for Intercept<I> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<I> fmt::Debug for Intercept<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Intercept").finish()
+    }
+}
+
+impl<I, S> Middleware<S> for Intercept<I> {
+    type Service = InterceptService<I, S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        InterceptService {
+            service,
+            interceptor: self.0.clone(),
+        }
+    }
+}
+
+/// Service that calls interceptor around inner service
+pub struct InterceptService<I, S> {
+    service: S,
+    interceptor: Rc<I>,
+}
+
+impl<I, S: Clone> Clone for InterceptService<I, S> {
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            interceptor: self.interceptor.clone(),
+        }
+    }
+}
+
+impl<I, S> fmt::Debug for InterceptService<I, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InterceptService").finish()
+    }
+}
+
+impl<I, S> Service<ServerRequest> for InterceptService<I, S>
+where
+    I: Interceptor,
+    S: Service<ServerRequest, Response = ServerResponse, Error = ServerError>,
+{
+    type Response = ServerResponse;
+    type Error = ServerError;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.service.poll_shutdown(cx)
+    }
+
+    async fn call(
+        &self,
+        mut req: ServerRequest,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<ServerResponse, ServerError> {
+        self.interceptor.request(&mut req).await?;
+
+        let name = req.name.clone();
+        let res = ctx.call(&self.service, req).await;
+        self.interceptor.response(&name, res).await
+    }
+}
+
+/// Service factory that applies middleware to services of inner factory
+pub struct ApplyMiddleware<M, T> {
+    middleware: M,
+    factory: Rc<T>,
+}
+
+impl<M, T> ApplyMiddleware<M, T> {
+    pub(super) fn new(middleware: M, factory: Rc<T>) -> Self {
+        Self {
+            middleware,
+            factory,
+        }
+    }
+}
+
+impl<M, T> fmt::Debug for ApplyMiddleware<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApplyMiddleware").finish()
+    }
+}
+
+impl<M, T> ServiceFactory<ServerRequest> for ApplyMiddleware<M, T>
+where
+    T: ServiceFactory<ServerRequest>,
+    M: Middleware<T::Service>,
+    M::Service: Service<ServerRequest>,
+{
+    type Response = <M::Service as Service<ServerRequest>>::Response;
+    type Error = <M::Service as Service<ServerRequest>>::Error;
+
+    type Service = M::Service;
+    type InitError = T::InitError;
+
+    async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        Ok(self.middleware.create(self.factory.create(()).await?))
+    }
+}
@@ -1,11 +1,25 @@
 use std::ops;
+use std::time::{Duration, Instant};
 
-use ntex_bytes::{ByteString, Bytes};
+use ntex_bytes::{ByteString, Bytes, BytesMut};
 use ntex_http::{HeaderMap, HeaderName, HeaderValue};
 
+mod middleware;
+mod router;
 mod service;
+mod streaming;
+#[cfg(feature = "web")]
+mod web;
 
+pub use self::middleware::{ApplyMiddleware, Intercept, InterceptService, Interceptor};
+pub use self::router::{NamedService, Router, RouterService};
 pub use self::service::{GrpcServer, GrpcService};
+pub use self::streaming::{Payload, ResponseStream, Streaming};
+#[cfg(feature = "web")]
+pub use self::web::{GrpcWebServer, GrpcWebService};
+
+use self::streaming::EncodeStream;
+use crate::{service::MethodDef, GrpcStatus, Status};
 
 #[derive(thiserror::Error, Clone, Debug)]
 pub enum ServerError {
@@ -15,19 +29,39 @@ pub enum ServerError {
     NotFound(ByteString),
     #[error("Service method is not implemented: {0}")]
     NotImplemented(ByteString),
+    #[error("{0}")]
+    Status(#[from] crate::Status),
+}
+
+impl From<ServerError> for Status {
+    fn from(err: ServerError) -> Status {
+        match err {
+            ServerError::Decode(err) => Status::new(GrpcStatus::InvalidArgument, err.to_string()),
+            ServerError::NotFound(_) | ServerError::NotImplemented(_) => {
+                Status::new(GrpcStatus::Unimplemented, err.to_string())
+            }
+            ServerError::Status(status) => status,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct ServerRequest {
+    /// Full name of the service, `package.Service`
+    pub service: ByteString,
     pub name: ByteString,
-    pub payload: Bytes,
+    pub payload: Payload,
     pub headers: HeaderMap,
+    /// Call deadline from `grpc-timeout` header
+    pub deadline: Option<Instant>,
 }
 
 #[derive(Debug)]
 pub struct ServerResponse {
     pub payload: Bytes,
     pub headers: Vec<(HeaderName, HeaderValue)>,
+    /// Response messages for streaming methods, `payload` is not used
+    pub stream: Option<ResponseStream<Bytes>>,
 }
 
 impl ServerResponse {
@@ -41,7 +75,27 @@ impl ServerResponse {
         payload: Bytes,
         headers: Vec<(HeaderName, HeaderValue)>,
     ) -> ServerResponse {
-        ServerResponse { payload, headers }
+        ServerResponse {
+            payload,
+            headers,
+            stream: None,
+        }
+    }
+
+    /// Create response for server streaming method
+    pub fn streaming<M>(
+        method: M,
+        stream: ResponseStream<M::Output>,
+        headers: Vec<(HeaderName, HeaderValue)>,
+    ) -> ServerResponse
+    where
+        M: MethodDef + 'static,
+    {
+        ServerResponse {
+            headers,
+            payload: Bytes::new(),
+            stream: Some(ResponseStream::new(EncodeStream { method, stream })),
+        }
     }
 }
 
@@ -53,6 +107,8 @@ pub struct Request<T> {
     pub name: ByteString,
     pub headers: HeaderMap,
     pub message: T,
+    /// Call deadline, call is aborted with `DeadlineExceeded` status after deadline
+    pub deadline: Option<Instant>,
 }
 
 impl<T> FromRequest<T> for T {
@@ -73,6 +129,12 @@ impl<T> Request<T> {
     pub fn into_inner(self) -> T {
         self.message
     }
+
+    /// Time remaining until call deadline
+    pub fn timeout(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
 }
 
 impl<T> ops::Deref for Request<T> {
@@ -113,3 +175,139 @@ impl<T> From<T> for Response<T> {
         }
     }
 }
+
+/// Conversion of service method result to response
+///
+/// Service methods could return message, `Response`, or `Result` of either
+/// of them with `Status` error.
+pub trait IntoResponse<T> {
+    fn into_response(self) -> Result<Response<T>, Status>;
+}
+
+impl<T> IntoResponse<T> for T {
+    #[inline]
+    fn into_response(self) -> Result<Response<T>, Status> {
+        Ok(Response::new(self))
+    }
+}
+
+impl<T> IntoResponse<T> for Response<T> {
+    #[inline]
+    fn into_response(self) -> Result<Response<T>, Status> {
+        Ok(self)
+    }
+}
+
+impl<T> IntoResponse<T> for Result<T, Status> {
+    #[inline]
+    fn into_response(self) -> Result<Response<T>, Status> {
+        self.map(Response::new)
+    }
+}
+
+impl<T> IntoResponse<T> for Result<Response<T>, Status> {
+    #[inline]
+    fn into_response(self) -> Result<Response<T>, Status> {
+        self
+    }
+}
+
+/// Request of service method
+///
+/// Implemented for unary and client streaming methods, `STREAMING` parameter
+/// is method's `MethodDef::CLIENT_STREAMING` value. Used by `server` macro.
+pub trait MethodRequest<const STREAMING: bool>: MethodDef {
+    type Message;
+
+    /// Read request message from payload
+    ///
+    /// Trailers of unary request are merged into request headers.
+    #[allow(async_fn_in_trait)]
+    async fn request(&self, req: ServerRequest) -> Result<Request<Self::Message>, ServerError>;
+}
+
+impl<M: MethodDef> MethodRequest<false> for M {
+    type Message = M::Input;
+
+    async fn request(&self, req: ServerRequest) -> Result<Request<M::Input>, ServerError> {
+        let ServerRequest {
+            name,
+            mut headers,
+            mut payload,
+            deadline,
+            ..
+        } = req;
+        let message = self.decode(&mut payload.message().await?)?;
+        if let Some(trailers) = payload.trailers() {
+            for (name, val) in trailers.iter() {
+                headers.insert(name.clone(), val.clone());
+            }
+        }
+
+        Ok(Request {
+            name,
+            headers,
+            message,
+            deadline,
+        })
+    }
+}
+
+impl<M: MethodDef + Clone + 'static> MethodRequest<true> for M {
+    type Message = Streaming<M::Input>;
+
+    async fn request(
+        &self,
+        req: ServerRequest,
+    ) -> Result<Request<Streaming<M::Input>>, ServerError> {
+        Ok(Request {
+            name: req.name,
+            headers: req.headers,
+            message: Streaming::new(self.clone(), req.payload),
+            deadline: req.deadline,
+        })
+    }
+}
+
+/// Response of service method
+///
+/// Implemented for unary and server streaming methods, `STREAMING` parameter
+/// is method's `MethodDef::SERVER_STREAMING` value. Used by `server` macro.
+pub trait MethodResponse<const STREAMING: bool>: MethodDef {
+    type Message;
+
+    /// Convert service method result to response
+    fn response<R>(&self, result: R) -> Result<ServerResponse, ServerError>
+    where
+        R: IntoResponse<Self::Message>;
+}
+
+impl<M: MethodDef> MethodResponse<false> for M {
+    type Message = M::Output;
+
+    fn response<R>(&self, result: R) -> Result<ServerResponse, ServerError>
+    where
+        R: IntoResponse<M::Output>,
+    {
+        let response = result.into_response()?;
+        let mut buf = BytesMut::new();
+        self.encode(response.message, &mut buf);
+        Ok(ServerResponse::with_headers(buf.freeze(), response.headers))
+    }
+}
+
+impl<M: MethodDef + Clone + 'static> MethodResponse<true> for M {
+    type Message = ResponseStream<M::Output>;
+
+    fn response<R>(&self, result: R) -> Result<ServerResponse, ServerError>
+    where
+        R: IntoResponse<ResponseStream<M::Output>>,
+    {
+        let response = result.into_response()?;
+        Ok(ServerResponse::streaming(
+            self.clone(),
+            response.message,
+            response.headers,
+        ))
+    }
+}
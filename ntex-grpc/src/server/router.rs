@@ -0,0 +1,146 @@
+use std::{fmt, rc::Rc, task::Context, task::Poll};
+
+use ntex_service::boxed::{self, BoxService, BoxServiceFactory};
+use ntex_service::{Service, ServiceCtx, ServiceFactory};
+use ntex_util::HashMap;
+
+use super::{ServerError, ServerRequest, ServerResponse};
+
+type Factory = BoxServiceFactory<(), ServerRequest, ServerResponse, ServerError, ()>;
+type BoxedService = BoxService<ServerRequest, ServerResponse, ServerError>;
+
+/// Service that implements grpc service definition
+///
+/// Implemented by `#[server]` macro, service is registered in [`Router`]
+/// by full name of the service.
+pub trait NamedService {
+    /// Full name of the service, `package.Service`
+    const NAME: &'static str;
+}
+
+/// Routes calls to services by full name of the service
+///
+/// Calls to unknown services fail with `Unimplemented` status.
+///
+/// ```rust,ignore
+/// GrpcServer::new(
+///     Router::new()
+///         .service(GreeterServer)
+///         .service(HealthServer::new(reporter.clone())),
+/// )
+/// ```
+#[derive(Default)]
+pub struct Router {
+    services: Vec<(&'static str, Factory)>,
+}
+
+impl Router {
+    /// Create empty router
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register service factory
+    ///
+    /// Service registered last wins if services have the same name.
+    pub fn service<F>(mut self, factory: F) -> Self
+    where
+        F: ServiceFactory<
+                ServerRequest,
+                Response = ServerResponse,
+                Error = ServerError,
+                InitError = (),
+            > + NamedService
+            + 'static,
+    {
+        self.services.retain(|(name, _)| *name != F::NAME);
+        self.services.push((F::NAME, boxed::factory(factory)));
+        self
+    }
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Router")
+            .field(
+                "services",
+                &self
+                    .services
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl ServiceFactory<ServerRequest> for Router {
+    type Response = ServerResponse;
+    type Error = ServerError;
+    type Service = RouterService;
+    type InitError = ();
+
+    async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        let mut services = HashMap::default();
+        for (name, factory) in &self.services {
+            services.insert(*name, factory.create(()).await?);
+        }
+        Ok(RouterService(Rc::new(services)))
+    }
+}
+
+/// Service that dispatches calls to registered services
+#[derive(Clone)]
+pub struct RouterService(Rc<HashMap<&'static str, BoxedService>>);
+
+impl fmt::Debug for RouterService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouterService")
+            .field("services", &self.0.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Service<ServerRequest> for RouterService {
+    type Response = ServerResponse;
+    type Error = ServerError;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let mut ready = true;
+        for srv in self.0.values() {
+            ready &= srv.poll_ready(cx)?.is_ready();
+        }
+        if ready {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn poll_shutdown(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut ready = true;
+        for srv in self.0.values() {
+            ready &= srv.poll_shutdown(cx).is_ready();
+        }
+        if ready {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    async fn call(
+        &self,
+        req: ServerRequest,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<ServerResponse, ServerError> {
+        if let Some(srv) = self.0.get(req.service.as_str()) {
+            ctx.call(srv, req).await
+        } else {
+            log::debug!("Service is not found: {}", req.service);
+            Err(ServerError::NotFound(
+                format!("/{}/{}", req.service, req.name).into(),
+            ))
+        }
+    }
+}
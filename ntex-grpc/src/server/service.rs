@@ -1,19 +1,110 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::Cell, cell::RefCell, rc::Rc, time::Duration, time::Instant};
 
-use ntex_bytes::{Buf, BufMut, ByteString, BytesMut};
-use ntex_h2::{self as h2, frame::Reason, frame::StreamId};
-use ntex_http::{HeaderMap, HeaderValue, StatusCode};
+use ntex_bytes::{ByteString, BytesMut};
+use ntex_h2::{self as h2, frame::StreamId};
+use ntex_http::{header, HeaderMap, StatusCode};
 use ntex_io::{Filter, Io, IoBoxed};
 use ntex_service::{Service, ServiceCtx, ServiceFactory};
-use ntex_util::HashMap;
+use ntex_util::channel::oneshot;
+use ntex_util::future::{select, stream_recv, Either};
+use ntex_util::{time, HashMap};
 
-use crate::{consts, status::GrpcStatus, utils::Data};
+use crate::compression::{Compressors, Encoder};
+use crate::status::{GrpcStatus, Status};
+use crate::{consts, utils, utils::Data, DecodeError};
 
-use super::{ServerError, ServerRequest, ServerResponse};
+use super::middleware::{ApplyMiddleware, Intercept, Interceptor};
+use super::streaming::PayloadSender;
+#[cfg(feature = "web")]
+use super::GrpcWebServer;
+use super::{Payload, ServerError, ServerRequest, ServerResponse};
 
 /// Grpc server
+///
+/// Serves `application/grpc` requests over h2. Requests with other content
+/// types, including gRPC-Web, are rejected with http `415 Unsupported Media Type`
+/// response, gRPC-Web requests are served by `GrpcServer::web()` service.
 pub struct GrpcServer<T> {
     factory: Rc<T>,
+    config: Rc<ServerConfig>,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct ServerConfig {
+    pub(super) compressors: Compressors,
+    pub(super) max_recv_message_size: usize,
+    pub(super) max_send_message_size: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            compressors: Compressors::default(),
+            max_recv_message_size: consts::DEFAULT_MAX_MESSAGE_SIZE,
+            max_send_message_size: consts::DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+}
+
+/// Call parameters from request path and headers
+pub(super) struct CallInfo {
+    pub(super) service: ByteString,
+    pub(super) method: ByteString,
+    /// Decoder of request messages
+    pub(super) decoder: Encoder,
+    /// Encoder of response messages, selected from `grpc-accept-encoding`
+    pub(super) encoder: Encoder,
+    pub(super) timeout: Option<Duration>,
+}
+
+impl ServerConfig {
+    /// Parse call parameters, used by grpc and grpc-web transports
+    pub(super) fn prepare_call(
+        &self,
+        headers: &HeaderMap,
+        mut path: ByteString,
+    ) -> Result<CallInfo, Status> {
+        let mut path = path.split_off(1);
+        let service = if let Some(n) = path.find('/') {
+            path.split_to(n)
+        } else {
+            return Err(Status::new(
+                GrpcStatus::Unimplemented,
+                format!("Service is not found: {}", path),
+            ));
+        };
+
+        let mut path = path.split_off(1);
+        let method = if let Some(n) = path.find('/') {
+            path.split_to(n)
+        } else {
+            path
+        };
+
+        let decoder = self
+            .compressors
+            .decoder(headers.get(consts::GRPC_ENCODING))
+            .ok_or_else(|| Status::new(GrpcStatus::Unimplemented, "Unsupported grpc-encoding"))?;
+        let encoder = self
+            .compressors
+            .select(headers.get(consts::GRPC_ACCEPT_ENCODING));
+
+        let timeout = if let Some(val) = headers.get(consts::GRPC_TIMEOUT) {
+            Some(utils::parse_timeout(val).ok_or_else(|| {
+                Status::new(GrpcStatus::InvalidArgument, "Malformed grpc-timeout")
+            })?)
+        } else {
+            None
+        };
+
+        Ok(CallInfo {
+            service,
+            method,
+            decoder,
+            encoder,
+            timeout,
+        })
+    }
 }
 
 impl<T> GrpcServer<T> {
@@ -21,8 +112,65 @@ impl<T> GrpcServer<T> {
     pub fn new(factory: T) -> Self {
         Self {
             factory: Rc::new(factory),
+            config: Rc::new(ServerConfig::default()),
         }
     }
+
+    /// Set registry of supported compressors
+    ///
+    /// Response encoding is selected from client's `grpc-accept-encoding` list.
+    /// By default registry is empty and messages are not compressed.
+    pub fn compressors(mut self, compressors: Compressors) -> Self {
+        Rc::make_mut(&mut self.config).compressors = compressors;
+        self
+    }
+
+    /// Set max size of received message
+    ///
+    /// Call fails with `ResourceExhausted` status if request message is larger.
+    /// By default max size is 4Mb.
+    pub fn max_recv_message_size(mut self, size: usize) -> Self {
+        Rc::make_mut(&mut self.config).max_recv_message_size = size;
+        self
+    }
+
+    /// Set max size of sent message
+    ///
+    /// Call fails with `ResourceExhausted` status if response message is larger.
+    /// By default max size is 4Mb.
+    pub fn max_send_message_size(mut self, size: usize) -> Self {
+        Rc::make_mut(&mut self.config).max_send_message_size = size;
+        self
+    }
+
+    /// Wrap services with middleware
+    ///
+    /// Middleware applied last is called first.
+    pub fn middleware<M>(self, middleware: M) -> GrpcServer<ApplyMiddleware<M, T>> {
+        GrpcServer {
+            factory: Rc::new(ApplyMiddleware::new(middleware, self.factory)),
+            config: self.config,
+        }
+    }
+
+    /// Wrap services with call interceptor
+    ///
+    /// Interceptor applied last is called first.
+    pub fn interceptor<I>(self, interceptor: I) -> GrpcServer<ApplyMiddleware<Intercept<I>, T>>
+    where
+        I: Interceptor,
+    {
+        self.middleware(Intercept::new(interceptor))
+    }
+
+    /// Convert to gRPC-Web server
+    ///
+    /// gRPC-Web server is a service of ntex http server, it serves
+    /// `application/grpc-web` and `application/grpc-web-text` requests.
+    #[cfg(feature = "web")]
+    pub fn web(self) -> GrpcWebServer<T> {
+        GrpcWebServer::new(self.factory, self.config)
+    }
 }
 
 impl<T> GrpcServer<T>
@@ -34,6 +182,7 @@ where
     pub fn make_server(&self) -> GrpcService<T> {
         GrpcService {
             factory: self.factory.clone(),
+            config: self.config.clone(),
         }
     }
 }
@@ -56,6 +205,7 @@ where
 
 pub struct GrpcService<T> {
     factory: Rc<T>,
+    config: Rc<ServerConfig>,
 }
 
 impl<T, F> Service<Io<F>> for GrpcService<T>
@@ -74,7 +224,7 @@ where
             io.into(),
             h2::Config::server(),
             ControlService,
-            PublishService::new(service),
+            PublishService::new(service, self.config.clone()),
         )
         .await;
 
@@ -97,7 +247,7 @@ where
             io,
             h2::Config::server(),
             ControlService,
-            PublishService::new(service),
+            PublishService::new(service, self.config.clone()),
         )
         .await;
 
@@ -123,23 +273,28 @@ impl Service<h2::ControlMessage<h2::StreamError>> for ControlService {
 
 struct PublishService<S: Service<ServerRequest>> {
     service: S,
+    config: Rc<ServerConfig>,
     streams: RefCell<HashMap<StreamId, Inflight>>,
 }
 
 struct Inflight {
-    name: ByteString,
-    service: ByteString,
     data: Data,
-    headers: HeaderMap,
+    encoder: Encoder,
+    max_size: usize,
+    /// Request messages sender, `None` after end of request stream
+    tx: Option<PayloadSender>,
+    /// Aborts service call with status, dropping sender cancels service call
+    abort: Option<oneshot::Sender<Status>>,
 }
 
 impl<S> PublishService<S>
 where
     S: Service<ServerRequest, Response = ServerResponse, Error = ServerError>,
 {
-    fn new(service: S) -> Self {
+    fn new(service: S, config: Rc<ServerConfig>) -> Self {
         Self {
             service,
+            config,
             streams: RefCell::new(HashMap::default()),
         }
     }
@@ -158,7 +313,6 @@ where
         ctx: ServiceCtx<'_, Self>,
     ) -> Result<Self::Response, Self::Error> {
         let id = msg.id();
-        let mut streams = self.streams.borrow_mut();
         let h2::Message { stream, kind } = msg;
 
         match kind {
@@ -167,143 +321,295 @@ where
                 pseudo,
                 eof,
             } => {
-                let mut path = pseudo.path.unwrap().split_off(1);
-                let srvname = if let Some(n) = path.find('/') {
-                    path.split_to(n)
-                } else {
-                    // not found
-                    let _ =
-                        stream.send_response(StatusCode::NOT_FOUND, HeaderMap::default(), true);
-                    return Ok(());
-                };
-
-                // stream eof, cannot do anything
-                if eof {
-                    if stream
-                        .send_response(StatusCode::OK, HeaderMap::default(), false)
-                        .is_ok()
-                    {
-                        let mut trailers = HeaderMap::default();
-                        trailers.insert(consts::GRPC_STATUS, GrpcStatus::InvalidArgument.into());
-                        trailers.insert(
-                            consts::GRPC_MESSAGE,
-                            HeaderValue::from_static("Cannot decode request message"),
-                        );
-                        stream.send_trailers(trailers);
-                    }
+                let compressors = &self.config.compressors;
+
+                // non-grpc requests, grpc-web is served by `GrpcWebServer`
+                if !headers
+                    .get(header::CONTENT_TYPE)
+                    .map(|val| {
+                        val.as_bytes().starts_with(b"application/grpc")
+                            && !val.as_bytes().starts_with(b"application/grpc-web")
+                    })
+                    .unwrap_or(false)
+                {
+                    let _ = stream.send_response(
+                        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                        HeaderMap::default(),
+                        true,
+                    );
                     return Ok(());
                 }
 
-                let mut path = path.split_off(1);
-                let methodname = if let Some(n) = path.find('/') {
-                    path.split_to(n)
-                } else {
-                    path
+                // errors before any response message are sent as trailers-only response
+                let mut hdrs = HeaderMap::default();
+                hdrs.insert(consts::GRPC_ACCEPT_ENCODING, compressors.accept_encoding());
+                let mut responder =
+                    Responder::new(&stream, hdrs, self.config.max_send_message_size);
+
+                let CallInfo {
+                    service,
+                    method,
+                    decoder,
+                    encoder,
+                    timeout,
+                } = match self.config.prepare_call(&headers, pseudo.path.unwrap()) {
+                    Ok(info) => info,
+                    Err(status) => {
+                        responder.send_status(status);
+                        return Ok(());
+                    }
                 };
+                responder.encoder = encoder;
 
-                let _ = streams.insert(
+                // request messages are delivered to the service as they arrive
+                let (tx, payload) = Payload::channel();
+                let (abort_tx, abort) = oneshot::channel();
+                let _ = self.streams.borrow_mut().insert(
                     stream.id(),
                     Inflight {
-                        headers,
+                        encoder: decoder,
+                        max_size: self.config.max_recv_message_size,
+                        tx: if eof { None } else { Some(tx) },
                         data: Data::Empty,
-                        name: methodname,
-                        service: srvname,
+                        abort: Some(abort_tx),
                     },
                 );
+
+                log::debug!("Call service {} method {}", service, method);
+                let req = ServerRequest {
+                    payload,
+                    headers,
+                    service,
+                    name: method,
+                    deadline: timeout.map(|t| Instant::now() + t),
+                };
+
+                let call = async {
+                    let result = ctx.call(&self.service, req).await;
+                    responder.send_response(result).await;
+                };
+                let call = async {
+                    if let Some(timeout) = timeout {
+                        // abort service call after deadline
+                        if time::timeout(timeout, call).await.is_err() {
+                            log::debug!("Deadline exceeded for stream {:?}", id);
+                            responder.send_status(Status::new(
+                                GrpcStatus::DeadlineExceeded,
+                                "Deadline exceeded",
+                            ));
+                        }
+                    } else {
+                        call.await;
+                    }
+                };
+
+                // stream reset or disconnect cancels service call,
+                // abort is checked first, call fails once request stream is closed
+                match select(abort, call).await {
+                    Either::Left(Ok(status)) => {
+                        log::debug!("Service call is aborted for stream {:?}: {}", id, status);
+                        responder.send_status(status);
+                    }
+                    Either::Left(Err(_)) => {
+                        log::debug!("Service call is canceled for stream {:?}", id);
+                    }
+                    Either::Right(_) => (),
+                }
+
+                // streaming calls receive request messages until response is complete
+                self.streams.borrow_mut().remove(&id);
             }
-            h2::MessageKind::Data(data, _cap) => {
-                if let Some(inflight) = streams.get_mut(&stream.id()) {
-                    inflight.data.push(data);
+            h2::MessageKind::Data(data, cap) => {
+                if let Some(inflight) = self.streams.borrow_mut().get_mut(&id) {
+                    if inflight.tx.is_some() {
+                        inflight.data.push(data);
+                        inflight.send_messages();
+                    }
+                    // stream window is released once service receives messages
+                    if let Some(ref tx) = inflight.tx {
+                        tx.hold(cap);
+                    }
                 }
             }
+            h2::MessageKind::Eof(h2::StreamEof::Error(err)) => {
+                self.streams.borrow_mut().remove(&id);
+                return Err(err);
+            }
             h2::MessageKind::Eof(data) => {
-                if let Some(mut inflight) = streams.remove(&id) {
-                    match data {
-                        h2::StreamEof::Data(chunk) => inflight.data.push(chunk),
-                        h2::StreamEof::Trailers(hdrs) => {
-                            for (name, val) in hdrs.iter() {
-                                inflight.headers.insert(name.clone(), val.clone());
-                            }
+                if let Some(inflight) = self.streams.borrow_mut().get_mut(&id) {
+                    let trailers = match data {
+                        h2::StreamEof::Data(chunk) => {
+                            inflight.data.push(chunk);
+                            None
+                        }
+                        h2::StreamEof::Trailers(hdrs) => Some(hdrs),
+                        h2::StreamEof::Error(_) => None,
+                    };
+                    inflight.send_messages();
+
+                    // end of request stream
+                    if let Some(tx) = inflight.tx.take() {
+                        if inflight.data.len() != 0 {
+                            tx.send(Err(ServerError::Decode(DecodeError::new(
+                                "Cannot decode request message: not enough data provided",
+                            ))));
+                        } else if let Some(hdrs) = trailers {
+                            tx.trailers(hdrs);
                         }
-                        h2::StreamEof::Error(err) => return Err(err),
                     }
+                }
+            }
+            h2::MessageKind::Disconnect(_) => {
+                self.streams.borrow_mut().remove(&id);
+            }
+        }
+        Ok(())
+    }
+}
 
-                    let mut data = inflight.data.get();
-                    let _compressed = data.get_u8();
-                    let len = data.get_u32();
-                    if (len as usize) > data.len() {
-                        if stream
-                            .send_response(StatusCode::OK, HeaderMap::default(), false)
-                            .is_ok()
-                        {
-                            let mut trailers = HeaderMap::default();
-                            trailers
-                                .insert(consts::GRPC_STATUS, GrpcStatus::InvalidArgument.into());
-                            trailers.insert(
-                                consts::GRPC_MESSAGE,
-                                HeaderValue::from_static(
-                                    "Cannot decode request message: not enough data provided",
-                                ),
-                            );
-                            stream.send_trailers(trailers);
+impl Inflight {
+    /// Deliver complete messages to the service
+    fn send_messages(&mut self) {
+        loop {
+            let msg = match self.data.next_message(self.max_size) {
+                Ok(Some((compressed, msg))) => {
+                    match self.encoder.decode(compressed, msg, self.max_size) {
+                        Ok(msg) if msg.len() > self.max_size => {
+                            return self.abort(utils::decompressed_size_exceeded(self.max_size))
                         }
-                        return Ok(());
-                    }
-                    let data = data
-                        .split_to_checked(len as usize)
-                        .ok_or(h2::StreamError::Reset(Reason::PROTOCOL_ERROR))?;
-
-                    log::debug!("Call service {} method {}", inflight.service, inflight.name);
-                    let req = ServerRequest {
-                        payload: data,
-                        name: inflight.name,
-                        headers: inflight.headers,
-                    };
-                    if stream
-                        .send_response(StatusCode::OK, HeaderMap::default(), false)
-                        .is_err()
-                    {
-                        return Ok(());
+                        Ok(msg) => Ok(msg),
+                        Err(err) => Err(ServerError::Decode(err)),
                     }
-                    drop(streams);
-
-                    match ctx.call(&self.service, req).await {
-                        Ok(res) => {
-                            log::debug!("Response is received {:?}", res);
-                            let mut buf = BytesMut::with_capacity(res.payload.len() + 5);
-                            buf.put_u8(0); // compression
-                            buf.put_u32(res.payload.len() as u32); // length
-                            buf.extend_from_slice(&res.payload);
-
-                            let _ = stream.send_payload(buf.freeze(), false).await;
-
-                            let mut trailers = HeaderMap::default();
-                            trailers.insert(consts::GRPC_STATUS, GrpcStatus::Ok.into());
-                            for (name, val) in res.headers {
-                                trailers.append(name, val);
-                            }
+                }
+                Ok(None) => return,
+                Err(status) => return self.abort(status),
+            };
+            if let Some(ref tx) = self.tx {
+                tx.send(msg);
+            }
+        }
+    }
 
-                            stream.send_trailers(trailers);
-                        }
-                        Err(err) => {
-                            let error = format!("Failure during service call: {}", err);
-                            log::debug!("{}", error);
-                            let mut trailers = HeaderMap::default();
-                            trailers.insert(consts::GRPC_STATUS, GrpcStatus::Aborted.into());
-                            if let Ok(val) = HeaderValue::from_str(&error) {
-                                trailers.insert(consts::GRPC_MESSAGE, val);
+    /// Stop receiving request messages and abort service call
+    fn abort(&mut self, status: Status) {
+        self.tx = None;
+        self.data = Data::Empty;
+        if let Some(abort) = self.abort.take() {
+            let _ = abort.send(status);
+        }
+    }
+}
+
+/// Sends response to the client
+///
+/// Response headers are sent with the first response message,
+/// if there are no messages status is sent as trailers-only response.
+struct Responder<'a> {
+    stream: &'a h2::StreamRef,
+    headers: Cell<Option<HeaderMap>>,
+    encoder: Encoder,
+    max_size: usize,
+}
+
+impl<'a> Responder<'a> {
+    fn new(stream: &'a h2::StreamRef, headers: HeaderMap, max_size: usize) -> Self {
+        Self {
+            stream,
+            max_size,
+            headers: Cell::new(Some(headers)),
+            encoder: Encoder::identity(),
+        }
+    }
+
+    /// Send service call result
+    async fn send_response(&self, result: Result<ServerResponse, ServerError>) {
+        match result {
+            Ok(res) => {
+                log::debug!("Response is received {:?}", res);
+                let ServerResponse {
+                    payload,
+                    headers,
+                    stream: messages,
+                } = res;
+
+                if let Some(mut messages) = messages {
+                    while let Some(item) = stream_recv(&mut messages).await {
+                        match item {
+                            Ok(payload) => {
+                                if !self.send_message(&payload).await {
+                                    return;
+                                }
+                            }
+                            Err(err) => {
+                                self.send_error(err);
+                                return;
                             }
-                            stream.send_trailers(trailers);
                         }
-                    };
+                    }
+                } else if !self.send_message(&payload).await {
+                    return;
+                }
 
-                    return Ok(());
+                let mut trailers = HeaderMap::default();
+                trailers.insert(consts::GRPC_STATUS, GrpcStatus::Ok.into());
+                for (name, val) in headers {
+                    trailers.append(name, val);
                 }
+                self.send_trailers(trailers);
             }
-            h2::MessageKind::Disconnect(_) => {
-                streams.remove(&id);
+            Err(err) => self.send_error(err),
+        }
+    }
+
+    /// Send response message, returns `false` if stream is closed
+    async fn send_message(&self, msg: &[u8]) -> bool {
+        if msg.len() > self.max_size {
+            self.send_status(utils::send_size_exceeded(msg.len(), self.max_size));
+            return false;
+        }
+
+        let mut buf = BytesMut::new();
+        if let Err(e) = self.encoder.encode(msg, &mut buf) {
+            log::error!("Cannot compress response message: {}", e);
+            self.send_status(Status::new(
+                GrpcStatus::Internal,
+                "Cannot compress response message",
+            ));
+            return false;
+        }
+
+        if let Some(mut headers) = self.headers.take() {
+            headers.insert(consts::GRPC_ENCODING, self.encoder.encoding().to_header());
+            if self
+                .stream
+                .send_response(StatusCode::OK, headers, false)
+                .is_err()
+            {
+                return false;
             }
         }
-        Ok(())
+        self.stream.send_payload(buf.freeze(), false).await.is_ok()
+    }
+
+    fn send_error(&self, err: ServerError) {
+        let status = Status::from(err);
+        log::debug!("Service call failed: {}", status);
+        self.send_status(status);
+    }
+
+    fn send_status(&self, status: Status) {
+        self.send_trailers(status.to_headers());
+    }
+
+    fn send_trailers(&self, trailers: HeaderMap) {
+        if let Some(mut headers) = self.headers.take() {
+            // trailers-only response
+            for (name, val) in trailers.iter() {
+                headers.append(name.clone(), val.clone());
+            }
+            let _ = self.stream.send_response(StatusCode::OK, headers, true);
+        } else {
+            self.stream.send_trailers(trailers);
+        }
     }
 }
@@ -0,0 +1,300 @@
+use std::task::{Context, Poll};
+use std::{cell::Cell, cell::RefCell, fmt, future::poll_fn, pin::Pin, rc::Rc};
+
+use ntex_bytes::{Bytes, BytesMut};
+use ntex_h2::Capacity;
+use ntex_http::HeaderMap;
+use ntex_util::{channel::mpsc, ready, Stream};
+
+use crate::{service::MethodDef, DecodeError, GrpcStatus, Status};
+
+use super::ServerError;
+
+/// Stream of raw request messages
+pub struct Payload {
+    kind: PayloadKind,
+    trailers: Option<HeaderMap>,
+    window: Option<Rc<RecvWindow>>,
+}
+
+enum PayloadKind {
+    Message(Option<Bytes>),
+    Stream(mpsc::Receiver<PayloadItem>),
+}
+
+enum PayloadItem {
+    Message(Result<Bytes, ServerError>),
+    Trailers(HeaderMap),
+}
+
+/// Flow-control window of request stream
+///
+/// Capacity of received data is not released to the peer while service
+/// has pending request messages, so peer cannot send more than stream
+/// window to the service that reads messages slowly.
+#[derive(Default)]
+struct RecvWindow {
+    capacity: RefCell<Option<Capacity>>,
+    pending: Cell<usize>,
+}
+
+impl RecvWindow {
+    /// Release held capacity if service has received all messages
+    fn release(&self) {
+        if self.pending.get() == 0 {
+            self.capacity.borrow_mut().take();
+        }
+    }
+}
+
+/// Sending side of request payload
+pub(super) struct PayloadSender {
+    tx: mpsc::Sender<PayloadItem>,
+    window: Rc<RecvWindow>,
+}
+
+impl PayloadSender {
+    /// Deliver request message
+    pub(super) fn send(&self, msg: Result<Bytes, ServerError>) {
+        if self.tx.send(PayloadItem::Message(msg)).is_ok() {
+            self.window.pending.set(self.window.pending.get() + 1);
+        }
+    }
+
+    /// Deliver request trailers
+    pub(super) fn trailers(&self, hdrs: HeaderMap) {
+        let _ = self.tx.send(PayloadItem::Trailers(hdrs));
+    }
+
+    /// Hold capacity of received data until service receives pending messages
+    pub(super) fn hold(&self, cap: Capacity) {
+        let mut capacity = self.window.capacity.borrow_mut();
+        *capacity = Some(match capacity.take() {
+            Some(prev) => prev + cap,
+            None => cap,
+        });
+        drop(capacity);
+        self.window.release();
+    }
+}
+
+impl Payload {
+    pub(super) fn channel() -> (PayloadSender, Payload) {
+        let (tx, rx) = mpsc::channel();
+        let window = Rc::new(RecvWindow::default());
+        let payload = Payload {
+            kind: PayloadKind::Stream(rx),
+            trailers: None,
+            window: Some(window.clone()),
+        };
+        (PayloadSender { tx, window }, payload)
+    }
+
+    /// Receive next request message
+    pub async fn recv(&mut self) -> Option<Result<Bytes, ServerError>> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Receive next request message
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, ServerError>>> {
+        match self.kind {
+            PayloadKind::Message(ref mut msg) => Poll::Ready(msg.take().map(Ok)),
+            PayloadKind::Stream(ref mut rx) => loop {
+                match ready!(rx.poll_recv(cx)) {
+                    Some(PayloadItem::Message(msg)) => {
+                        if let Some(ref window) = self.window {
+                            window.pending.set(window.pending.get() - 1);
+                            window.release();
+                        }
+                        return Poll::Ready(Some(msg));
+                    }
+                    Some(PayloadItem::Trailers(hdrs)) => self.trailers = Some(hdrs),
+                    None => return Poll::Ready(None),
+                }
+            },
+        }
+    }
+
+    /// Request trailers
+    ///
+    /// Trailers are available after the end of request stream.
+    pub fn trailers(&self) -> Option<&HeaderMap> {
+        self.trailers.as_ref()
+    }
+
+    /// Receive single request message
+    ///
+    /// Waits for the end of request stream. Returns decode error if request
+    /// does not contain any messages and `Internal` status if request
+    /// contains more than one message.
+    pub async fn message(&mut self) -> Result<Bytes, ServerError> {
+        let msg = match self.recv().await {
+            Some(res) => res?,
+            None => {
+                return Err(ServerError::Decode(DecodeError::new(
+                    "Cannot decode request message: not enough data provided",
+                )))
+            }
+        };
+        match self.recv().await {
+            None => Ok(msg),
+            Some(Err(err)) => Err(err),
+            Some(Ok(_)) => Err(Status::new(
+                GrpcStatus::Internal,
+                "Too many request messages for unary call",
+            )
+            .into()),
+        }
+    }
+}
+
+impl From<Bytes> for Payload {
+    fn from(msg: Bytes) -> Self {
+        Payload {
+            kind: PayloadKind::Message(Some(msg)),
+            trailers: None,
+            window: None,
+        }
+    }
+}
+
+impl Stream for Payload {
+    type Item = Result<Bytes, ServerError>;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl Drop for Payload {
+    fn drop(&mut self) {
+        // pending messages are not received by service
+        if let Some(ref window) = self.window {
+            window.pending.set(0);
+            window.release();
+        }
+    }
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Payload").finish()
+    }
+}
+
+type DecodeFn<T> = Box<dyn Fn(&mut Bytes) -> Result<T, DecodeError>>;
+
+/// Stream of decoded request messages
+pub struct Streaming<T> {
+    payload: Payload,
+    decode: DecodeFn<T>,
+}
+
+impl<T> Streaming<T> {
+    /// Create request stream, messages are decoded with method definition
+    pub fn new<M>(method: M, payload: Payload) -> Self
+    where
+        M: MethodDef<Input = T> + 'static,
+    {
+        Self {
+            payload,
+            decode: Box::new(move |buf| method.decode(buf)),
+        }
+    }
+
+    /// Request trailers
+    ///
+    /// Trailers are available after the end of request stream.
+    pub fn trailers(&self) -> Option<&HeaderMap> {
+        self.payload.trailers()
+    }
+
+    /// Receive next request message
+    pub async fn recv(&mut self) -> Option<Result<T, ServerError>> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Receive next request message
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<T, ServerError>>> {
+        match self.payload.poll_recv(cx) {
+            Poll::Ready(Some(Ok(mut msg))) => {
+                Poll::Ready(Some((self.decode)(&mut msg).map_err(ServerError::Decode)))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Stream for Streaming<T> {
+    type Item = Result<T, ServerError>;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T> Unpin for Streaming<T> {}
+
+impl<T> fmt::Debug for Streaming<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Streaming").finish()
+    }
+}
+
+/// Stream of response messages
+pub struct ResponseStream<T>(Pin<Box<dyn Stream<Item = Result<T, ServerError>>>>);
+
+impl<T> ResponseStream<T> {
+    /// Create response stream
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<T, ServerError>> + 'static,
+    {
+        Self(Box::pin(stream))
+    }
+}
+
+impl<T> Stream for ResponseStream<T> {
+    type Item = Result<T, ServerError>;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.as_mut().poll_next(cx)
+    }
+}
+
+impl<T> fmt::Debug for ResponseStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseStream").finish()
+    }
+}
+
+/// Encodes response messages with method definition
+pub(super) struct EncodeStream<M: MethodDef> {
+    pub(super) method: M,
+    pub(super) stream: ResponseStream<M::Output>,
+}
+
+impl<M: MethodDef> Stream for EncodeStream<M> {
+    type Item = Result<Bytes, ServerError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.stream).poll_next(cx) {
+            Poll::Ready(Some(Ok(msg))) => {
+                let mut buf = BytesMut::new();
+                this.method.encode(msg, &mut buf);
+                Poll::Ready(Some(Ok(buf.freeze())))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<M: MethodDef> Unpin for EncodeStream<M> {}
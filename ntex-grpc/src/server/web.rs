@@ -0,0 +1,413 @@
+use std::{convert::Infallible, future::pending, pin::Pin, rc::Rc, task::Context, task::Poll};
+use std::{fmt, time::Instant};
+
+use ntex::http::{body::Body, error::ResponseError, Payload as HttpPayload, Request, Response};
+use ntex_bytes::{ByteString, Bytes, BytesMut};
+use ntex_http::{header, HeaderMap, StatusCode};
+use ntex_service::{Service, ServiceCtx, ServiceFactory};
+use ntex_util::future::{select, stream_recv, Either};
+use ntex_util::{time, Stream};
+
+use crate::compression::Encoder;
+use crate::status::{GrpcStatus, Status};
+use crate::web::{self, TextDecoder, HDRV_CT_GRPC_WEB, HDRV_CT_GRPC_WEB_TEXT};
+use crate::{consts, utils, utils::Data, DecodeError};
+
+use super::service::{CallInfo, ServerConfig};
+use super::streaming::PayloadSender;
+use super::{Payload, ResponseStream, ServerError, ServerRequest, ServerResponse};
+
+/// gRPC-Web server
+///
+/// Serves `application/grpc-web` and `application/grpc-web-text` requests
+/// with the same services as [`GrpcServer`](super::GrpcServer). Server is
+/// a service of ntex http server and could be used with HTTP/1.1 and h2.
+/// Request stream is received while service call is in progress, browser
+/// clients support unary and server streaming calls only.
+///
+/// ```rust,ignore
+/// Server::build()
+///     .bind("grpc-web", "0.0.0.0:8080", |_| {
+///         HttpService::build().h1(GrpcServer::new(GreeterServer).web())
+///     })?
+///     .run()
+///     .await
+/// ```
+pub struct GrpcWebServer<T> {
+    factory: Rc<T>,
+    config: Rc<ServerConfig>,
+}
+
+impl<T> GrpcWebServer<T> {
+    pub(super) fn new(factory: Rc<T>, config: Rc<ServerConfig>) -> Self {
+        Self { factory, config }
+    }
+}
+
+impl<T> fmt::Debug for GrpcWebServer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrpcWebServer")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<T> ServiceFactory<Request> for GrpcWebServer<T>
+where
+    T: ServiceFactory<ServerRequest, Response = ServerResponse, Error = ServerError>,
+{
+    type Response = Response;
+    type Error = ServerError;
+    type Service = GrpcWebService<T::Service>;
+    type InitError = T::InitError;
+
+    async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        Ok(GrpcWebService {
+            service: self.factory.create(()).await?,
+            config: self.config.clone(),
+        })
+    }
+}
+
+/// gRPC-Web service
+pub struct GrpcWebService<S> {
+    service: S,
+    config: Rc<ServerConfig>,
+}
+
+impl<S> fmt::Debug for GrpcWebService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrpcWebService")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<S> Service<Request> for GrpcWebService<S>
+where
+    S: Service<ServerRequest, Response = ServerResponse, Error = ServerError>,
+{
+    type Response = Response;
+    type Error = ServerError;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.service.poll_shutdown(cx)
+    }
+
+    async fn call(
+        &self,
+        mut req: Request,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Response, ServerError> {
+        let compressors = &self.config.compressors;
+
+        // non grpc-web requests
+        let text = if let Some(text) = web::is_grpc_web(req.headers().get(header::CONTENT_TYPE)) {
+            text
+        } else {
+            return Ok(Response::new(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+        };
+
+        // errors before any response message are sent as trailers-only response
+        let mut hdrs = HeaderMap::default();
+        hdrs.insert(
+            header::CONTENT_TYPE,
+            if text {
+                HDRV_CT_GRPC_WEB_TEXT
+            } else {
+                HDRV_CT_GRPC_WEB
+            },
+        );
+        hdrs.insert(consts::GRPC_ACCEPT_ENCODING, compressors.accept_encoding());
+        let mut responder = Responder {
+            text,
+            headers: hdrs,
+            encoder: Encoder::identity(),
+            deadline: None,
+            max_size: self.config.max_send_message_size,
+        };
+
+        let headers = req.headers().clone();
+        let CallInfo {
+            service,
+            method,
+            decoder,
+            encoder,
+            timeout,
+        } = match self
+            .config
+            .prepare_call(&headers, ByteString::from(req.path()))
+        {
+            Ok(info) => info,
+            Err(status) => return Ok(responder.trailers_only(status)),
+        };
+        let deadline = timeout.map(|t| Instant::now() + t);
+        responder.encoder = encoder;
+        responder.deadline = deadline;
+
+        // request messages are delivered to the service as they arrive
+        let (tx, payload) = Payload::channel();
+        let body = req.take_payload();
+        let read = async {
+            let text = if text {
+                Some(TextDecoder::default())
+            } else {
+                None
+            };
+            let max_size = self.config.max_recv_message_size;
+            if let Err(status) = read_payload(body, text, decoder, max_size, tx).await {
+                status
+            } else {
+                pending().await
+            }
+        };
+
+        log::debug!("Call service {} method {}", service, method);
+        let req = ServerRequest {
+            payload,
+            headers,
+            service,
+            name: method,
+            deadline,
+        };
+        let call = async {
+            match select(ctx.call(&self.service, req), read).await {
+                Either::Left(result) => result,
+                Either::Right(status) => Err(status.into()),
+            }
+        };
+
+        let result = if let Some(timeout) = timeout {
+            // abort service call after deadline
+            match time::timeout(timeout, call).await {
+                Ok(result) => result,
+                Err(_) => {
+                    Err(Status::new(GrpcStatus::DeadlineExceeded, "Deadline exceeded").into())
+                }
+            }
+        } else {
+            call.await
+        };
+        Ok(responder.response(result))
+    }
+}
+
+/// Deliver complete request messages to the service
+async fn read_payload(
+    mut body: HttpPayload,
+    mut text: Option<TextDecoder>,
+    decoder: Encoder,
+    max_size: usize,
+    tx: PayloadSender,
+) -> Result<(), Status> {
+    let mut data = Data::Empty;
+    while let Some(chunk) = stream_recv(&mut body).await {
+        let chunk = chunk.map_err(|err| {
+            Status::new(
+                GrpcStatus::Internal,
+                format!("Cannot read request body: {}", err),
+            )
+        })?;
+        let chunk = if let Some(ref mut text) = text {
+            text.decode(&chunk)
+                .map_err(|err| Status::from(ServerError::Decode(err)))?
+        } else {
+            chunk
+        };
+        data.push(chunk);
+
+        while let Some((compressed, msg)) = data.next_message(max_size)? {
+            let msg = match decoder.decode(compressed, msg, max_size) {
+                Ok(msg) if msg.len() > max_size => {
+                    return Err(utils::decompressed_size_exceeded(max_size))
+                }
+                Ok(msg) => Ok(msg),
+                Err(err) => Err(ServerError::Decode(err)),
+            };
+            tx.send(msg);
+        }
+    }
+
+    // end of request stream
+    if data.len() != 0 || text.map(|text| !text.is_empty()).unwrap_or(false) {
+        tx.send(Err(ServerError::Decode(DecodeError::new(
+            "Cannot decode request message: not enough data provided",
+        ))));
+    }
+    Ok(())
+}
+
+/// Builds gRPC-Web response
+///
+/// Response messages are followed by trailers frame, if service call
+/// fails before any response message status is sent as trailers-only response.
+struct Responder {
+    text: bool,
+    encoder: Encoder,
+    headers: HeaderMap,
+    deadline: Option<Instant>,
+    max_size: usize,
+}
+
+impl Responder {
+    fn response(mut self, result: Result<ServerResponse, ServerError>) -> Response {
+        let ServerResponse {
+            payload,
+            headers,
+            stream: messages,
+        } = match result {
+            Ok(res) => res,
+            Err(err) => {
+                let status = Status::from(err);
+                log::debug!("Service call failed: {}", status);
+                return self.trailers_only(status);
+            }
+        };
+
+        let mut trailers = HeaderMap::default();
+        trailers.insert(consts::GRPC_STATUS, GrpcStatus::Ok.into());
+        for (name, val) in headers {
+            trailers.append(name, val);
+        }
+        self.headers
+            .insert(consts::GRPC_ENCODING, self.encoder.encoding().to_header());
+
+        if let Some(messages) = messages {
+            let body = WebBody {
+                messages: Some(messages),
+                trailers: Some(trailers),
+                deadline: self
+                    .deadline
+                    .map(|d| time::sleep(d.saturating_duration_since(Instant::now()))),
+                text: self.text,
+                encoder: self.encoder,
+                max_size: self.max_size,
+            };
+            let mut res = Response::new(StatusCode::OK);
+            *res.headers_mut() = self.headers;
+            res.set_body(Body::from_message(ntex::http::body::BodyStream::new(body)))
+        } else {
+            let mut buf = BytesMut::new();
+            if let Err(status) = encode_message(&self.encoder, self.max_size, &payload, &mut buf) {
+                return self.trailers_only(status);
+            }
+            web::encode_trailers(&trailers, &mut buf);
+
+            let mut res = Response::new(StatusCode::OK);
+            *res.headers_mut() = self.headers;
+            res.set_body(Body::from(encode_frames(self.text, buf)))
+        }
+    }
+
+    fn trailers_only(mut self, status: Status) -> Response {
+        for (name, val) in status.to_headers().iter() {
+            self.headers.append(name.clone(), val.clone());
+        }
+        let mut res = Response::new(StatusCode::OK);
+        *res.headers_mut() = self.headers;
+        res
+    }
+}
+
+/// Append length-prefixed response message to the buffer
+fn encode_message(
+    encoder: &Encoder,
+    max_size: usize,
+    msg: &[u8],
+    dst: &mut BytesMut,
+) -> Result<(), Status> {
+    if msg.len() > max_size {
+        return Err(utils::send_size_exceeded(msg.len(), max_size));
+    }
+    encoder.encode(msg, dst).map_err(|e| {
+        log::error!("Cannot compress response message: {}", e);
+        Status::new(GrpcStatus::Internal, "Cannot compress response message")
+    })
+}
+
+fn encode_frames(text: bool, buf: BytesMut) -> Bytes {
+    if text {
+        web::encode_text(&buf)
+    } else {
+        buf.freeze()
+    }
+}
+
+/// Body of streaming response
+struct WebBody {
+    /// Response messages, `None` after trailers frame is sent
+    messages: Option<ResponseStream<Bytes>>,
+    trailers: Option<HeaderMap>,
+    deadline: Option<time::Sleep>,
+    text: bool,
+    encoder: Encoder,
+    max_size: usize,
+}
+
+impl WebBody {
+    /// Trailers frame, ends response stream
+    fn finish(&mut self, trailers: &HeaderMap) -> Bytes {
+        self.messages = None;
+        let mut buf = BytesMut::new();
+        web::encode_trailers(trailers, &mut buf);
+        encode_frames(self.text, buf)
+    }
+}
+
+impl Stream for WebBody {
+    type Item = Result<Bytes, Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let messages = if let Some(ref mut messages) = this.messages {
+            messages
+        } else {
+            return Poll::Ready(None);
+        };
+
+        if let Some(ref deadline) = this.deadline {
+            if deadline.poll_elapsed(cx).is_ready() {
+                let status = Status::new(GrpcStatus::DeadlineExceeded, "Deadline exceeded");
+                return Poll::Ready(Some(Ok(this.finish(&status.to_headers()))));
+            }
+        }
+
+        let frame = match Pin::new(messages).poll_next(cx) {
+            Poll::Ready(Some(Ok(msg))) => {
+                let mut buf = BytesMut::new();
+                match encode_message(&this.encoder, this.max_size, &msg, &mut buf) {
+                    Ok(()) => encode_frames(this.text, buf),
+                    Err(status) => this.finish(&status.to_headers()),
+                }
+            }
+            Poll::Ready(Some(Err(err))) => {
+                let status = Status::from(err);
+                log::debug!("Service call failed: {}", status);
+                this.finish(&status.to_headers())
+            }
+            Poll::Ready(None) => {
+                let trailers = this.trailers.take().unwrap_or_default();
+                this.finish(&trailers)
+            }
+            Poll::Pending => return Poll::Pending,
+        };
+        Poll::Ready(Some(Ok(frame)))
+    }
+}
+
+/// Service readiness error is sent as trailers-only response
+impl ResponseError for ServerError {
+    fn error_response(&self) -> Response {
+        let mut res = Response::new(StatusCode::OK);
+        *res.headers_mut() = Status::from(self.clone()).to_headers();
+        res.headers_mut()
+            .insert(header::CONTENT_TYPE, HDRV_CT_GRPC_WEB);
+        res
+    }
+}
@@ -6,6 +6,11 @@ use crate::{encoding::DecodeError, types::Message};
 pub trait ServiceDef {
     const NAME: &'static str;
 
+    /// Serialized `FileDescriptorProto` of the service file and its dependencies
+    ///
+    /// Descriptor of the service file is first, used by server reflection.
+    const FILE_DESCRIPTORS: &'static [&'static [u8]] = &[];
+
     type Methods;
 
     fn method_by_name(name: &str) -> Option<Self::Methods>;
@@ -17,6 +22,12 @@ pub trait MethodDef {
 
     const PATH: ByteString;
 
+    /// Method accepts stream of request messages
+    const CLIENT_STREAMING: bool = false;
+
+    /// Method returns stream of response messages
+    const SERVER_STREAMING: bool = false;
+
     type Input: Message;
 
     type Output: Message;
@@ -1,5 +1,25 @@
+use std::{convert::TryFrom, fmt};
+
+use base64::engine::{general_purpose::GeneralPurpose, DecodePaddingMode};
+use base64::{alphabet, engine::GeneralPurposeConfig, Engine};
+use ntex_bytes::{ByteString, Bytes, BytesMut};
 use ntex_h2::frame::Reason;
-use ntex_http::HeaderValue;
+use ntex_http::{HeaderMap, HeaderValue};
+use percent_encoding::{percent_decode, utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::google_rpc::{self, StatusDetail};
+use crate::{consts, google_types::Any, Message};
+
+/// Characters of `grpc-message` that must be percent-encoded, non-ascii chars are encoded as well
+const MESSAGE_ENCODE_SET: &AsciiSet = &CONTROLS.add(b'%');
+
+/// Binary header values are base64 encoded without padding, padding is optional on decoding
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
 
 macro_rules! gen_error_code {
     (
@@ -110,3 +130,155 @@ impl From<Reason> for GrpcStatus {
         }
     }
 }
+
+/// Grpc call status
+///
+/// Status details are transferred as `google.rpc.Status` message
+/// in `grpc-status-details-bin` trailer.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Status {
+    pub code: GrpcStatus,
+    pub message: ByteString,
+    pub details: Vec<Any>,
+}
+
+impl Status {
+    /// Create status with code and message
+    pub fn new<T: Into<ByteString>>(code: GrpcStatus, message: T) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: Vec::new(),
+        }
+    }
+
+    /// Add status detail message
+    pub fn detail<T: StatusDetail>(mut self, detail: &T) -> Self {
+        let mut buf = BytesMut::with_capacity(detail.encoded_len());
+        detail.write(&mut buf);
+        self.details.push(Any {
+            type_url: ByteString::from_static(T::TYPE_URL),
+            value: buf.freeze(),
+        });
+        self
+    }
+
+    /// Get first detail message of specified type
+    pub fn get_detail<T: StatusDetail>(&self) -> Option<T> {
+        self.details
+            .iter()
+            .find(|any| any.type_url == T::TYPE_URL)
+            .and_then(|any| T::read(&mut any.value.clone()).ok())
+    }
+
+    /// Build status from response trailers
+    pub(crate) fn from_headers(code: GrpcStatus, hdrs: &HeaderMap) -> Self {
+        let message = hdrs
+            .get(consts::GRPC_MESSAGE)
+            .map(|val| ByteString::from(percent_decode(val.as_bytes()).decode_utf8_lossy()))
+            .unwrap_or_default();
+
+        let details = hdrs
+            .get(consts::GRPC_STATUS_DETAILS)
+            .and_then(|val| BASE64.decode(val.as_bytes()).ok())
+            .and_then(|data| google_rpc::Status::read(&mut Bytes::from(data)).ok())
+            .map(|st| st.details)
+            .unwrap_or_default();
+
+        Self {
+            code,
+            message,
+            details,
+        }
+    }
+
+    /// Convert status to response trailers
+    pub(crate) fn to_headers(&self) -> HeaderMap {
+        let mut hdrs = HeaderMap::default();
+        hdrs.insert(consts::GRPC_STATUS, self.code.into());
+        if !self.message.is_empty() {
+            let msg = utf8_percent_encode(&self.message, MESSAGE_ENCODE_SET).to_string();
+            if let Ok(val) = HeaderValue::try_from(msg) {
+                hdrs.insert(consts::GRPC_MESSAGE, val);
+            }
+        }
+
+        if !self.details.is_empty() {
+            let st = google_rpc::Status {
+                code: i32::from(self.code.code()),
+                message: self.message.clone(),
+                details: self.details.clone(),
+            };
+            let mut buf = BytesMut::with_capacity(st.encoded_len());
+            st.write(&mut buf);
+            if let Ok(val) = HeaderValue::try_from(BASE64.encode(&buf)) {
+                hdrs.insert(consts::GRPC_STATUS_DETAILS, val);
+            }
+        }
+        hdrs
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code.as_str(), self.message)
+    }
+}
+
+impl std::error::Error for Status {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::google_rpc::{bad_request::FieldViolation, BadRequest, ErrorInfo};
+
+    #[test]
+    fn status_details() {
+        let bad_request = BadRequest {
+            field_violations: vec![FieldViolation {
+                field: "query".into(),
+                description: "must not be empty".into(),
+            }],
+        };
+        let st = Status::new(GrpcStatus::InvalidArgument, "Invalid query").detail(&bad_request);
+
+        let hdrs = st.to_headers();
+        assert_eq!(hdrs.get(consts::GRPC_STATUS).unwrap(), "3");
+        assert_eq!(hdrs.get(consts::GRPC_MESSAGE).unwrap(), "Invalid query");
+
+        let st2 = Status::from_headers(GrpcStatus::InvalidArgument, &hdrs);
+        assert_eq!(st, st2);
+        assert_eq!(st2.get_detail::<BadRequest>(), Some(bad_request));
+        assert_eq!(st2.get_detail::<ErrorInfo>(), None);
+
+        // padded value
+        let mut hdrs = hdrs;
+        let data = BASE64
+            .decode(hdrs.get(consts::GRPC_STATUS_DETAILS).unwrap().as_bytes())
+            .unwrap();
+        let val = base64::engine::general_purpose::STANDARD.encode(data);
+        assert!(val.ends_with('='));
+        hdrs.insert(
+            consts::GRPC_STATUS_DETAILS,
+            HeaderValue::try_from(val).unwrap(),
+        );
+        assert_eq!(Status::from_headers(GrpcStatus::InvalidArgument, &hdrs), st);
+    }
+
+    #[test]
+    fn status_message() {
+        let st = Status::new(GrpcStatus::Internal, "Ошибка: 100%\r\nretry");
+        let hdrs = st.to_headers();
+        assert_eq!(
+            hdrs.get(consts::GRPC_MESSAGE).unwrap(),
+            "%D0%9E%D1%88%D0%B8%D0%B1%D0%BA%D0%B0: 100%25%0D%0Aretry"
+        );
+        assert_eq!(Status::from_headers(GrpcStatus::Internal, &hdrs), st);
+
+        // invalid sequences are not decoded
+        let mut hdrs = HeaderMap::default();
+        hdrs.insert(consts::GRPC_MESSAGE, HeaderValue::from_static("100% %zz"));
+        let st = Status::from_headers(GrpcStatus::Internal, &hdrs);
+        assert_eq!(st.message, "100% %zz");
+    }
+}
@@ -49,7 +49,7 @@ pub trait NativeType: PartialEq + Default + Sized + fmt::Debug {
     /// Encode field tag and length
     fn encode_type(&self, tag: u32, dst: &mut BytesMut) {
         encoding::encode_key(tag, Self::TYPE, dst);
-        if Self::TYPE != WireType::Varint {
+        if Self::TYPE == WireType::LengthDelimited {
             encoding::encode_varint(self.value_len() as u64, dst);
         }
     }
@@ -102,7 +102,7 @@ pub trait NativeType: PartialEq + Default + Sized + fmt::Debug {
     ) -> Result<(), DecodeError> {
         encoding::check_wire_type(Self::TYPE, wtype)?;
 
-        if Self::TYPE == WireType::Varint {
+        if Self::TYPE != WireType::LengthDelimited {
             self.merge(src)
         } else {
             let len = encoding::decode_varint(src)? as usize;
@@ -621,6 +621,7 @@ fixed_width!(
 );
 
 #[cfg(test)]
+#[allow(clippy::identity_op, clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
 
@@ -690,4 +691,35 @@ mod tests {
         msg3.deserialize(tag, wire_type, &mut buf2).unwrap();
         assert_eq!(msg, msg3);
     }
+
+    #[test]
+    fn test_fixed_width_round_trip() {
+        let mut buf = BytesMut::new();
+        NativeType::serialize(&1.5f32, 1, DefaultValue::Default, &mut buf);
+        NativeType::serialize(&-2.25f64, 2, DefaultValue::Default, &mut buf);
+        NativeType::serialize(&7u32, 3, DefaultValue::Default, &mut buf);
+
+        // fixed width values are not length-delimited
+        let mut expected = vec![0x0d];
+        expected.extend_from_slice(&1.5f32.to_le_bytes());
+        expected.push(0x11);
+        expected.extend_from_slice(&(-2.25f64).to_le_bytes());
+        expected.extend_from_slice(&[0x18, 7]);
+        assert_eq!(&buf[..], &expected[..]);
+        assert_eq!(NativeType::encoded_len(&1.5f32, 1), 5);
+        assert_eq!(NativeType::encoded_len(&-2.25f64, 2), 9);
+
+        let mut src = buf.freeze();
+        let (mut f, mut d, mut v) = (0f32, 0f64, 0u32);
+        while !src.is_empty() {
+            let (tag, wire_type) = encoding::decode_key(&mut src).unwrap();
+            match tag {
+                1 => f.deserialize(tag, wire_type, &mut src).unwrap(),
+                2 => d.deserialize(tag, wire_type, &mut src).unwrap(),
+                3 => v.deserialize(tag, wire_type, &mut src).unwrap(),
+                _ => panic!("unexpected tag {}", tag),
+            }
+        }
+        assert_eq!((f, d, v), (1.5, -2.25, 7));
+    }
 }
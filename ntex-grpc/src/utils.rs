@@ -1,6 +1,9 @@
-use std::mem;
+use std::{convert::TryFrom, mem, time::Duration};
 
-use ntex_bytes::{Bytes, BytesMut};
+use ntex_bytes::{Buf, Bytes, BytesMut};
+use ntex_http::HeaderValue;
+
+use crate::{GrpcStatus, Status};
 
 pub(crate) enum Data {
     Chunk(Bytes),
@@ -33,4 +36,156 @@ impl Data {
             };
         }
     }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Data::Chunk(data) => data.len(),
+            Data::MutChunk(data) => data.len(),
+            Data::Empty => 0,
+        }
+    }
+
+    /// Split next length-prefixed message from the buffer
+    ///
+    /// Returns compressed flag and message, or `None` if buffer
+    /// does not contain complete message yet. Messages larger than `max_size`
+    /// are rejected before they are buffered.
+    pub(crate) fn next_message(
+        &mut self,
+        max_size: usize,
+    ) -> Result<Option<(bool, Bytes)>, Status> {
+        Ok(self
+            .next_frame(max_size)?
+            .map(|(flags, msg)| (flags == 1, msg)))
+    }
+
+    /// Split next length-prefixed frame from the buffer
+    ///
+    /// Same as `next_message()`, returns flags byte of the frame.
+    pub(crate) fn next_frame(&mut self, max_size: usize) -> Result<Option<(u8, Bytes)>, Status> {
+        if self.len() < 5 {
+            return Ok(None);
+        }
+
+        let mut data = self.get();
+        let len = (&data[1..5]).get_u32() as usize;
+        if len > max_size {
+            Err(recv_size_exceeded(len, max_size))
+        } else if data.len() < len + 5 {
+            *self = Data::Chunk(data);
+            Ok(None)
+        } else {
+            let flags = data.get_u8();
+            data.advance(4);
+            let msg = data.split_to(len);
+            if !data.is_empty() {
+                *self = Data::Chunk(data);
+            }
+            Ok(Some((flags, msg)))
+        }
+    }
+}
+
+/// Status for received message that exceeds size limit
+pub(crate) fn recv_size_exceeded(size: usize, max_size: usize) -> Status {
+    Status::new(
+        GrpcStatus::ResourceExhausted,
+        format!(
+            "Received message larger than max ({} vs. {})",
+            size, max_size
+        ),
+    )
+}
+
+/// Status for compressed message that exceeds size limit after decompression
+pub(crate) fn decompressed_size_exceeded(max_size: usize) -> Status {
+    Status::new(
+        GrpcStatus::ResourceExhausted,
+        format!(
+            "Received message after decompression larger than max ({})",
+            max_size
+        ),
+    )
+}
+
+/// Status for sent message that exceeds size limit
+pub(crate) fn send_size_exceeded(size: usize, max_size: usize) -> Status {
+    Status::new(
+        GrpcStatus::ResourceExhausted,
+        format!("Sent message larger than max ({} vs. {})", size, max_size),
+    )
+}
+
+/// Encode `grpc-timeout` header value
+///
+/// Value is limited to 8 digits, the most precise unit that fits is used.
+pub(crate) fn encode_timeout(timeout: Duration) -> HeaderValue {
+    const MAX: u128 = 99_999_999;
+
+    let val = if timeout.as_nanos() <= MAX {
+        format!("{}n", timeout.as_nanos())
+    } else if timeout.as_micros() <= MAX {
+        format!("{}u", timeout.as_micros())
+    } else if timeout.as_millis() <= MAX {
+        format!("{}m", timeout.as_millis())
+    } else if u128::from(timeout.as_secs()) <= MAX {
+        format!("{}S", timeout.as_secs())
+    } else if u128::from(timeout.as_secs() / 60) <= MAX {
+        format!("{}M", timeout.as_secs() / 60)
+    } else {
+        format!("{}H", (timeout.as_secs() / 3600).min(MAX as u64))
+    };
+    HeaderValue::try_from(val).unwrap()
+}
+
+/// Parse `grpc-timeout` header value
+pub(crate) fn parse_timeout(val: &HeaderValue) -> Option<Duration> {
+    let val = val.to_str().ok()?;
+    if val.len() < 2 || val.len() > 9 {
+        return None;
+    }
+    let (digits, unit) = val.split_at(val.len() - 1);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let num: u64 = digits.parse().ok()?;
+
+    match unit {
+        "H" => Some(Duration::from_secs(num * 3600)),
+        "M" => Some(Duration::from_secs(num * 60)),
+        "S" => Some(Duration::from_secs(num)),
+        "m" => Some(Duration::from_millis(num)),
+        "u" => Some(Duration::from_micros(num)),
+        "n" => Some(Duration::from_nanos(num)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout() {
+        let t = Duration::from_millis(50);
+        assert_eq!(encode_timeout(t), "50000000n");
+        assert_eq!(parse_timeout(&encode_timeout(t)), Some(t));
+
+        let t = Duration::from_secs(10);
+        assert_eq!(encode_timeout(t), "10000000u");
+        assert_eq!(parse_timeout(&encode_timeout(t)), Some(t));
+
+        let t = Duration::from_secs(3600 * 24 * 365);
+        assert_eq!(encode_timeout(t), "31536000S");
+        assert_eq!(parse_timeout(&encode_timeout(t)), Some(t));
+
+        assert_eq!(
+            parse_timeout(&HeaderValue::from_static("2H")),
+            Some(Duration::from_secs(7200))
+        );
+        assert_eq!(parse_timeout(&HeaderValue::from_static("100")), None);
+        assert_eq!(parse_timeout(&HeaderValue::from_static("m")), None);
+        assert_eq!(parse_timeout(&HeaderValue::from_static("123456789m")), None);
+        assert_eq!(parse_timeout(&HeaderValue::from_static("-1m")), None);
+    }
 }
@@ -0,0 +1,175 @@
+//! gRPC-Web framing
+//!
+//! gRPC-Web messages use the same length-prefixed framing as gRPC, trailers
+//! are sent as the last frame of the body with `0x80` flag. `-text` content
+//! types encode every frame with base64.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ntex_bytes::{BufMut, Bytes, BytesMut};
+use ntex_http::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::DecodeError;
+
+pub(crate) const HDRV_CT_GRPC_WEB: HeaderValue =
+    HeaderValue::from_static("application/grpc-web+proto");
+pub(crate) const HDRV_CT_GRPC_WEB_TEXT: HeaderValue =
+    HeaderValue::from_static("application/grpc-web-text+proto");
+
+/// Flag of the trailers frame
+pub(crate) const TRAILERS_FLAG: u8 = 0x80;
+
+/// Returns `Some(text)` for gRPC-Web content types
+pub(crate) fn is_grpc_web(content_type: Option<&HeaderValue>) -> Option<bool> {
+    let val = content_type?.to_str().ok()?;
+    let val = val.split(';').next().unwrap_or_default().trim();
+    if val == "application/grpc-web-text" || val.starts_with("application/grpc-web-text+") {
+        Some(true)
+    } else if val == "application/grpc-web" || val.starts_with("application/grpc-web+") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Append trailers frame to the buffer
+pub(crate) fn encode_trailers(trailers: &HeaderMap, dst: &mut BytesMut) {
+    let mut block = BytesMut::new();
+    for (name, val) in trailers.iter() {
+        block.extend_from_slice(name.as_str().as_bytes());
+        block.extend_from_slice(b":");
+        block.extend_from_slice(val.as_bytes());
+        block.extend_from_slice(b"\r\n");
+    }
+    dst.reserve(block.len() + 5);
+    dst.put_u8(TRAILERS_FLAG);
+    dst.put_u32(block.len() as u32);
+    dst.extend_from_slice(&block);
+}
+
+/// Parse payload of the trailers frame
+pub(crate) fn decode_trailers(block: &[u8]) -> Result<HeaderMap, DecodeError> {
+    let mut trailers = HeaderMap::default();
+    for line in block.split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let pos = line
+            .iter()
+            .position(|b| *b == b':')
+            .ok_or_else(|| DecodeError::new("Malformed trailers frame"))?;
+        let (name, val) = (&line[..pos], &line[pos + 1..]);
+        let skip = val
+            .iter()
+            .take_while(|b| **b == b' ' || **b == b'\t')
+            .count();
+
+        let name = HeaderName::from_bytes(&name.to_ascii_lowercase())
+            .map_err(|_| DecodeError::new("Malformed trailer name"))?;
+        let val = HeaderValue::from_bytes(&val[skip..])
+            .map_err(|_| DecodeError::new("Malformed trailer value"))?;
+        trailers.append(name, val);
+    }
+    Ok(trailers)
+}
+
+/// Base64 encoding of the frames for `-text` content types
+pub(crate) fn encode_text(frames: &[u8]) -> Bytes {
+    Bytes::from(STANDARD.encode(frames))
+}
+
+/// Incremental decoder of base64 encoded body
+///
+/// Body is a concatenation of separately encoded frames, each of them
+/// could end with padding.
+#[derive(Default)]
+pub(crate) struct TextDecoder {
+    buf: BytesMut,
+}
+
+impl TextDecoder {
+    /// Decode complete base64 quantums of received data
+    pub(crate) fn decode(&mut self, data: &[u8]) -> Result<Bytes, DecodeError> {
+        self.buf
+            .extend(data.iter().filter(|b| !b.is_ascii_whitespace()));
+
+        let chunk = self.buf.split_to(self.buf.len() / 4 * 4);
+        let mut out = Vec::with_capacity(chunk.len() / 4 * 3);
+        let mut start = 0;
+        for (idx, quantum) in chunk.chunks_exact(4).enumerate() {
+            // padded quantum terminates separately encoded frame
+            if quantum[3] == b'=' {
+                let end = (idx + 1) * 4;
+                decode_base64(&chunk[start..end], &mut out)?;
+                start = end;
+            }
+        }
+        decode_base64(&chunk[start..], &mut out)?;
+        Ok(Bytes::from(out))
+    }
+
+    /// Check that all received data is decoded
+    pub(crate) fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+fn decode_base64(src: &[u8], dst: &mut Vec<u8>) -> Result<(), DecodeError> {
+    STANDARD
+        .decode_vec(src, dst)
+        .map_err(|_| DecodeError::new("Malformed base64 body"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_decoder() {
+        // separately encoded frames, received in arbitrary chunks
+        let body = [
+            encode_text(b"frame1"),
+            encode_text(b"fr2"),
+            encode_text(b"f3"),
+        ]
+        .concat();
+        assert_eq!(&body, b"ZnJhbWUxZnIyZjM=");
+
+        let mut decoder = TextDecoder::default();
+        let mut decoded = Vec::new();
+        for chunk in body.chunks(3) {
+            decoded.extend_from_slice(&decoder.decode(chunk).unwrap());
+        }
+        assert!(decoder.is_empty());
+        assert_eq!(decoded, b"frame1fr2f3");
+
+        let mut decoder = TextDecoder::default();
+        assert!(decoder.decode(b"Zm=y").is_err());
+    }
+
+    #[test]
+    fn trailers() {
+        let mut trailers = HeaderMap::default();
+        trailers.insert(
+            HeaderName::from_static("grpc-status"),
+            HeaderValue::from_static("0"),
+        );
+        let mut buf = BytesMut::new();
+        encode_trailers(&trailers, &mut buf);
+        assert_eq!(&buf[..], b"\x80\x00\x00\x00\x0fgrpc-status:0\r\n");
+
+        let trailers = decode_trailers(b"Grpc-Status: 5\r\ngrpc-message:not found\n").unwrap();
+        assert_eq!(trailers.get("grpc-status").unwrap(), "5");
+        assert_eq!(trailers.get("grpc-message").unwrap(), "not found");
+        assert!(decode_trailers(b"grpc-status").is_err());
+    }
+
+    #[test]
+    fn content_type() {
+        let ct = |val| is_grpc_web(Some(&HeaderValue::from_static(val)));
+        assert_eq!(ct("application/grpc-web"), Some(false));
+        assert_eq!(ct("application/grpc-web+proto"), Some(false));
+        assert_eq!(ct("application/grpc-web-text; charset=utf-8"), Some(true));
+        assert_eq!(ct("application/grpc"), None);
+        assert_eq!(is_grpc_web(None), None);
+    }
+}
@@ -0,0 +1,286 @@
+use std::{cell::RefCell, rc::Rc};
+use std::{io, time::Duration, time::Instant};
+
+use ntex::time::{sleep, timeout, Millis};
+use ntex_grpc::client::Channel;
+use ntex_grpc::client::ConnectivityState;
+use ntex_grpc::client::Resolver;
+use ntex_grpc::client::{Client, ClientError, ClientInformation, ConnectBackoff};
+use ntex_grpc::{GrpcStatus, Status};
+use ntex_h2::client as h2;
+
+use common::counts::{CountsSearchClient, SearchRequest};
+use common::{run, start_server, start_server_at};
+use common::{serve_uds, unused_address, Proxy};
+
+mod common;
+
+/// Resolver that returns prepared addresses
+#[derive(Clone, Default)]
+struct StubResolver {
+    addrs: Rc<RefCell<Vec<std::net::SocketAddr>>>,
+    names: Rc<RefCell<Vec<(String, u16)>>>,
+}
+
+impl StubResolver {
+    fn set(&self, addrs: &[&str]) {
+        *self.addrs.borrow_mut() = addrs.iter().map(|addr| addr.parse().unwrap()).collect();
+    }
+}
+
+impl Resolver for StubResolver {
+    async fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<std::net::SocketAddr>> {
+        self.names.borrow_mut().push((host.to_string(), port));
+        Ok(self.addrs.borrow().clone())
+    }
+}
+
+#[test]
+fn channel_resolver() {
+    run(async {
+        let address = start_server();
+        let port: u16 = address.rsplit(':').next().unwrap().parse().unwrap();
+        let unused = unused_address();
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+
+        // static addresses
+        let counts_client = CountsSearchClient::new(
+            Channel::connect(&format!("ipv4:{}", address))
+                .await
+                .expect("failed to connect"),
+        );
+        assert!(counts_client.search(&request).await.is_ok());
+        assert_eq!(counts_client.transport().get_ref().len(), 1);
+
+        // invalid target
+        let err = Channel::connect("ipv4:localhost").await.unwrap_err();
+        assert!(matches!(err, ClientError::Target(_)));
+
+        // empty resolution result
+        let resolver = StubResolver::default();
+        let err = Channel::build(&format!("dns:///counts.test:{}", port))
+            .unwrap()
+            .resolver(resolver.clone())
+            .finish()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ClientError::Client(_)));
+
+        // periodic re-resolution
+        resolver.set(&[&unused]);
+        let channel = Channel::build(&format!("dns:///counts.test:{}", port))
+            .unwrap()
+            .resolver(resolver.clone())
+            .resolve_interval(Duration::from_millis(50))
+            .finish()
+            .await
+            .expect("failed to connect");
+        let counts_client = CountsSearchClient::new(channel);
+        assert!(counts_client.search(&request).await.is_err());
+
+        resolver.set(&[&address, &unused]);
+        sleep(Millis(150)).await;
+        assert_eq!(counts_client.transport().get_ref().len(), 2);
+        resolver.set(&[&address]);
+        sleep(Millis(150)).await;
+        assert_eq!(counts_client.transport().get_ref().len(), 1);
+        assert!(counts_client.search(&request).await.is_ok());
+        assert!(resolver
+            .names
+            .borrow()
+            .iter()
+            .all(|name| name == &("counts.test".to_string(), port)));
+
+        // resolution stops after channel is dropped
+        drop(counts_client);
+        sleep(Millis(100)).await;
+        let resolved = resolver.names.borrow().len();
+        sleep(Millis(150)).await;
+        assert_eq!(resolver.names.borrow().len(), resolved);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn channel_unix() {
+    run(async {
+        let path = std::env::temp_dir().join("ntex-grpc-integration.sock");
+        let _ = std::fs::remove_file(&path);
+
+        serve_uds(&path);
+
+        let counts_client = CountsSearchClient::new(
+            Channel::connect(&format!("unix://{}", path.display()))
+                .await
+                .expect("failed to connect"),
+        );
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+    });
+}
+
+#[test]
+fn connect_backoff() {
+    run(async {
+        let address = unused_address();
+        let counts_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address.clone()).finish()).connect_backoff(
+                ConnectBackoff::new()
+                    .initial_backoff(Duration::from_millis(300))
+                    .jitter(0.0),
+            ),
+        );
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+
+        // connection failure starts backoff
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(matches!(err, ClientError::Client(_)));
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::Unavailable,
+                    ..
+                },
+                _
+            )
+        ));
+
+        // wait for ready call fails with deadline
+        let start = Instant::now();
+        let err = counts_client
+            .search(&request)
+            .wait_for_ready()
+            .timeout(Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::DeadlineExceeded,
+                    ..
+                },
+                _
+            )
+        ));
+
+        // wait for ready call is sent after reconnect
+        start_server_at(&address);
+        let response = counts_client
+            .search(&request)
+            .wait_for_ready()
+            .timeout(Duration::from_secs(5))
+            .await
+            .expect("failed to search");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+        assert!(counts_client.search(&request).await.is_ok());
+    });
+}
+
+#[test]
+fn connectivity_state() {
+    run(async {
+        let address = unused_address();
+        let client = Client::new(h2::Client::with_default(address.clone()).finish())
+            .connect_backoff(
+                ConnectBackoff::new()
+                    .initial_backoff(Duration::from_millis(100))
+                    .jitter(0.0),
+            );
+        assert_eq!(client.state(), ConnectivityState::Idle);
+
+        // collect state changes
+        let states = Rc::new(RefCell::new(Vec::new()));
+        let watcher = client.clone();
+        let states2 = states.clone();
+        ntex::rt::spawn(async move {
+            let mut state = ConnectivityState::Idle;
+            while state != ConnectivityState::Shutdown {
+                state = watcher.watch_state(state).await;
+                states2.borrow_mut().push(state);
+            }
+        });
+        sleep(Millis(10)).await;
+
+        let counts_client = CountsSearchClient::new(client.clone());
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        assert!(counts_client.search(&request).await.is_err());
+        assert_eq!(client.state(), ConnectivityState::TransientFailure);
+
+        start_server_at(&address);
+        assert!(counts_client
+            .search(&request)
+            .wait_for_ready()
+            .timeout(Duration::from_secs(5))
+            .await
+            .is_ok());
+        assert_eq!(client.state(), ConnectivityState::Ready);
+
+        client.close();
+        assert_eq!(client.state(), ConnectivityState::Shutdown);
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::Unavailable,
+                    ..
+                },
+                _
+            )
+        ));
+
+        sleep(Millis(50)).await;
+        assert_eq!(
+            &*states.borrow(),
+            &[
+                ConnectivityState::Connecting,
+                ConnectivityState::TransientFailure,
+                ConnectivityState::Idle,
+                ConnectivityState::Connecting,
+                ConnectivityState::Ready,
+                ConnectivityState::Shutdown
+            ]
+        );
+    });
+}
+
+#[test]
+fn connectivity_state_connection_closed() {
+    run(async {
+        let proxy = Proxy::start(&start_server());
+        let client = Client::with_default(proxy.address());
+        let counts_client = CountsSearchClient::new(client.clone());
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        assert!(counts_client.search(&request).await.is_ok());
+        assert_eq!(client.state(), ConnectivityState::Ready);
+
+        // connection is closed without calls in flight
+        proxy.close();
+        let state = timeout(Millis(5_000), client.watch_state(ConnectivityState::Ready))
+            .await
+            .expect("connectivity state is not changed");
+        assert_eq!(state, ConnectivityState::Idle);
+
+        // next call reconnects
+        assert!(counts_client.search(&request).await.is_ok());
+        assert_eq!(client.state(), ConnectivityState::Ready);
+    });
+}
@@ -8,7 +8,7 @@
     clippy::derive_partial_eq_without_eq,
     clippy::manual_range_patterns
 )]
-/// DO NOT MODIFY. Auto-generated file
+// DO NOT MODIFY. Auto-generated file
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct SearchRequest {
@@ -39,6 +39,9 @@ pub struct CountsSearch;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CountsSearchMethods {
     Search(CountsSearchSearchMethod),
+    SearchStream(CountsSearchSearchStreamMethod),
+    Upload(CountsSearchUploadMethod),
+    Echo(CountsSearchEchoMethod),
 }
 
 #[derive(Debug, Clone)]
@@ -55,6 +58,43 @@ impl ::ntex_grpc::MethodDef for CountsSearchSearchMethod {
     type Output = SearchResponse;
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CountsSearchSearchStreamMethod;
+
+impl ::ntex_grpc::MethodDef for CountsSearchSearchStreamMethod {
+    const NAME: &'static str = "SearchStream";
+    const PATH: ::ntex_grpc::ByteString =
+        ::ntex_grpc::ByteString::from_static("/counts.CountsSearch/SearchStream");
+    const SERVER_STREAMING: bool = true;
+    type Input = SearchRequest;
+    type Output = Counts;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CountsSearchUploadMethod;
+
+impl ::ntex_grpc::MethodDef for CountsSearchUploadMethod {
+    const NAME: &'static str = "Upload";
+    const PATH: ::ntex_grpc::ByteString =
+        ::ntex_grpc::ByteString::from_static("/counts.CountsSearch/Upload");
+    const CLIENT_STREAMING: bool = true;
+    type Input = Count;
+    type Output = Counts;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CountsSearchEchoMethod;
+
+impl ::ntex_grpc::MethodDef for CountsSearchEchoMethod {
+    const NAME: &'static str = "Echo";
+    const PATH: ::ntex_grpc::ByteString =
+        ::ntex_grpc::ByteString::from_static("/counts.CountsSearch/Echo");
+    const CLIENT_STREAMING: bool = true;
+    const SERVER_STREAMING: bool = true;
+    type Input = Count;
+    type Output = Count;
+}
+
 mod _priv_impl {
     use super::*;
 
@@ -280,6 +320,7 @@ mod _priv_impl {
 
     impl ::ntex_grpc::ServiceDef for CountsSearch {
         const NAME: &'static str = "counts.CountsSearch";
+        const FILE_DESCRIPTORS: &'static [&'static [u8]] = &[b"\n\x0ccounts.proto\x12\x06counts\"%\n\rSearchRequest\x12\x14\n\x05query\x18\x01 \x01(\tR\x05query\":\n\x0eSearchResponse\x12(\n\x07results\x18\x01 \x03(\x0b2\x0e.counts.CountsR\x07results\"K\n\x05Count\x12\x14\n\x05value\x18\x01 \x01(\x01R\x05value\x12\x16\n\x06offset\x18\x02 \x01(\x04R\x06offset\x12\x14\n\x05count\x18\x03 \x01(\x04R\x05count\"/\n\x06Counts\x12%\n\x06counts\x18\x01 \x03(\x0b2\r.counts.CountR\x06counts2\xd5\x01\n\x0cCountsSearch\x127\n\x06Search\x12\x15.counts.SearchRequest\x1a\x16.counts.SearchResponse\x127\n\x0cSearchStream\x12\x15.counts.SearchRequest\x1a\x0e.counts.Counts0\x01\x12)\n\x06Upload\x12\r.counts.Count\x1a\x0e.counts.Counts(\x01\x12(\n\x04Echo\x12\r.counts.Count\x1a\r.counts.Count(\x010\x01b\x06proto3"];
         type Methods = CountsSearchMethods;
 
         #[inline]
@@ -289,6 +330,15 @@ mod _priv_impl {
                 CountsSearchSearchMethod::NAME => {
                     Some(CountsSearchMethods::Search(CountsSearchSearchMethod))
                 }
+                CountsSearchSearchStreamMethod::NAME => Some(CountsSearchMethods::SearchStream(
+                    CountsSearchSearchStreamMethod,
+                )),
+                CountsSearchUploadMethod::NAME => {
+                    Some(CountsSearchMethods::Upload(CountsSearchUploadMethod))
+                }
+                CountsSearchEchoMethod::NAME => {
+                    Some(CountsSearchMethods::Echo(CountsSearchEchoMethod))
+                }
                 _ => None,
             }
         }
@@ -336,4 +386,36 @@ mod _priv_impl {
             ::ntex_grpc::client::Request::new(&self.0, req)
         }
     }
+
+    impl<T: ::ntex_grpc::client::StreamingTransport<CountsSearchSearchStreamMethod>>
+        CountsSearchClient<T>
+    {
+        pub fn search_stream<'a>(
+            &'a self,
+            req: &'a super::SearchRequest,
+        ) -> ::ntex_grpc::client::ServerStreamingRequest<'a, T, CountsSearchSearchStreamMethod>
+        {
+            ::ntex_grpc::client::ServerStreamingRequest::new(&self.0, req)
+        }
+    }
+
+    impl<T: ::ntex_grpc::client::StreamingTransport<CountsSearchUploadMethod>> CountsSearchClient<T> {
+        pub fn upload<'a, S>(
+            &'a self,
+            req: S,
+        ) -> ::ntex_grpc::client::ClientStreamingRequest<'a, T, CountsSearchUploadMethod>
+        where
+            S: ::ntex_grpc::Stream<Item = super::Count> + 'a,
+        {
+            ::ntex_grpc::client::ClientStreamingRequest::new(&self.0, req)
+        }
+    }
+
+    impl<T: ::ntex_grpc::client::StreamingTransport<CountsSearchEchoMethod>> CountsSearchClient<T> {
+        pub fn echo(
+            &self,
+        ) -> ::ntex_grpc::client::BidiStreamingRequest<'_, T, CountsSearchEchoMethod> {
+            ::ntex_grpc::client::BidiStreamingRequest::new(&self.0)
+        }
+    }
 }
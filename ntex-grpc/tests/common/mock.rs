@@ -0,0 +1,157 @@
+//! Mock implementation of `counts.CountsSearch` service
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ntex::http::header::{HeaderName, HeaderValue};
+use ntex::time::{sleep, Millis};
+use ntex::{channel::mpsc, ServiceFactory};
+use ntex_grpc::google_rpc::{bad_request::FieldViolation, BadRequest, ErrorInfo};
+use ntex_grpc::server;
+use ntex_grpc::server::{ResponseStream, Streaming};
+use ntex_grpc::{GrpcStatus, Status};
+
+use super::counts::{
+    Count, Counts, SearchRequest as CountsSearchRequest, SearchResponse as CountsSearchResponse,
+};
+
+pub static CANCEL_STARTED: AtomicUsize = AtomicUsize::new(0);
+pub static CANCEL_COMPLETED: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone)]
+pub struct MockcountServer;
+
+#[server(crate::common::counts::CountsSearch)]
+impl MockcountServer {
+    #[method(Search)]
+    async fn search(
+        &self,
+        request: server::Request<CountsSearchRequest>,
+    ) -> Result<server::Response<CountsSearchResponse>, Status> {
+        if request.query == "missing" {
+            return Err(Status::new(GrpcStatus::NotFound, "Query is not found"));
+        } else if request.query == "unicode" {
+            return Err(Status::new(
+                GrpcStatus::FailedPrecondition,
+                "Запрос не выполнен: 100%\nretry",
+            ));
+        } else if request.query == "slow" {
+            sleep(Millis(1000)).await;
+        } else if request.query == "cancel" {
+            CANCEL_STARTED.fetch_add(1, Ordering::SeqCst);
+            sleep(Millis(500)).await;
+            CANCEL_COMPLETED.fetch_add(1, Ordering::SeqCst);
+        }
+
+        let mut response = server::Response::new(CountsSearchResponse {
+            results: vec![Counts {
+                counts: vec![
+                    Count {
+                        value: 382.8263,
+                        offset: 1,
+                        count: 73,
+                    },
+                    Count {
+                        value: 2129.284,
+                        offset: 7,
+                        count: 394,
+                    },
+                    Count {
+                        value: 6964.8898,
+                        offset: 15,
+                        count: 1287,
+                    },
+                    Count {
+                        value: 10017.193,
+                        offset: 30,
+                        count: 2009,
+                    },
+                ],
+            }],
+        });
+        if let Some(timeout) = request.timeout() {
+            response.headers.push((
+                HeaderName::from_static("x-timeout"),
+                HeaderValue::from(timeout.as_millis() as u64),
+            ));
+        }
+        for val in request.headers.get_all("x-request-value") {
+            response
+                .headers
+                .push((HeaderName::from_static("x-request-value"), val.clone()));
+        }
+        if let Some(val) = request.headers.get("x-request-trailer") {
+            response
+                .headers
+                .push((HeaderName::from_static("x-request-trailer"), val.clone()));
+        }
+        Ok(response)
+    }
+
+    #[method(SearchStream)]
+    async fn search_stream(&self, request: CountsSearchRequest) -> ResponseStream<Counts> {
+        let (tx, rx) = mpsc::channel();
+        if request.query.is_empty() {
+            let status = Status::new(GrpcStatus::InvalidArgument, "Invalid query")
+                .detail(&BadRequest {
+                    field_violations: vec![FieldViolation {
+                        field: "query".into(),
+                        description: "must not be empty".into(),
+                    }],
+                })
+                .detail(&ErrorInfo {
+                    reason: "EMPTY_QUERY".into(),
+                    domain: "counts".into(),
+                    metadata: Default::default(),
+                });
+            let _ = tx.send(Err(status.into()));
+            return ResponseStream::new(rx);
+        }
+        for offset in 0..3 {
+            let _ = tx.send(Ok(Counts {
+                counts: vec![Count {
+                    value: 1.0,
+                    offset,
+                    count: 1,
+                }],
+            }));
+        }
+        ResponseStream::new(rx)
+    }
+
+    #[method(Upload)]
+    async fn upload(&self, mut request: Streaming<Count>) -> Result<Counts, Status> {
+        let mut counts = Vec::new();
+        while let Some(count) = request.recv().await {
+            let count = count?;
+            if count.value < 0.0 {
+                // slow reader
+                sleep(Millis(5000)).await;
+            }
+            counts.push(count);
+        }
+        if counts.is_empty() {
+            Err(Status::new(
+                GrpcStatus::InvalidArgument,
+                "Nothing is uploaded",
+            ))
+        } else {
+            Ok(Counts { counts })
+        }
+    }
+
+    #[method(Echo)]
+    async fn echo(&self, request: Streaming<Count>) -> ResponseStream<Count> {
+        ResponseStream::new(request)
+    }
+}
+
+impl ServiceFactory<server::ServerRequest> for MockcountServer {
+    type Error = server::ServerError;
+    type InitError = ();
+    type Response = server::ServerResponse;
+    type Service = Self;
+
+    #[allow(clippy::no_effect_underscore_binding)]
+    async fn create(&self, _configuration: ()) -> Result<Self::Service, Self::InitError> {
+        Ok(Self)
+    }
+}
@@ -0,0 +1,229 @@
+//! Shared helpers of integration tests
+#![allow(dead_code)]
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::{future::Future, io, path::Path, thread, time::Duration};
+
+use ntex::http::header::{self, HeaderValue};
+use ntex::http::{HeaderMap, Method, StatusCode};
+use ntex::server::{Server, ServerBuilder, ServerStatus};
+use ntex::{io::Io, rt::System, time::Millis, util::Bytes, ServiceFactory};
+use ntex_grpc::server;
+use ntex_h2::{client as h2, MessageKind};
+
+pub mod counts;
+pub mod mock;
+
+use self::mock::MockcountServer;
+
+pub type MockServer = server::GrpcServer<MockcountServer>;
+
+/// Max duration of a single test
+const TEST_TIMEOUT: Millis = Millis(30_000);
+
+/// Run async test, test fails if it is not completed in time
+pub fn run<F: Future<Output = ()> + 'static>(test: F) {
+    System::new("test").block_on(async move {
+        ntex::time::timeout(TEST_TIMEOUT, test)
+            .await
+            .expect("test timed out")
+    })
+}
+
+/// Address on ephemeral port without server
+///
+/// Used for servers that are started later in the test.
+pub fn unused_address() -> String {
+    let lst = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+    lst.local_addr().unwrap().to_string()
+}
+
+/// Start grpc server with mock service, returns server address
+pub fn start_server() -> String {
+    start_server_with(|srv| srv)
+}
+
+/// Start grpc server with mock service on the address
+pub fn start_server_at(address: &str) -> String {
+    serve(address, || server::GrpcServer::new(MockcountServer))
+}
+
+/// Start grpc server with configured mock service
+pub fn start_server_with<T>(configure: fn(MockServer) -> server::GrpcServer<T>) -> String
+where
+    T: ServiceFactory<
+            server::ServerRequest,
+            Response = server::ServerResponse,
+            Error = server::ServerError,
+        > + 'static,
+    T::Service: Clone,
+{
+    serve("127.0.0.1:0", move || {
+        configure(server::GrpcServer::new(MockcountServer))
+    })
+}
+
+/// Start server on the address, waits until server accepts connections
+///
+/// Returns bound address, port `0` selects ephemeral port.
+pub fn serve<F, R>(address: &str, factory: F) -> String
+where
+    F: Fn() -> R + Send + Clone + 'static,
+    R: ServiceFactory<Io> + 'static,
+{
+    let mut bound = String::new();
+    start(|| {
+        let lst = bind(address);
+        bound = lst.local_addr().unwrap().to_string();
+        let factory = factory.clone();
+        move |builder: ServerBuilder| builder.listen("integration", lst, move |_| factory())
+    });
+    bound
+}
+
+/// Bind listener, waits until listener of restarted server is closed
+fn bind(address: &str) -> TcpListener {
+    for _ in 0..500 {
+        match TcpListener::bind(address) {
+            Ok(lst) => return lst,
+            Err(err) if err.kind() == io::ErrorKind::AddrInUse => {
+                thread::sleep(Duration::from_millis(10))
+            }
+            Err(err) => panic!("failed to bind server: {}", err),
+        }
+    }
+    panic!("failed to bind server, address is in use")
+}
+
+/// Run server configured by `setup`, waits until server accepts connections
+///
+/// Server could miss availability update of its worker and never start
+/// accepting connections, such server is stopped and started again.
+fn start<S, B>(mut setup: S)
+where
+    S: FnMut() -> B,
+    B: FnOnce(ServerBuilder) -> io::Result<ServerBuilder> + Send + 'static,
+{
+    for _ in 0..5 {
+        let build = setup();
+        let (tx, rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        thread::spawn(move || {
+            System::new("server").block_on(async move {
+                let server = build(Server::build())
+                    .expect("failed to start server")
+                    .status_handler(move |status| {
+                        if status == ServerStatus::Ready {
+                            let _ = ready_tx.send(());
+                        }
+                    })
+                    .workers(1)
+                    .disable_signals()
+                    .run();
+                let _ = tx.send(server.clone());
+                server.await.expect("failed to run server");
+            });
+        });
+        let server = rx
+            .recv_timeout(Duration::from_secs(10))
+            .expect("failed to start server");
+        if ready_rx.recv_timeout(Duration::from_millis(500)).is_ok() {
+            return;
+        }
+        // stop command is sent on call, completion is not awaited
+        drop(server.stop(false));
+    }
+    panic!("failed to start server")
+}
+
+/// Tcp proxy to server
+///
+/// Used to close client connections as if server is stopped.
+pub struct Proxy {
+    address: String,
+    streams: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl Proxy {
+    /// Start proxy to the address
+    pub fn start(target: &str) -> Self {
+        let lst = TcpListener::bind("127.0.0.1:0").expect("failed to bind proxy");
+        let address = lst.local_addr().unwrap().to_string();
+        let streams = Arc::new(Mutex::new(Vec::new()));
+
+        let target = target.to_string();
+        let streams2 = streams.clone();
+        thread::spawn(move || {
+            for stream in lst.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => break,
+                };
+                let upstream = TcpStream::connect(&target).expect("failed to connect server");
+                streams2.lock().unwrap().push(stream.try_clone().unwrap());
+                pipe(stream.try_clone().unwrap(), upstream.try_clone().unwrap());
+                pipe(upstream, stream);
+            }
+        });
+        Proxy { address, streams }
+    }
+
+    /// Proxy address
+    pub fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    /// Close proxied connections
+    pub fn close(&self) {
+        for stream in self.streams.lock().unwrap().drain(..) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn pipe(mut from: TcpStream, mut to: TcpStream) {
+    thread::spawn(move || {
+        let _ = io::copy(&mut from, &mut to);
+        let _ = to.shutdown(Shutdown::Both);
+    });
+}
+
+/// Start grpc server with mock service on unix domain socket
+#[cfg(unix)]
+pub fn serve_uds(path: &Path) {
+    start(|| {
+        let path = path.to_path_buf();
+        move |builder: ServerBuilder| {
+            builder.bind_uds("integration", path, |_| {
+                server::GrpcServer::new(MockcountServer)
+            })
+        }
+    });
+}
+
+/// Send raw request, returns response status, headers and end-of-stream flag
+pub async fn raw_call(
+    client: &h2::Client,
+    path: &'static str,
+    content_type: &'static str,
+    body: &'static [u8],
+) -> (Option<StatusCode>, HeaderMap, bool) {
+    let mut hdrs = HeaderMap::new();
+    hdrs.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    let (snd, rcv) = client
+        .send(Method::POST, path.into(), hdrs, false)
+        .await
+        .unwrap();
+    snd.send_payload(Bytes::from_static(body), true)
+        .await
+        .unwrap();
+
+    match rcv.recv().await.unwrap().kind {
+        MessageKind::Headers {
+            headers,
+            pseudo,
+            eof,
+        } => (pseudo.status, headers, eof),
+        kind => panic!("unexpected message: {:?}", kind),
+    }
+}
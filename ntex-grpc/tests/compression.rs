@@ -0,0 +1,222 @@
+use std::io;
+
+use ntex_bytes::BytesMut;
+use ntex_grpc::client::{Client, ClientError};
+use ntex_grpc::compression::{GzipCompressor, InvalidEncoding};
+use ntex_grpc::{CompressionEncoding, Compressor, Compressors, GrpcStatus, Status};
+use ntex_h2::client as h2;
+
+use common::counts::{CountsSearchClient, SearchRequest};
+use common::{run, start_server, start_server_with};
+
+mod common;
+
+#[test]
+fn gzip_compression() {
+    run(async {
+        let address = start_server_with(|srv| srv.compressors(gzip()));
+        let counts_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address.clone()).finish())
+                .compressors(gzip())
+                .compression(CompressionEncoding::Gzip),
+        );
+
+        let request = SearchRequest {
+            query: "test".repeat(1024).into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert_eq!(response.headers().get("grpc-encoding").unwrap(), "gzip");
+        assert!(response.req_size < 1024);
+        assert_eq!(response.output.results[0].counts.len(), 4);
+
+        // per-call encoding
+        let response = counts_client
+            .search(&request)
+            .compression(CompressionEncoding::Identity)
+            .await
+            .expect("failed to search");
+        assert!(response.req_size > 4096);
+        // response encoding is selected by server from accepted encodings
+        assert_eq!(response.headers().get("grpc-encoding").unwrap(), "gzip");
+
+        // client without compressors does not accept compressed responses
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert_eq!(response.headers().get("grpc-encoding").unwrap(), "identity");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+    });
+}
+
+fn gzip() -> Compressors {
+    Compressors::new().register(GzipCompressor).unwrap()
+}
+
+/// Reverses message bytes
+struct ReverseCompressor;
+
+impl Compressor for ReverseCompressor {
+    fn encoding(&self) -> CompressionEncoding {
+        CompressionEncoding::Custom("reverse")
+    }
+
+    fn compress(&self, src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        dst.extend(src.iter().rev().copied());
+        Ok(())
+    }
+
+    fn decompress(&self, src: &[u8], dst: &mut BytesMut, _: usize) -> io::Result<()> {
+        dst.extend(src.iter().rev().copied());
+        Ok(())
+    }
+}
+
+#[test]
+fn custom_compressor() {
+    run(async {
+        let address = start_server_with(|srv| {
+            srv.compressors(Compressors::new().register(ReverseCompressor).unwrap())
+        });
+        let counts_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address.clone()).finish())
+                .compressors(Compressors::new().register(ReverseCompressor).unwrap())
+                .compression(CompressionEncoding::Custom("reverse")),
+        );
+
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert_eq!(response.headers().get("grpc-encoding").unwrap(), "reverse");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+
+        // gzip is not registered on server
+        let err = counts_client
+            .search(&request)
+            .compression(CompressionEncoding::Gzip)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ClientError::Compression(_)));
+    });
+}
+
+#[test]
+fn compression_min_size() {
+    run(async {
+        let address = start_server_with(|srv| srv.compressors(gzip()));
+        let counts_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address.clone()).finish())
+                .compressors(gzip().min_size(1024))
+                .compression(CompressionEncoding::Gzip),
+        );
+
+        // small messages are sent uncompressed
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert_eq!(response.req_size, 5 + 6);
+        assert_eq!(response.output.results[0].counts.len(), 4);
+
+        let request = SearchRequest {
+            query: "test".repeat(1024).into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert!(response.req_size < 1024);
+    });
+}
+
+#[test]
+fn unsupported_compression() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let err = counts_client
+            .search(&request)
+            .header("grpc-encoding", "br")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::Unimplemented,
+                    ..
+                },
+                _
+            )
+        ));
+
+        // compressors are not registered on server by default
+        let counts_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address.clone()).finish())
+                .compressors(gzip())
+                .compression(CompressionEncoding::Gzip),
+        );
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::Unimplemented,
+                    ..
+                },
+                _
+            )
+        ));
+    });
+}
+
+/// Compressor with configurable encoding name
+struct NamedCompressor(&'static str);
+
+impl Compressor for NamedCompressor {
+    fn encoding(&self) -> CompressionEncoding {
+        CompressionEncoding::Custom(self.0)
+    }
+
+    fn compress(&self, src: &[u8], dst: &mut BytesMut) -> io::Result<()> {
+        dst.extend_from_slice(src);
+        Ok(())
+    }
+
+    fn decompress(&self, src: &[u8], dst: &mut BytesMut, _: usize) -> io::Result<()> {
+        dst.extend_from_slice(src);
+        Ok(())
+    }
+}
+
+#[test]
+fn invalid_encoding_name() {
+    for name in ["", "rev erse", "rev,erse", "тест", "gzip", "identity"] {
+        let err = Compressors::new()
+            .register(NamedCompressor(name))
+            .unwrap_err();
+        assert_eq!(err, InvalidEncoding(name));
+    }
+    assert!(Compressors::new()
+        .register(NamedCompressor("x-reverse.v1"))
+        .is_ok());
+}
@@ -0,0 +1,159 @@
+use std::sync::atomic::Ordering;
+use std::{time::Duration, time::Instant};
+
+use ntex::time::{sleep, Millis};
+use ntex::util::{select, Either};
+use ntex_grpc::client::{Client, ClientError};
+use ntex_grpc::{GrpcStatus, Status};
+use ntex_h2::client as h2;
+
+use common::counts::{CountsSearchClient, SearchRequest};
+use common::mock::{self};
+use common::{run, start_server};
+
+mod common;
+
+#[test]
+fn client_deadline() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        // remaining time is available to the handler
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .timeout(Duration::from_secs(5))
+            .await
+            .expect("failed to search");
+        let timeout: u64 = response
+            .trailers()
+            .get("x-timeout")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(timeout > 0 && timeout <= 5000);
+
+        let request = SearchRequest {
+            query: "slow".into(),
+        };
+        let start = Instant::now();
+        let err = counts_client
+            .search(&request)
+            .timeout(Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::DeadlineExceeded,
+                    ..
+                },
+                _
+            )
+        ));
+        assert!(start.elapsed() < Duration::from_millis(1000));
+
+        // deadline is checked before call
+        let err = counts_client
+            .search(&request)
+            .deadline(Instant::now())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::DeadlineExceeded,
+                    ..
+                },
+                _
+            )
+        ));
+    });
+}
+
+#[test]
+fn server_deadline() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        let request = SearchRequest {
+            query: "slow".into(),
+        };
+        let start = Instant::now();
+        let err = counts_client
+            .search(&request)
+            .header("grpc-timeout", "100m")
+            .await
+            .unwrap_err();
+        match err {
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::DeadlineExceeded,
+                    ..
+                },
+                trailers,
+            ) => {
+                assert_eq!(trailers.get("grpc-message").unwrap(), "Deadline exceeded")
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+        assert!(start.elapsed() < Duration::from_millis(1000));
+
+        let err = counts_client
+            .search(&request)
+            .header("grpc-timeout", "100")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::InvalidArgument,
+                    ..
+                },
+                _
+            )
+        ));
+    });
+}
+
+#[test]
+fn cancel_call() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        let request = SearchRequest {
+            query: "cancel".into(),
+        };
+        let started = async {
+            while mock::CANCEL_STARTED.load(Ordering::SeqCst) == 0 {
+                sleep(Millis(5)).await;
+            }
+        };
+        // request future is dropped as soon as handler is started
+        match select(counts_client.search(&request), started).await {
+            Either::Left(res) => panic!("call is not cancelled: {:?}", res),
+            Either::Right(()) => (),
+        }
+
+        // handler completes in 500ms unless it is cancelled
+        sleep(Millis(750)).await;
+        assert_eq!(mock::CANCEL_STARTED.load(Ordering::SeqCst), 1);
+        assert_eq!(mock::CANCEL_COMPLETED.load(Ordering::SeqCst), 0);
+    });
+}
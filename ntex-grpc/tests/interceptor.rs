@@ -0,0 +1,176 @@
+use std::cell::Cell;
+
+use ntex::http::header::{self, HeaderName, HeaderValue};
+use ntex_bytes::ByteString;
+use ntex_grpc::client::RequestContext;
+use ntex_grpc::client::{self, Client, ClientError, ClientInformation};
+use ntex_grpc::{server, GrpcStatus, MethodDef, Status};
+use ntex_h2::client as h2;
+
+use common::counts::{CountsSearchClient, SearchRequest};
+use common::{run, start_server_with};
+
+mod common;
+
+struct AuthInterceptor;
+
+impl server::Interceptor for AuthInterceptor {
+    async fn request(&self, req: &mut server::ServerRequest) -> Result<(), Status> {
+        match req.headers.get(header::AUTHORIZATION) {
+            Some(val) if val == "Bearer secret" => Ok(()),
+            _ => Err(Status::new(GrpcStatus::Unauthenticated, "Invalid token")),
+        }
+    }
+}
+
+struct MethodInterceptor;
+
+impl server::Interceptor for MethodInterceptor {
+    async fn response(
+        &self,
+        name: &ByteString,
+        res: Result<server::ServerResponse, server::ServerError>,
+    ) -> Result<server::ServerResponse, server::ServerError> {
+        res.map(|mut res| {
+            res.headers.push((
+                HeaderName::from_static("x-method"),
+                HeaderValue::from_str(name).unwrap(),
+            ));
+            res
+        })
+    }
+}
+
+#[test]
+fn server_interceptor() {
+    run(async {
+        let address = start_server_with(|srv| {
+            srv.interceptor(MethodInterceptor)
+                .interceptor(AuthInterceptor)
+        });
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        match counts_client.search(&request).await {
+            Err(ClientError::GrpcStatus(status, _)) => {
+                assert_eq!(status.code, GrpcStatus::Unauthenticated);
+                assert_eq!(status.message, "Invalid token");
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        let response = counts_client
+            .search(&request)
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .await
+            .expect("failed to search");
+        assert_eq!(response.trailers().get("x-method").unwrap(), "Search");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+
+        // streaming calls are intercepted as well
+        let mut stream = counts_client
+            .search_stream(&request)
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .await
+            .expect("failed to search");
+        while let Some(item) = stream.recv().await {
+            assert!(item.is_ok());
+        }
+        assert_eq!(stream.trailers().get("x-method").unwrap(), "SearchStream");
+    });
+}
+
+#[derive(Default)]
+struct TokenInterceptor {
+    responses: Cell<usize>,
+    res_size: Cell<usize>,
+    errors: Cell<usize>,
+}
+
+impl client::Interceptor for TokenInterceptor {
+    async fn request<M: MethodDef>(&self, ctx: &mut RequestContext) -> Result<(), ClientError> {
+        if ctx.headers().iter().any(|(name, _)| name == "x-no-token") {
+            return Err(Status::new(GrpcStatus::Cancelled, "Token is not requested").into());
+        }
+        ctx.header(header::AUTHORIZATION, "Bearer secret");
+        Ok(())
+    }
+
+    async fn response<M: MethodDef>(&self, res: &mut client::Response<M>) {
+        self.responses.set(self.responses.get() + 1);
+        self.res_size.set(self.res_size.get() + res.res_size);
+    }
+
+    async fn error<M: MethodDef>(&self, err: ClientError) -> ClientError {
+        self.errors.set(self.errors.get() + 1);
+        match err {
+            ClientError::GrpcStatus(mut status, hdrs) => {
+                status.message = format!("{}: {}", M::NAME, status.message).into();
+                ClientError::GrpcStatus(status, hdrs)
+            }
+            err => err,
+        }
+    }
+}
+
+#[test]
+fn client_interceptor() {
+    run(async {
+        let address = start_server_with(|srv| srv.interceptor(AuthInterceptor));
+        let counts_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address.clone()).finish())
+                .interceptor(TokenInterceptor::default()),
+        );
+
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+
+        let interceptor = counts_client.transport().get_interceptor();
+        assert_eq!(interceptor.responses.get(), 1);
+        assert_eq!(interceptor.res_size.get(), response.res_size);
+
+        // streaming calls
+        let mut stream = counts_client
+            .search_stream(&request)
+            .await
+            .expect("failed to search");
+        assert!(stream.recv().await.unwrap().is_ok());
+
+        // call is failed by interceptor
+        match counts_client
+            .search(&request)
+            .header("x-no-token", "1")
+            .await
+        {
+            Err(ClientError::GrpcStatus(status, _)) => {
+                assert_eq!(status.code, GrpcStatus::Cancelled);
+                assert_eq!(status.message, "Search: Token is not requested");
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // errors are transformed
+        let request = SearchRequest {
+            query: "missing".into(),
+        };
+        match counts_client.search(&request).await {
+            Err(ClientError::GrpcStatus(status, _)) => {
+                assert_eq!(status.code, GrpcStatus::NotFound);
+                assert_eq!(status.message, "Search: Query is not found");
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        assert_eq!(interceptor.responses.get(), 1);
+        assert_eq!(interceptor.errors.get(), 2);
+    });
+}
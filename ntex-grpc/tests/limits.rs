@@ -0,0 +1,122 @@
+use ntex_grpc::client::{Client, ClientError};
+use ntex_grpc::compression::GzipCompressor;
+use ntex_grpc::{CompressionEncoding, Compressors, GrpcStatus};
+use ntex_h2::client as h2;
+
+use common::counts::{CountsSearchClient, SearchRequest};
+use common::{run, start_server_with};
+
+mod common;
+
+#[test]
+fn max_message_size() {
+    run(async {
+        let address = start_server_with(|srv| srv.max_recv_message_size(64));
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        let response = counts_client
+            .search(&SearchRequest {
+                query: "test".into(),
+            })
+            .await
+            .expect("failed to search");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+
+        // request is rejected by server
+        let request = SearchRequest {
+            query: "q".repeat(128).into(),
+        };
+        match counts_client.search(&request).await {
+            Err(ClientError::GrpcStatus(status, _)) => {
+                assert_eq!(status.code, GrpcStatus::ResourceExhausted);
+                assert_eq!(
+                    status.message,
+                    "Received message larger than max (131 vs. 64)"
+                );
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // request is rejected by client
+        let small_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address.clone()).finish())
+                .max_send_message_size(16),
+        );
+        match small_client.search(&request).await {
+            Err(ClientError::GrpcStatus(status, _)) => {
+                assert_eq!(status.code, GrpcStatus::ResourceExhausted);
+                assert_eq!(status.message, "Sent message larger than max (131 vs. 16)");
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // response is rejected by client
+        let small_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address).finish()).max_recv_message_size(16),
+        );
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        match small_client.search(&request).await {
+            Err(ClientError::GrpcStatus(status, _)) => {
+                assert_eq!(status.code, GrpcStatus::ResourceExhausted);
+                assert!(status
+                    .message
+                    .starts_with("Received message larger than max"));
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // response is rejected by server
+        let address = start_server_with(|srv| srv.max_send_message_size(16));
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+        match counts_client.search(&request).await {
+            Err(ClientError::GrpcStatus(status, _)) => {
+                assert_eq!(status.code, GrpcStatus::ResourceExhausted);
+                assert!(status.message.starts_with("Sent message larger than max"));
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    });
+}
+
+#[test]
+fn decompressed_message_size() {
+    run(async {
+        let address = start_server_with(|srv| {
+            srv.max_recv_message_size(4096)
+                .compressors(Compressors::new().register(GzipCompressor).unwrap())
+        });
+        let counts_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address.clone()).finish())
+                .compressors(Compressors::new().register(GzipCompressor).unwrap())
+                .compression(CompressionEncoding::Gzip),
+        );
+
+        // 1Mb message is compressed to frame that fits the limit
+        let request = SearchRequest {
+            query: "a".repeat(1024 * 1024).into(),
+        };
+        match counts_client.search(&request).await {
+            Err(ClientError::GrpcStatus(status, _)) => {
+                assert_eq!(status.code, GrpcStatus::ResourceExhausted);
+                assert_eq!(
+                    status.message,
+                    "Received message after decompression larger than max (4096)"
+                );
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        let response = counts_client
+            .search(&SearchRequest {
+                query: "test".into(),
+            })
+            .await
+            .expect("failed to search");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+    });
+}
@@ -0,0 +1,248 @@
+use ntex::util::Bytes;
+use ntex_bytes::ByteString;
+use ntex_grpc::client::{self, Client, ClientError};
+use ntex_grpc::health::ServingStatus;
+use ntex_grpc::health::{HealthCheckRequest, HealthClient, HealthReporter, HealthServer};
+use ntex_grpc::reflection::server_reflection_request::MessageRequest;
+use ntex_grpc::reflection::server_reflection_response::MessageResponse;
+use ntex_grpc::reflection::{ReflectionServer, ServerReflectionClient, ServerReflectionRequest};
+use ntex_grpc::{server, GrpcStatus, MethodDef, ServiceDef, Status};
+use ntex_h2::client as h2;
+
+use common::counts::{CountsSearch, CountsSearchClient, SearchRequest, SearchResponse};
+use common::mock::MockcountServer;
+use common::{run, serve, start_server};
+
+mod common;
+
+#[test]
+fn health_service() {
+    run(async {
+        let reporter = HealthReporter::new();
+        reporter.set_serving::<CountsSearch>();
+        let health = HealthServer::new(reporter.clone());
+        let address = serve("127.0.0.1:0", move || {
+            server::GrpcServer::new(
+                server::Router::new()
+                    .service(MockcountServer)
+                    .service(health.clone()),
+            )
+        });
+
+        let client = Client::new(h2::Client::with_default(address).finish());
+        let health_client = HealthClient::new(client.clone());
+        let check = |service: &'static str| HealthCheckRequest {
+            service: service.into(),
+        };
+
+        let res = health_client.check(&check("")).await.unwrap();
+        assert_eq!(res.status, ServingStatus::Serving);
+        let res = health_client
+            .check(&check("counts.CountsSearch"))
+            .await
+            .unwrap();
+        assert_eq!(res.status, ServingStatus::Serving);
+        let err = health_client.check(&check("unknown")).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::NotFound,
+                    ..
+                },
+                _
+            )
+        ));
+
+        // application service is served next to health service
+        let counts_client = CountsSearchClient::new(client);
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        assert!(counts_client.search(&request).await.is_ok());
+
+        let request = check("counts.CountsSearch");
+        let mut stream = health_client.watch(&request).await.unwrap();
+        let res = stream.recv().await.unwrap().unwrap();
+        assert_eq!(res.status, ServingStatus::Serving);
+
+        reporter.set_not_serving::<CountsSearch>();
+        let res = stream.recv().await.unwrap().unwrap();
+        assert_eq!(res.status, ServingStatus::NotServing);
+
+        reporter.clear_serving_status("counts.CountsSearch");
+        let res = stream.recv().await.unwrap().unwrap();
+        assert_eq!(res.status, ServingStatus::ServiceUnknown);
+    });
+}
+
+#[test]
+fn reflection_service() {
+    run(async {
+        let address = serve("127.0.0.1:0", || {
+            server::GrpcServer::new(
+                server::Router::new()
+                    .service(MockcountServer)
+                    .service(ReflectionServer::new().register::<CountsSearch>()),
+            )
+        });
+
+        let client = Client::new(h2::Client::with_default(address).finish());
+        let reflection_client = ServerReflectionClient::new(client);
+        let (tx, mut rx) = reflection_client
+            .server_reflection_info()
+            .await
+            .expect("failed to start call");
+        let request = |msg| ServerReflectionRequest {
+            host: ByteString::new(),
+            message_request: Some(msg),
+        };
+
+        tx.send(&request(MessageRequest::ListServices("*".into())))
+            .await
+            .unwrap();
+        match rx.recv().await.unwrap().unwrap().message_response {
+            Some(MessageResponse::ListServicesResponse(res)) => assert_eq!(
+                res.service
+                    .iter()
+                    .map(|srv| srv.name.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["grpc.reflection.v1.ServerReflection", "counts.CountsSearch"]
+            ),
+            res => panic!("unexpected response: {:?}", res),
+        }
+
+        for msg in [
+            MessageRequest::FileContainingSymbol("counts.CountsSearch".into()),
+            MessageRequest::FileContainingSymbol("counts.SearchRequest".into()),
+            MessageRequest::FileByFilename("counts.proto".into()),
+        ] {
+            tx.send(&request(msg.clone())).await.unwrap();
+            let res = rx.recv().await.unwrap().unwrap();
+            assert_eq!(res.original_request.message_request, Some(msg));
+            match res.message_response {
+                Some(MessageResponse::FileDescriptorResponse(res)) => assert_eq!(
+                    res.file_descriptor_proto,
+                    vec![Bytes::from_static(
+                        <CountsSearch as ServiceDef>::FILE_DESCRIPTORS[0]
+                    )]
+                ),
+                res => panic!("unexpected response: {:?}", res),
+            }
+        }
+
+        tx.send(&request(MessageRequest::FileContainingSymbol(
+            "counts.Unknown".into(),
+        )))
+        .await
+        .unwrap();
+        match rx.recv().await.unwrap().unwrap().message_response {
+            Some(MessageResponse::ErrorResponse(res)) => {
+                assert_eq!(res.error_code, GrpcStatus::NotFound as i32)
+            }
+            res => panic!("unexpected response: {:?}", res),
+        }
+
+        tx.close().await.expect("failed to close call");
+        assert!(rx.recv().await.is_none());
+    });
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct UnknownSearchMethod;
+
+impl MethodDef for UnknownSearchMethod {
+    const NAME: &'static str = "Search";
+    const PATH: ByteString = ByteString::from_static("/counts.Unknown/Search");
+    type Input = SearchRequest;
+    type Output = SearchResponse;
+}
+
+#[test]
+fn router() {
+    run(async {
+        let reporter = HealthReporter::new();
+        reporter.set_serving::<CountsSearch>();
+
+        let address = serve("127.0.0.1:0", move || {
+            server::GrpcServer::new(
+                server::Router::new()
+                    .service(MockcountServer)
+                    .service(HealthServer::new(reporter.clone()))
+                    .service(ReflectionServer::new().register::<CountsSearch>()),
+            )
+        });
+
+        let client = Client::new(h2::Client::with_default(address).finish());
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let counts_client = CountsSearchClient::new(client.clone());
+        assert!(counts_client.search(&request).await.is_ok());
+
+        let health_client = HealthClient::new(client.clone());
+        let res = health_client
+            .check(&HealthCheckRequest {
+                service: "counts.CountsSearch".into(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(res.status, ServingStatus::Serving);
+
+        let reflection_client = ServerReflectionClient::new(client.clone());
+        let (tx, mut rx) = reflection_client.server_reflection_info().await.unwrap();
+        tx.send(&ServerReflectionRequest {
+            host: ByteString::new(),
+            message_request: Some(MessageRequest::ListServices("*".into())),
+        })
+        .await
+        .unwrap();
+        let res = rx.recv().await.unwrap().unwrap();
+        assert!(matches!(
+            res.message_response,
+            Some(MessageResponse::ListServicesResponse(_))
+        ));
+        tx.close().await.unwrap();
+
+        // unknown service
+        let err = client::Request::<_, UnknownSearchMethod>::new(&client, &request)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::Unimplemented,
+                    ..
+                },
+                _
+            )
+        ));
+    });
+}
+
+#[test]
+fn unknown_service() {
+    run(async {
+        let address = start_server();
+        let client = Client::new(h2::Client::with_default(address).finish());
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+
+        // method name matches, but service does not
+        let err = client::Request::<_, UnknownSearchMethod>::new(&client, &request)
+            .await
+            .unwrap_err();
+        match err {
+            ClientError::GrpcStatus(status, _) => {
+                assert_eq!(status.code, GrpcStatus::Unimplemented);
+                assert_eq!(
+                    status.message,
+                    "Service method is not found: /counts.Unknown/Search"
+                );
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+    });
+}
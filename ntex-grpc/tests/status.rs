@@ -0,0 +1,167 @@
+use ntex::channel::mpsc;
+use ntex::http::StatusCode;
+use ntex_grpc::client::{Client, ClientError};
+use ntex_grpc::google_rpc::{bad_request::FieldViolation, BadRequest, ErrorInfo};
+use ntex_grpc::GrpcStatus;
+use ntex_h2::client as h2;
+
+use common::counts::{Count, CountsSearchClient, SearchRequest};
+use common::{raw_call, run, start_server};
+
+mod common;
+
+#[test]
+fn status_details() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        let request = SearchRequest { query: "".into() };
+        let mut stream = counts_client
+            .search_stream(&request)
+            .await
+            .expect("failed to search");
+        let status = match stream.recv().await {
+            Some(Err(ClientError::GrpcStatus(status, _))) => status,
+            res => panic!("unexpected result: {:?}", res),
+        };
+        assert_eq!(status.code, GrpcStatus::InvalidArgument);
+        assert_eq!(status.message, "Invalid query");
+        assert_eq!(status.details.len(), 2);
+        assert_eq!(
+            status.get_detail::<BadRequest>().unwrap().field_violations,
+            vec![FieldViolation {
+                field: "query".into(),
+                description: "must not be empty".into(),
+            }]
+        );
+        assert_eq!(
+            status.get_detail::<ErrorInfo>().unwrap().reason,
+            "EMPTY_QUERY"
+        );
+    });
+}
+
+#[test]
+fn handler_error() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        let request = SearchRequest {
+            query: "missing".into(),
+        };
+        match counts_client.search(&request).await {
+            Err(ClientError::GrpcStatus(status, trailers)) => {
+                assert_eq!(status.code, GrpcStatus::NotFound);
+                assert_eq!(status.message, "Query is not found");
+                assert_eq!(trailers.get("grpc-status").unwrap(), "5");
+            }
+            res => panic!("unexpected result: {:?}", res.map(|r| r.into_inner())),
+        }
+
+        // message is percent-encoded
+        let request = SearchRequest {
+            query: "unicode".into(),
+        };
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("FailedPrecondition: Запрос не выполнен: 100%\nretry"));
+        match err {
+            ClientError::GrpcStatus(status, trailers) => {
+                assert_eq!(status.message, "Запрос не выполнен: 100%\nretry");
+                assert!(trailers.get("grpc-message").unwrap().to_str().is_ok());
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        let (tx, rx) = mpsc::channel::<Count>();
+        drop(tx);
+        let err = counts_client.upload(rx).await.unwrap_err();
+        match err {
+            ClientError::GrpcStatus(status, _) => {
+                assert_eq!(status.code, GrpcStatus::InvalidArgument);
+                assert_eq!(status.message, "Nothing is uploaded");
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+    });
+}
+
+#[test]
+fn grpc_web_request() {
+    run(async {
+        let address = start_server();
+        let client = h2::Client::with_default(address.clone()).finish();
+
+        // grpc-web requests are served by grpc-web server only
+        for content_type in [
+            "application/grpc-web",
+            "application/grpc-web+proto",
+            "application/grpc-web-text",
+        ] {
+            let (status, _, eof) =
+                raw_call(&client, "/counts.CountsSearch/Search", content_type, b"").await;
+            assert_eq!(status, Some(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+            assert!(eof);
+        }
+    });
+}
+
+#[test]
+fn status_mapping() {
+    run(async {
+        let address = start_server();
+        let client = h2::Client::with_default(address.clone()).finish();
+
+        // not a grpc request
+        let (status, _, eof) =
+            raw_call(&client, "/counts.CountsSearch/Search", "text/plain", b"").await;
+        assert_eq!(status, Some(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+        assert!(eof);
+
+        // errors are sent as trailers-only response
+        let (status, hdrs, eof) = raw_call(&client, "/unknown", "application/grpc", b"").await;
+        assert_eq!(status, Some(StatusCode::OK));
+        assert_eq!(hdrs.get("grpc-status").unwrap(), "12");
+        assert!(eof);
+
+        let (status, hdrs, eof) = raw_call(
+            &client,
+            "/counts.CountsSearch/Unknown",
+            "application/grpc",
+            b"",
+        )
+        .await;
+        assert_eq!(status, Some(StatusCode::OK));
+        assert_eq!(hdrs.get("grpc-status").unwrap(), "12");
+        assert!(eof);
+
+        let (status, hdrs, eof) = raw_call(
+            &client,
+            "/counts.CountsSearch/Search",
+            "application/grpc+proto",
+            &[0, 0, 0, 0, 2, 0x0a, 0xff],
+        )
+        .await;
+        assert_eq!(status, Some(StatusCode::OK));
+        assert_eq!(hdrs.get("grpc-status").unwrap(), "3");
+        assert!(eof);
+
+        let (_, hdrs, eof) = raw_call(
+            &client,
+            "/counts.CountsSearch/Search",
+            "application/grpc",
+            &[0, 0, 0, 0, 0],
+        )
+        .await;
+        assert_eq!(hdrs.get("grpc-encoding").unwrap(), "identity");
+        assert!(hdrs.get("grpc-status").is_none());
+        assert!(!eof);
+    });
+}
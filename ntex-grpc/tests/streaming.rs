@@ -0,0 +1,254 @@
+use ntex::http::header::{self, HeaderName, HeaderValue};
+use ntex::http::{HeaderMap, Method, StatusCode};
+use ntex::time::{timeout, Millis};
+use ntex::{channel::mpsc, util::Bytes};
+use ntex_grpc::client::Client;
+use ntex_h2::{client as h2, MessageKind, StreamEof};
+
+use common::counts::{Count, CountsSearchClient, SearchRequest};
+use common::{raw_call, run, start_server};
+
+mod common;
+
+#[test]
+fn search_count() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        dbg!(response);
+    });
+}
+
+#[test]
+fn request_repeated_headers() {
+    run(async {
+        let address = start_server();
+        let counts_client =
+            CountsSearchClient::new(Client::new(h2::Client::with_default(address).finish()));
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .header("x-request-value", "1")
+            .header("x-request-value", "2")
+            .await
+            .unwrap();
+        let values: Vec<_> = response.trailers().get_all("x-request-value").collect();
+        assert_eq!(values, vec!["1", "2"]);
+    });
+}
+
+#[test]
+fn search_stream() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let mut stream = counts_client
+            .search_stream(&request)
+            .await
+            .expect("failed to search");
+
+        let mut offsets = Vec::new();
+        while let Some(item) = stream.recv().await {
+            let counts = item.expect("failed to receive counts");
+            offsets.push(counts.counts[0].offset);
+        }
+        assert_eq!(offsets, vec![0, 1, 2]);
+        assert_eq!(
+            stream.trailers().get("grpc-status").unwrap().as_bytes(),
+            b"0"
+        );
+    });
+}
+
+#[test]
+fn upload_stream() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        let (tx, rx) = mpsc::channel();
+        for offset in 0..3 {
+            let _ = tx.send(Count {
+                value: 1.0,
+                offset,
+                count: 1,
+            });
+        }
+        drop(tx);
+
+        let response = counts_client.upload(rx).await.expect("failed to upload");
+        let offsets: Vec<_> = response.output.counts.iter().map(|c| c.offset).collect();
+        assert_eq!(offsets, vec![0, 1, 2]);
+    });
+}
+
+#[test]
+fn upload_stream_error() {
+    run(async {
+        let address = start_server();
+        let client = h2::Client::with_default(address).finish();
+
+        // malformed message after valid one fails the call
+        let (status, hdrs, eof) = raw_call(
+            &client,
+            "/counts.CountsSearch/Upload",
+            "application/grpc",
+            &[0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0x09, 0, 0],
+        )
+        .await;
+        assert_eq!(status, Some(StatusCode::OK));
+        assert_eq!(hdrs.get("grpc-status").unwrap(), "3");
+        assert!(eof);
+    });
+}
+
+#[test]
+fn upload_slow_reader() {
+    run(async {
+        let address = start_server();
+        let client = h2::Client::with_default(address).finish();
+
+        let mut hdrs = HeaderMap::new();
+        hdrs.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc"),
+        );
+        let (snd, _rcv) = client
+            .send(
+                Method::POST,
+                "/counts.CountsSearch/Upload".into(),
+                hdrs,
+                false,
+            )
+            .await
+            .unwrap();
+
+        // negative value stalls the service
+        let mut msg = vec![0, 0, 0, 0, 9, 0x09];
+        msg.extend_from_slice(&(-1.0f64).to_le_bytes());
+        snd.send_payload(Bytes::from(msg), false).await.unwrap();
+
+        // 1kb messages with unknown field
+        let mut msg = vec![0, 0, 0, 0x04, 0x03, 0x7a, 0x80, 0x08];
+        msg.resize(1032, 0);
+        let msg = Bytes::from(msg);
+
+        // peer cannot send more than stream window while messages are not received
+        let send = async {
+            for _ in 0..1024 {
+                snd.send_payload(msg.clone(), false).await.unwrap();
+            }
+        };
+        assert!(timeout(Millis(1000), send).await.is_err());
+    });
+}
+
+#[test]
+fn unary_extra_message() {
+    run(async {
+        let address = start_server();
+        let client = h2::Client::with_default(address).finish();
+
+        // unary call accepts single request message
+        let (status, hdrs, eof) = raw_call(
+            &client,
+            "/counts.CountsSearch/Search",
+            "application/grpc",
+            &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        )
+        .await;
+        assert_eq!(status, Some(StatusCode::OK));
+        assert_eq!(hdrs.get("grpc-status").unwrap(), "13");
+        assert!(eof);
+    });
+}
+
+#[test]
+fn unary_request_trailers() {
+    run(async {
+        let address = start_server();
+        let client = h2::Client::with_default(address).finish();
+
+        // trailers of unary request are merged into request headers
+        let mut hdrs = HeaderMap::new();
+        hdrs.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc"),
+        );
+        let (snd, rcv) = client
+            .send(
+                Method::POST,
+                "/counts.CountsSearch/Search".into(),
+                hdrs,
+                false,
+            )
+            .await
+            .unwrap();
+        snd.send_payload(Bytes::from_static(&[0, 0, 0, 0, 0]), false)
+            .await
+            .unwrap();
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            HeaderName::from_static("x-request-trailer"),
+            HeaderValue::from_static("1"),
+        );
+        snd.send_trailers(trailers);
+
+        // response headers of service are sent in trailers
+        loop {
+            match rcv.recv().await.unwrap().kind {
+                MessageKind::Eof(StreamEof::Trailers(trailers)) => {
+                    assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+                    assert_eq!(trailers.get("x-request-trailer").unwrap(), "1");
+                    break;
+                }
+                MessageKind::Headers { .. } | MessageKind::Data(..) => (),
+                kind => panic!("unexpected message: {:?}", kind),
+            }
+        }
+    });
+}
+
+#[test]
+fn echo_stream() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(Client::new(
+            h2::Client::with_default(address.clone()).finish(),
+        ));
+
+        let (tx, mut rx) = counts_client.echo().await.expect("failed to start call");
+        for offset in 0..3 {
+            tx.send(&Count {
+                value: 1.0,
+                offset,
+                count: 1,
+            })
+            .await
+            .expect("failed to send count");
+
+            let count = rx.recv().await.unwrap().expect("failed to receive count");
+            assert_eq!(count.offset, offset);
+        }
+        tx.close().await.expect("failed to close call");
+        assert!(rx.recv().await.is_none());
+    });
+}
@@ -0,0 +1,478 @@
+use std::{cell::RefCell, collections::VecDeque, io, rc::Rc};
+use std::{time::Duration, time::Instant};
+
+use ntex::http::header::{HeaderName, HeaderValue};
+use ntex::http::HeaderMap;
+use ntex::time::sleep;
+use ntex_grpc::client::RequestContext;
+use ntex_grpc::client::{self, Client, ClientError};
+use ntex_grpc::client::{Balancer, Hedge, HedgingPolicy, LoadBalancingPolicy};
+use ntex_grpc::client::{ConnectBackoff, ConnectivityState};
+use ntex_grpc::client::{Retry, RetryPolicy, Transport};
+use ntex_grpc::{GrpcStatus, MethodDef, Status};
+use ntex_h2::client as h2;
+
+use common::counts::{
+    CountsSearchClient, CountsSearchSearchMethod, SearchRequest, SearchResponse,
+};
+use common::unused_address;
+use common::{run, start_server, start_server_at};
+
+mod common;
+
+/// Prepared call result with response delay
+type StubResult = (Duration, Result<(), ClientError>);
+
+/// Transport that replays prepared call results
+#[derive(Clone, Default)]
+struct StubTransport {
+    results: Rc<RefCell<VecDeque<StubResult>>>,
+    attempts: Rc<RefCell<Vec<Option<HeaderValue>>>>,
+}
+
+impl StubTransport {
+    fn push(&self, res: Result<(), ClientError>) {
+        self.push_delayed(Duration::ZERO, res);
+    }
+
+    fn push_delayed(&self, delay: Duration, res: Result<(), ClientError>) {
+        self.results.borrow_mut().push_back((delay, res));
+    }
+
+    fn attempts(&self) -> Vec<Option<HeaderValue>> {
+        self.attempts.borrow_mut().drain(..).collect()
+    }
+}
+
+impl Transport<CountsSearchSearchMethod> for StubTransport {
+    type Error = ClientError;
+
+    async fn request(
+        &self,
+        _: &SearchRequest,
+        ctx: RequestContext,
+    ) -> Result<client::Response<CountsSearchSearchMethod>, ClientError> {
+        self.attempts.borrow_mut().push(
+            ctx.headers()
+                .iter()
+                .find(|(name, _)| name == "grpc-previous-rpc-attempts")
+                .map(|(_, val)| val.clone()),
+        );
+        let (delay, res) = self
+            .results
+            .borrow_mut()
+            .pop_front()
+            .expect("unexpected call");
+        if !delay.is_zero() {
+            sleep(delay).await;
+        }
+        res.map(|_| client::Response {
+            output: SearchResponse { results: vec![] },
+            headers: HeaderMap::default(),
+            trailers: HeaderMap::default(),
+            req_size: 0,
+            res_size: 0,
+        })
+    }
+}
+
+fn status_error(code: GrpcStatus, pushback: Option<&'static str>) -> ClientError {
+    let mut trailers = HeaderMap::default();
+    if let Some(val) = pushback {
+        trailers.insert(
+            HeaderName::from_static("grpc-retry-pushback-ms"),
+            HeaderValue::from_static(val),
+        );
+    }
+    ClientError::GrpcStatus(Status::new(code, "error"), trailers)
+}
+
+#[test]
+fn retry_policy() {
+    run(async {
+        let stub = StubTransport::default();
+        let policy = RetryPolicy::new()
+            .max_attempts(3)
+            .initial_backoff(Duration::from_millis(10))
+            .retryable_codes(&[GrpcStatus::Unavailable, GrpcStatus::Aborted]);
+        let counts_client = CountsSearchClient::new(Retry::new(stub.clone(), policy.clone()));
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+
+        // retryable errors
+        stub.push(Err(status_error(GrpcStatus::Unavailable, None)));
+        stub.push(Err(status_error(GrpcStatus::Aborted, None)));
+        stub.push(Ok(()));
+        assert!(counts_client.search(&request).await.is_ok());
+        assert_eq!(
+            stub.attempts(),
+            vec![
+                None,
+                Some(HeaderValue::from_static("1")),
+                Some(HeaderValue::from_static("2"))
+            ]
+        );
+
+        // max attempts
+        for _ in 0..3 {
+            stub.push(Err(status_error(GrpcStatus::Unavailable, None)));
+        }
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::Unavailable,
+                    ..
+                },
+                _
+            )
+        ));
+        assert_eq!(stub.attempts().len(), 3);
+
+        // non-retryable error
+        stub.push(Err(status_error(GrpcStatus::NotFound, None)));
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::NotFound,
+                    ..
+                },
+                _
+            )
+        ));
+        assert_eq!(stub.attempts().len(), 1);
+
+        // server pushback
+        stub.push(Err(status_error(GrpcStatus::Unavailable, Some("100"))));
+        stub.push(Ok(()));
+        let start = Instant::now();
+        assert!(counts_client.search(&request).await.is_ok());
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(stub.attempts().len(), 2);
+
+        stub.push(Err(status_error(GrpcStatus::Unavailable, Some("-1"))));
+        assert!(counts_client.search(&request).await.is_err());
+        assert_eq!(stub.attempts().len(), 1);
+
+        // deadline is exceeded before next attempt
+        stub.push(Err(status_error(GrpcStatus::Unavailable, Some("100"))));
+        let res = counts_client
+            .search(&request)
+            .timeout(Duration::from_millis(50))
+            .await;
+        assert!(res.is_err());
+        assert_eq!(stub.attempts().len(), 1);
+
+        // retry throttling
+        let counts_client =
+            CountsSearchClient::new(Retry::new(stub.clone(), policy.throttling(4, 1.0)));
+        for _ in 0..3 {
+            stub.push(Err(status_error(GrpcStatus::Unavailable, None)));
+        }
+        assert!(counts_client.search(&request).await.is_err());
+        assert_eq!(stub.attempts().len(), 2);
+        assert!(counts_client.search(&request).await.is_err());
+        assert_eq!(stub.attempts().len(), 1);
+
+        // successful calls restore tokens
+        for _ in 0..3 {
+            stub.push(Ok(()));
+            assert!(counts_client.search(&request).await.is_ok());
+        }
+        stub.push(Err(status_error(GrpcStatus::Unavailable, None)));
+        stub.push(Ok(()));
+        assert!(counts_client.search(&request).await.is_ok());
+        assert_eq!(stub.attempts().len(), 5);
+    });
+}
+
+#[test]
+fn hedging_policy() {
+    run(async {
+        let stub = StubTransport::default();
+        let policy = HedgingPolicy::new()
+            .max_attempts(3)
+            .delay(Duration::from_millis(50));
+        let counts_client = CountsSearchClient::new(
+            Hedge::new(stub.clone()).method(CountsSearchSearchMethod::PATH, policy),
+        );
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+
+        // fast response, no hedged calls
+        stub.push(Ok(()));
+        assert!(counts_client.search(&request).await.is_ok());
+        assert_eq!(stub.attempts(), vec![None]);
+
+        // slow first call, second call wins
+        stub.push_delayed(Duration::from_millis(500), Ok(()));
+        stub.push_delayed(Duration::from_millis(10), Ok(()));
+        let start = Instant::now();
+        assert!(counts_client.search(&request).await.is_ok());
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert_eq!(
+            stub.attempts(),
+            vec![None, Some(HeaderValue::from_static("1"))]
+        );
+
+        // non-fatal error starts next call immediately
+        stub.push(Err(status_error(GrpcStatus::Unavailable, None)));
+        stub.push(Err(status_error(GrpcStatus::Unavailable, None)));
+        stub.push(Ok(()));
+        let start = Instant::now();
+        assert!(counts_client.search(&request).await.is_ok());
+        assert!(start.elapsed() < Duration::from_millis(50));
+        assert_eq!(stub.attempts().len(), 3);
+
+        // all calls failed
+        for _ in 0..3 {
+            stub.push(Err(status_error(GrpcStatus::Unavailable, None)));
+        }
+        assert!(counts_client.search(&request).await.is_err());
+        assert_eq!(stub.attempts().len(), 3);
+
+        // fatal error cancels other calls
+        stub.push_delayed(Duration::from_millis(500), Ok(()));
+        stub.push_delayed(
+            Duration::from_millis(10),
+            Err(status_error(GrpcStatus::NotFound, None)),
+        );
+        let start = Instant::now();
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::NotFound,
+                    ..
+                },
+                _
+            )
+        ));
+        assert_eq!(stub.attempts().len(), 2);
+
+        // server pushback stops hedging
+        stub.push(Err(status_error(GrpcStatus::Unavailable, Some("-1"))));
+        assert!(counts_client.search(&request).await.is_err());
+        assert_eq!(stub.attempts().len(), 1);
+
+        // hedging is not enabled for method
+        let counts_client = CountsSearchClient::new(Hedge::new(stub.clone()));
+        stub.push(Err(status_error(GrpcStatus::Unavailable, None)));
+        assert!(counts_client.search(&request).await.is_err());
+        assert_eq!(stub.attempts().len(), 1);
+    });
+}
+
+#[test]
+fn hedging_connection_failure() {
+    run(async {
+        let stub = StubTransport::default();
+        let counts_client = CountsSearchClient::new(Hedge::new(stub.clone()).method(
+            CountsSearchSearchMethod::PATH,
+            HedgingPolicy::new().delay(Duration::from_millis(500)),
+        ));
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+
+        // connection failure is non-fatal as `Unavailable` status
+        stub.push(Err(ClientError::Client(h2::ClientError::Disconnected(
+            io::Error::new(io::ErrorKind::ConnectionRefused, "refused"),
+        ))));
+        stub.push(Ok(()));
+        let start = Instant::now();
+        assert!(counts_client.search(&request).await.is_ok());
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert_eq!(
+            stub.attempts(),
+            vec![None, Some(HeaderValue::from_static("1"))]
+        );
+    });
+}
+
+#[test]
+fn hedging_client() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(
+            Hedge::new(Client::new(
+                h2::Client::with_default(address.clone()).finish(),
+            ))
+            .method(
+                CountsSearchSearchMethod::PATH,
+                HedgingPolicy::new().delay(Duration::from_millis(10)),
+            ),
+        );
+
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+    });
+}
+
+#[test]
+fn retry_client() {
+    run(async {
+        let address = start_server();
+        let counts_client = CountsSearchClient::new(
+            Client::new(h2::Client::with_default(address.clone()).finish())
+                .retry(RetryPolicy::new().retryable_codes(&[GrpcStatus::NotFound])),
+        );
+
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+
+        // retried call reports status of the last attempt
+        let request = SearchRequest {
+            query: "missing".into(),
+        };
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::NotFound,
+                    ..
+                },
+                _
+            )
+        ));
+
+        // streaming calls are not retried
+        let mut stream = counts_client
+            .search_stream(&request)
+            .await
+            .expect("failed to search");
+        assert!(stream.recv().await.unwrap().is_ok());
+    });
+}
+
+#[test]
+fn retry_connection_failure() {
+    run(async {
+        let address = unused_address();
+        let client = Client::new(h2::Client::with_default(address.clone()).finish())
+            .connect_backoff(
+                ConnectBackoff::new()
+                    .initial_backoff(Duration::from_millis(1))
+                    .jitter(0.0),
+            );
+
+        // server comes up after first connection attempt fails
+        let watcher = client.clone();
+        ntex::rt::spawn(async move {
+            let mut state = ConnectivityState::Idle;
+            while state != ConnectivityState::TransientFailure {
+                state = watcher.watch_state(state).await;
+            }
+            start_server_at(&address);
+        });
+
+        let counts_client = CountsSearchClient::new(Retry::new(
+            client.clone(),
+            RetryPolicy::new()
+                .max_attempts(5)
+                .initial_backoff(Duration::from_millis(50)),
+        ));
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        let response = counts_client
+            .search(&request)
+            .await
+            .expect("failed to search");
+        assert_eq!(response.output.results[0].counts.len(), 4);
+        assert_eq!(client.state(), ConnectivityState::Ready);
+    });
+}
+
+#[test]
+fn balancer_client() {
+    run(async {
+        let address = start_server();
+        let balancer = Balancer::new(LoadBalancingPolicy::RoundRobin)
+            .endpoint(Client::new(
+                h2::Client::with_default(address.clone()).finish(),
+            ))
+            .endpoint(Client::new(
+                h2::Client::with_default(unused_address()).finish(),
+            ));
+        let counts_client = CountsSearchClient::new(balancer);
+
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+        assert!(counts_client.search(&request).await.is_ok());
+
+        // endpoint without server is taken out of rotation
+        let err = counts_client.search(&request).await.unwrap_err();
+        assert!(matches!(err, ClientError::Client(_)));
+        for _ in 0..3 {
+            assert!(counts_client.search(&request).await.is_ok());
+            let mut stream = counts_client
+                .search_stream(&request)
+                .await
+                .expect("failed to search");
+            assert!(stream.recv().await.unwrap().is_ok());
+        }
+
+        // balancing between stub endpoints
+        let stub1 = StubTransport::default();
+        let stub2 = StubTransport::default();
+        let counts_client = CountsSearchClient::new(
+            Balancer::new(LoadBalancingPolicy::RoundRobin)
+                .endpoint(stub1.clone())
+                .endpoint(stub2.clone()),
+        );
+        for _ in 0..2 {
+            stub1.push(Ok(()));
+            stub2.push(Ok(()));
+        }
+        for _ in 0..4 {
+            assert!(counts_client.search(&request).await.is_ok());
+        }
+        assert_eq!(stub1.attempts().len(), 2);
+        assert_eq!(stub2.attempts().len(), 2);
+
+        // unavailable endpoint
+        stub1.push(Err(status_error(GrpcStatus::Unavailable, None)));
+        assert!(counts_client.search(&request).await.is_err());
+        for _ in 0..3 {
+            stub2.push(Ok(()));
+            assert!(counts_client.search(&request).await.is_ok());
+        }
+        assert_eq!(stub1.attempts().len(), 1);
+        assert_eq!(stub2.attempts().len(), 3);
+
+        // pick first
+        let counts_client = CountsSearchClient::new(
+            Balancer::new(LoadBalancingPolicy::PickFirst)
+                .endpoint(stub1.clone())
+                .endpoint(stub2.clone()),
+        );
+        for _ in 0..3 {
+            stub1.push(Ok(()));
+            assert!(counts_client.search(&request).await.is_ok());
+        }
+        assert_eq!(stub1.attempts().len(), 3);
+        assert!(stub2.attempts().is_empty());
+    });
+}
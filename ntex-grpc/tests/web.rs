@@ -0,0 +1,361 @@
+#![cfg(feature = "web")]
+use std::{time::Duration, time::Instant};
+
+use ntex::http::header::{self, HeaderValue};
+use ntex::http::{HeaderMap, Method, StatusCode};
+use ntex::{util::Bytes, ServiceFactory};
+use ntex_bytes::BytesMut;
+use ntex_grpc::client::ClientError;
+use ntex_grpc::{server, GrpcStatus, Status};
+use ntex_h2::{client as h2, MessageKind};
+
+use common::counts::{CountsSearchClient, SearchRequest, SearchResponse};
+use common::mock::MockcountServer;
+use common::unused_address;
+use common::{run, serve};
+
+mod common;
+
+type MockWebServer = server::GrpcWebServer<MockcountServer>;
+
+/// Start grpc-web server with configured http service, returns server address
+fn start_web_server<T>(configure: fn(MockWebServer) -> T) -> String
+where
+    T: ServiceFactory<ntex::io::Io> + 'static,
+{
+    serve("127.0.0.1:0", move || {
+        configure(server::GrpcServer::new(MockcountServer).web())
+    })
+}
+
+/// Length-prefixed gRPC-Web request frame
+fn web_request(msg: &SearchRequest) -> Bytes {
+    use ntex_bytes::BufMut;
+    use ntex_grpc::Message;
+
+    let mut payload = BytesMut::new();
+    msg.write(&mut payload);
+    let mut buf = BytesMut::new();
+    buf.put_u8(0);
+    buf.put_u32(payload.len() as u32);
+    buf.extend_from_slice(&payload);
+    buf.freeze()
+}
+
+/// Split gRPC-Web response body to messages and trailers
+fn web_response(mut body: Bytes) -> (Vec<Bytes>, String) {
+    use ntex_bytes::Buf;
+
+    let mut messages = Vec::new();
+    while !body.is_empty() {
+        let flag = body.get_u8();
+        let len = body.get_u32() as usize;
+        let frame = body.split_to(len);
+        if flag == 0x80 {
+            assert!(body.is_empty(), "trailers must be the last frame");
+            return (messages, String::from_utf8(frame.to_vec()).unwrap());
+        }
+        messages.push(frame);
+    }
+    panic!("trailers frame is missing")
+}
+
+#[test]
+fn grpc_web() {
+    run(async {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        use ntex::http::{client::Client as HttpClient, HttpService};
+        use ntex_grpc::Message;
+
+        let address = start_web_server(|srv| HttpService::build().h1(srv));
+
+        let client = HttpClient::new();
+        let url = |method: &str| format!("http://{}/counts.CountsSearch/{}", address, method);
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+
+        // binary
+        let mut res = client
+            .post(url("Search"))
+            .header(header::CONTENT_TYPE, "application/grpc-web+proto")
+            .send_body(web_request(&request))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/grpc-web+proto"
+        );
+        let (messages, trailers) = web_response(res.body().await.unwrap());
+        assert_eq!(messages.len(), 1);
+        let msg = SearchResponse::read(&mut messages[0].clone()).unwrap();
+        assert_eq!(msg.results[0].counts.len(), 4);
+        assert!(trailers.starts_with("grpc-status:0\r\n"));
+
+        // base64 encoded
+        let mut res = client
+            .post(url("Search"))
+            .header(header::CONTENT_TYPE, "application/grpc-web-text")
+            .send_body(STANDARD.encode(web_request(&request)))
+            .await
+            .unwrap();
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/grpc-web-text+proto"
+        );
+        let body = STANDARD.decode(res.body().await.unwrap()).unwrap();
+        let (messages, trailers) = web_response(Bytes::from(body));
+        assert_eq!(messages.len(), 1);
+        assert!(trailers.starts_with("grpc-status:0\r\n"));
+
+        // server streaming
+        let mut res = client
+            .post(url("SearchStream"))
+            .header(header::CONTENT_TYPE, "application/grpc-web")
+            .send_body(web_request(&request))
+            .await
+            .unwrap();
+        let (messages, trailers) = web_response(res.body().await.unwrap());
+        assert_eq!(messages.len(), 3);
+        assert!(trailers.starts_with("grpc-status:0\r\n"));
+
+        // error before any message is sent as trailers-only response
+        let mut res = client
+            .post(url("Search"))
+            .header(header::CONTENT_TYPE, "application/grpc-web")
+            .send_body(web_request(&SearchRequest {
+                query: "missing".into(),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("grpc-status").unwrap(), "5");
+        assert!(res.body().await.unwrap().is_empty());
+
+        // error after response messages is sent in trailers frame
+        let mut res = client
+            .post(url("SearchStream"))
+            .header(header::CONTENT_TYPE, "application/grpc-web")
+            .send_body(web_request(&SearchRequest { query: "".into() }))
+            .await
+            .unwrap();
+        let (messages, trailers) = web_response(res.body().await.unwrap());
+        assert!(messages.is_empty());
+        assert!(trailers.contains("grpc-status:3\r\n"));
+
+        // malformed timeout
+        let res = client
+            .post(url("Search"))
+            .header(header::CONTENT_TYPE, "application/grpc-web")
+            .header("grpc-timeout", "100")
+            .send_body(web_request(&request))
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get("grpc-status").unwrap(), "3");
+
+        // not a grpc-web request
+        let res = client
+            .post(url("Search"))
+            .header(header::CONTENT_TYPE, "application/grpc")
+            .send_body(web_request(&request))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    });
+}
+
+#[test]
+fn grpc_web_h2() {
+    run(async {
+        use ntex::http::HttpService;
+
+        let address = start_web_server(|srv| HttpService::build().h2(srv));
+
+        let client = h2::Client::with_default(address).finish();
+        let mut hdrs = HeaderMap::new();
+        hdrs.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc-web"),
+        );
+        let (snd, rcv) = client
+            .send(
+                Method::POST,
+                "/counts.CountsSearch/Search".into(),
+                hdrs,
+                false,
+            )
+            .await
+            .unwrap();
+        snd.send_payload(
+            web_request(&SearchRequest {
+                query: "test".into(),
+            }),
+            true,
+        )
+        .await
+        .unwrap();
+
+        let mut body = BytesMut::new();
+        loop {
+            match rcv.recv().await.unwrap().kind {
+                MessageKind::Headers { pseudo, .. } => {
+                    assert_eq!(pseudo.status, Some(StatusCode::OK))
+                }
+                MessageKind::Data(data, cap) => {
+                    body.extend_from_slice(&data);
+                    cap.consume(data.len() as u32);
+                }
+                MessageKind::Eof(ntex_h2::StreamEof::Data(data)) => {
+                    body.extend_from_slice(&data);
+                    break;
+                }
+                MessageKind::Eof(_) => break,
+                kind => panic!("unexpected message: {:?}", kind),
+            }
+        }
+        let (messages, trailers) = web_response(body.freeze());
+        assert_eq!(messages.len(), 1);
+        assert!(trailers.starts_with("grpc-status:0\r\n"));
+    });
+}
+
+#[test]
+fn grpc_web_client() {
+    run(async {
+        use ntex::http::{client::Client as HttpClient, HttpService};
+        use ntex_grpc::client::WebClient;
+
+        let address = start_web_server(|srv| HttpService::build().h1(srv));
+
+        assert!(matches!(
+            WebClient::new(HttpClient::new(), &address),
+            Err(ClientError::Target(_))
+        ));
+        let client = WebClient::new(HttpClient::new(), &format!("http://{}/", address)).unwrap();
+
+        for client in [client.clone(), client.text()] {
+            let counts_client = CountsSearchClient::new(client);
+
+            let request = SearchRequest {
+                query: "test".into(),
+            };
+            let response = counts_client
+                .search(&request)
+                .header("x-request-value", "1")
+                .header("x-request-value", "2")
+                .await
+                .unwrap();
+            assert_eq!(response.output.results[0].counts.len(), 4);
+            assert_eq!(response.trailers().get("grpc-status").unwrap(), "0");
+            let values: Vec<_> = response.trailers().get_all("x-request-value").collect();
+            assert_eq!(values, vec!["1", "2"]);
+
+            // status from trailers frame
+            let request = SearchRequest {
+                query: "unicode".into(),
+            };
+            match counts_client.search(&request).await.unwrap_err() {
+                ClientError::GrpcStatus(st, _) => {
+                    assert_eq!(st.code, GrpcStatus::FailedPrecondition);
+                    assert_eq!(st.message, "Запрос не выполнен: 100%\nretry");
+                }
+                err => panic!("unexpected error: {:?}", err),
+            }
+
+            let request = SearchRequest {
+                query: "slow".into(),
+            };
+            let start = Instant::now();
+            let err = counts_client
+                .search(&request)
+                .timeout(Duration::from_millis(100))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                ClientError::GrpcStatus(
+                    Status {
+                        code: GrpcStatus::DeadlineExceeded,
+                        ..
+                    },
+                    _
+                )
+            ));
+            assert!(start.elapsed() < Duration::from_millis(1000));
+        }
+
+        // connection failures are reported with `Unavailable` status
+        let client =
+            WebClient::new(HttpClient::new(), &format!("http://{}", unused_address())).unwrap();
+        let err = CountsSearchClient::new(client)
+            .search(&SearchRequest {
+                query: "test".into(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::GrpcStatus(
+                Status {
+                    code: GrpcStatus::Unavailable,
+                    ..
+                },
+                _
+            )
+        ));
+    });
+}
+
+#[test]
+fn grpc_web_client_frames() {
+    run(async {
+        use ntex::http::{client::Client as HttpClient, HttpService, Request, Response};
+        use ntex::service::fn_service;
+        use ntex_grpc::client::WebClient;
+
+        // responds with raw frames selected by request path
+        let address = serve("127.0.0.1:0", || {
+            HttpService::build().h1(fn_service(|req: Request| async move {
+                let body: &'static [u8] = if req.path().starts_with("/extra/") {
+                    b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x80\x00\x00\x00\x0fgrpc-status:0\r\n"
+                } else {
+                    b"\x02\x00\x00\x00\x00\x80\x00\x00\x00\x0fgrpc-status:0\r\n"
+                };
+                Ok::<_, std::io::Error>(
+                    Response::Ok()
+                        .content_type("application/grpc-web+proto")
+                        .body(Bytes::from_static(body)),
+                )
+            }))
+        });
+        let request = SearchRequest {
+            query: "test".into(),
+        };
+
+        // unary response with more than one message
+        let client = WebClient::new(HttpClient::new(), &format!("http://{}/extra", address));
+        let err = CountsSearchClient::new(client.unwrap())
+            .search(&request)
+            .await
+            .unwrap_err();
+        match err {
+            ClientError::GrpcStatus(st, _) => assert_eq!(st.code, GrpcStatus::Internal),
+            err => panic!("unexpected error: {:?}", err),
+        }
+
+        // frame with unknown flags
+        let client = WebClient::new(HttpClient::new(), &format!("http://{}/flags", address));
+        let err = CountsSearchClient::new(client.unwrap())
+            .search(&request)
+            .await
+            .unwrap_err();
+        match err {
+            ClientError::GrpcStatus(st, _) => {
+                assert_eq!(st.code, GrpcStatus::Internal);
+                assert_eq!(st.message, "Unsupported frame flags: 0x02");
+            }
+            err => panic!("unexpected error: {:?}", err),
+        }
+    });
+}
@@ -62,14 +62,7 @@ impl Comments {
         }
 
         // Append leading comments.
-        for line in &self.leading {
-            for _ in 0..indent_level {
-                buf.push_str("    ");
-            }
-            buf.push_str("///");
-            buf.push_str(&Self::sanitize_line(line));
-            buf.push('\n');
-        }
+        Self::append_doc_lines(&self.leading, indent_level, buf);
 
         // Append an empty comment line if there are leading and trailing comments.
         if !self.leading.is_empty() && !self.trailing.is_empty() {
@@ -80,13 +73,71 @@ impl Comments {
         }
 
         // Append trailing comments.
-        for line in &self.trailing {
+        Self::append_doc_lines(&self.trailing, indent_level, buf);
+    }
+
+    /// Appends doc comment lines to a buffer with indentation.
+    ///
+    /// Indented blocks are wrapped into `text` code fences, otherwise rustdoc
+    /// treats them as rust code and tries to compile them as doc tests.
+    fn append_doc_lines(lines: &[String], indent_level: u8, buf: &mut String) {
+        fn indent(line: &str) -> usize {
+            line.len() - line.trim_start().len()
+        }
+
+        let push = |buf: &mut String, line: &str| {
             for _ in 0..indent_level {
                 buf.push_str("    ");
             }
             buf.push_str("///");
-            buf.push_str(&Self::sanitize_line(line));
+            buf.push_str(line);
             buf.push('\n');
+        };
+
+        let base = lines
+            .iter()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| indent(l))
+            .min()
+            .unwrap_or(0);
+
+        let mut in_code = false;
+        let mut prev_blank = true;
+        let mut blanks = 0;
+        for line in lines {
+            let blank = line.trim().is_empty();
+            let code = !blank && indent(line) >= base + 4;
+
+            if in_code {
+                if blank {
+                    blanks += 1;
+                    continue;
+                }
+                if !code {
+                    push(buf, " ```");
+                    in_code = false;
+                }
+                for _ in 0..blanks {
+                    push(buf, "");
+                }
+                blanks = 0;
+            } else if code && prev_blank {
+                push(buf, " ```text");
+                in_code = true;
+            }
+
+            if in_code {
+                push(buf, &format!(" {}", &line[base..]));
+            } else {
+                push(buf, &Self::sanitize_line(line));
+            }
+            prev_blank = blank;
+        }
+        if in_code {
+            push(buf, " ```");
+            for _ in 0..blanks {
+                push(buf, "");
+            }
         }
     }
 
@@ -121,6 +172,9 @@ pub struct Service {
     pub methods: Vec<Method>,
     /// The service options.
     pub options: prost_types::ServiceOptions,
+    /// Serialized `FileDescriptorProto` of the service file, followed by
+    /// descriptors of its transitive dependencies.
+    pub file_descriptors: Vec<Vec<u8>>,
 }
 
 /// A service method descriptor.
@@ -285,4 +339,29 @@ mod tests {
             assert_eq!(expected, comments.leading, "failed {}", t.name);
         }
     }
+
+    #[test]
+    fn test_comment_append_with_indent_fences_code_blocks() {
+        let input = Comments {
+            leading_detached: vec![],
+            leading: vec![
+                " Example:".to_string(),
+                "".to_string(),
+                "     Timestamp start = ...;".to_string(),
+                "".to_string(),
+                "       start.seconds += 1;".to_string(),
+                "".to_string(),
+                " Done.".to_string(),
+            ],
+            trailing: vec![],
+        };
+
+        let mut actual = "".to_string();
+        input.append_with_indent(0, &mut actual);
+
+        assert_eq!(
+            actual,
+            "///  Example:\n///\n/// ```text\n///     Timestamp start = ...;\n///\n///       start.seconds += 1;\n/// ```\n///\n///  Done.\n"
+        );
+    }
 }
@@ -29,8 +29,10 @@ pub struct CodeGenerator<'a> {
     extern_paths: &'a ExternPaths,
     depth: u8,
     path: Vec<i32>,
+    mod_path: Vec<String>,
     buf: &'a mut String,
     priv_buf: String,
+    file_descriptors: Vec<Vec<u8>>,
 }
 
 fn push_indent(buf: &mut String, depth: u8) {
@@ -43,6 +45,7 @@ impl<'a> CodeGenerator<'a> {
         config: &mut Config,
         extern_paths: &ExternPaths,
         file: FileDescriptorProto,
+        file_descriptors: Vec<Vec<u8>>,
         buf: &mut String,
     ) {
         let mut source_info = file
@@ -70,8 +73,10 @@ impl<'a> CodeGenerator<'a> {
             extern_paths,
             depth: 0,
             path: Vec::new(),
+            mod_path: Vec::new(),
             buf,
             priv_buf: String::new(),
+            file_descriptors,
         };
 
         debug!(
@@ -190,8 +195,9 @@ impl<'a> CodeGenerator<'a> {
         self.buf.push_str(&to_upper_camel(&message_name));
         self.buf.push_str(" {\n");
 
+        let priv_name = self.priv_type_path(&to_upper_camel(&message_name));
         self.priv_buf.push_str("impl ::ntex_grpc::Message for ");
-        self.priv_buf.push_str(&to_upper_camel(&message_name));
+        self.priv_buf.push_str(&priv_name);
         self.priv_buf.push_str(" {\n");
 
         let mut has_fields = false;
@@ -335,8 +341,7 @@ impl<'a> CodeGenerator<'a> {
                  }}
              }}\n\n
         ",
-            to_upper_camel(&message_name),
-            default
+            priv_name, default
         ));
         // ==========================================
 
@@ -913,6 +918,7 @@ impl<'a> CodeGenerator<'a> {
             comments,
             methods,
             options: service.options.unwrap_or_default(),
+            file_descriptors: self.file_descriptors.clone(),
         };
 
         if let Some(service_generator) = self.config.service_generator.as_mut() {
@@ -937,15 +943,26 @@ impl<'a> CodeGenerator<'a> {
 
         self.package.push('.');
         self.package.push_str(module);
+        self.mod_path.push(to_snake(module));
 
         self.depth += 1;
     }
 
+    /// Type path relative to the `_priv_impl` module
+    fn priv_type_path(&self, name: &str) -> String {
+        self.mod_path
+            .iter()
+            .map(|m| m.as_str())
+            .chain(Some(name))
+            .join("::")
+    }
+
     fn pop_mod(&mut self) {
         self.depth -= 1;
 
         let idx = self.package.rfind('.').unwrap();
         self.package.truncate(idx);
+        self.mod_path.pop();
 
         self.push_indent();
         self.buf.push_str("}\n\n");
@@ -983,7 +1000,7 @@ impl<'a> CodeGenerator<'a> {
         // If no package is specified the start of the package name will be '.'
         // and split will return an empty string ("") which breaks resolution
         // The fix to this is to ignore the first item if it is empty.
-        if local_path.peek().map_or(false, |s| s.is_empty()) {
+        if local_path.peek().is_some_and(|s| s.is_empty()) {
             local_path.next();
         }
 
@@ -82,6 +82,10 @@ impl ExternPaths {
                 ".google.protobuf.UInt64Value".to_string(),
                 "::ntex_grpc::google_types::UInt64Value".to_string(),
             )?;
+            extern_paths.insert(
+                ".google.rpc".to_string(),
+                "::ntex_grpc::google_rpc".to_string(),
+            )?;
         }
 
         Ok(extern_paths)
@@ -136,7 +140,7 @@ impl ExternPaths {
                                 to_snake(segment)
                             }
                         })
-                        .chain(ident_type.into_iter())
+                        .chain(ident_type)
                         .join("::"),
                 );
             }
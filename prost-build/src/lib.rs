@@ -53,7 +53,7 @@
 //! ```rust,no_run
 //! use std::io::Result;
 //! fn main() -> Result<()> {
-//!     prost_build::compile_protos(&["src/items.proto"], &["src/"])?;
+//!     ntex_prost_build::compile_protos(&["src/items.proto"], &["src/"])?;
 //!     Ok(())
 //! }
 //! ```
@@ -242,7 +242,7 @@ impl Config {
     /// # Examples
     ///
     /// ```rust
-    /// # let mut config = prost_build::Config::new();
+    /// # let mut config = ntex_prost_build::Config::new();
     /// // Match a specific field in a message type.
     /// config.map_field_type(&[".my_messages.MyMessageType.my_bytes_field"], "MyCustomType");
     ///
@@ -257,7 +257,7 @@ impl Config {
     /// config.map_field_type(&["MyMessageType.my_bytes_field"], "Bytes");
     ///
     /// // Match all fields named 'my_bytes_field', and all fields in the 'foo.bar' package.
-    /// config.map_field_type(&["my_field", "::foo::bar::CustomType"]);
+    /// config.map_field_type(&["my_bytes_field", ".foo.bar"], "Bytes");
     /// ```
     pub fn map_field_type<I, S>(&mut self, paths: I, tp: &str) -> &mut Self
     where
@@ -289,7 +289,7 @@ impl Config {
     /// # Examples
     ///
     /// ```rust
-    /// # let mut config = prost_build::Config::new();
+    /// # let mut config = ntex_prost_build::Config::new();
     /// // Prost renames fields named `in` to `in_`. But if serialized through serde,
     /// // they should as `in`.
     /// config.field_attribute("in", "#[serde(rename = \"in\")]");
@@ -324,7 +324,7 @@ impl Config {
     /// # Examples
     ///
     /// ```rust
-    /// # let mut config = prost_build::Config::new();
+    /// # let mut config = ntex_prost_build::Config::new();
     /// // Nothing around uses floats, so we can derive real `Eq` in addition to `PartialEq`.
     /// config.type_attribute(".", "#[derive(Eq)]");
     /// // Some messages want to be serializable with serde as well.
@@ -379,7 +379,7 @@ impl Config {
     /// are possible, then omit comments on generated code during doctest builds:
     ///
     /// ```rust,ignore
-    /// let mut config = prost_build::Config::new();
+    /// let mut config = ntex_prost_build::Config::new();
     /// config.disable_comments(".");
     /// config.compile_protos(&["src/frontend.proto", "src/backend.proto"], &["src"])?;
     /// ```
@@ -491,7 +491,7 @@ impl Config {
     /// a type, and the Rust path should correspondingly refer to a Rust module or type.
     ///
     /// ```rust
-    /// # let mut config = prost_build::Config::new();
+    /// # let mut config = ntex_prost_build::Config::new();
     /// // Declare the `uuid` Protobuf package and all nested packages and types as externally
     /// // provided by the `uuid` crate.
     /// config.extern_path(".uuid", "::uuid");
@@ -527,7 +527,7 @@ impl Config {
     /// ```rust
     /// # use std::env;
     /// # use std::path::PathBuf;
-    /// # let mut config = prost_build::Config::new();
+    /// # let mut config = ntex_prost_build::Config::new();
     /// config.file_descriptor_set_path(
     ///     PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR environment variable not set"))
     ///         .join("file_descriptor_set.bin"));
@@ -555,7 +555,7 @@ impl Config {
     /// In `build.rs`:
     ///
     /// ```rust
-    /// # let mut config = prost_build::Config::new();
+    /// # let mut config = ntex_prost_build::Config::new();
     /// config.file_descriptor_set_path("path/from/build/system")
     ///     .skip_protoc_run()
     ///     .compile_protos(&["src/items.proto"], &["src/"]);
@@ -605,7 +605,7 @@ impl Config {
     /// ```rust,no_run
     /// # use std::io::Result;
     /// fn main() -> Result<()> {
-    ///   let mut prost_build = prost_build::Config::new();
+    ///   let mut prost_build = ntex_prost_build::Config::new();
     ///   // Enable a protoc experimental feature.
     ///   prost_build.protoc_arg("--experimental_allow_proto3_optional");
     ///   prost_build.compile_protos(&["src/frontend.proto", "src/backend.proto"], &["src"])?;
@@ -667,8 +667,8 @@ impl Config {
     /// ```rust,no_run
     /// # use std::io::Result;
     /// fn main() -> Result<()> {
-    ///   let mut prost_build = prost_build::Config::new();
-    ///   prost_build.btree_map(&["."]);
+    ///   let mut prost_build = ntex_prost_build::Config::new();
+    ///   prost_build.map_field_type(&["."], "Bytes");
     ///   prost_build.compile_protos(&["src/frontend.proto", "src/backend.proto"], &["src"])?;
     ///   Ok(())
     /// }
@@ -681,9 +681,7 @@ impl Config {
         let mut target_is_env = false;
         let target: PathBuf = self.out_dir.clone().map(Ok).unwrap_or_else(|| {
             env::var_os("OUT_DIR")
-                .ok_or_else(|| {
-                    Error::new(ErrorKind::Other, "OUT_DIR environment variable is not set")
-                })
+                .ok_or_else(|| Error::other("OUT_DIR environment variable is not set"))
                 .map(|val| {
                     target_is_env = true;
                     Into::into(val)
@@ -701,8 +699,7 @@ impl Config {
             path.clone()
         } else {
             if self.skip_protoc_run {
-                return Err(Error::new(
-                    ErrorKind::Other,
+                return Err(Error::other(
                     "file_descriptor_set_path is required with skip_protoc_run",
                 ));
             }
@@ -741,10 +738,10 @@ impl Config {
             })?;
 
             if !output.status.success() {
-                return Err(Error::new(
-                    ErrorKind::Other,
-                    format!("protoc failed: {}", String::from_utf8_lossy(&output.stderr)),
-                ));
+                return Err(Error::other(format!(
+                    "protoc failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                )));
             }
         }
 
@@ -883,6 +880,9 @@ impl Config {
     /// This is generally used when control over the output should not be managed by Prost,
     /// such as in a flow for a `protoc` code generating plugin. When compiling as part of a
     /// `build.rs` file, instead use [`compile_protos()`].
+    ///
+    /// Descriptors of files with services are embedded to generated code with descriptors
+    /// of their transitive dependencies, requests must contain all dependencies of such files.
     pub fn generate(
         &mut self,
         requests: Vec<(Module, FileDescriptorProto)>,
@@ -893,7 +893,26 @@ impl Config {
         let extern_paths = ExternPaths::new(&self.extern_paths, self.prost_types)
             .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
 
+        // serialized descriptors are embedded to generated services for server reflection
+        let descriptors = requests
+            .iter()
+            .map(|(_, file)| {
+                let mut file = file.clone();
+                file.source_code_info = None;
+                (
+                    file.name().to_string(),
+                    (file.encode_to_vec(), file.dependency.clone()),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
         for request in requests {
+            let file_descriptors = if request.1.service.is_empty() {
+                Vec::new()
+            } else {
+                file_descriptors(&descriptors, request.1.name())?
+            };
+
             // Only record packages that have services
             if !request.1.service.is_empty() {
                 packages.insert(request.0.clone(), request.1.package().to_string());
@@ -902,9 +921,9 @@ impl Config {
             let buf = modules.entry(request.0).or_insert_with(String::new);
             buf.insert_str(
                 0,
-                "#![allow(dead_code, unused_mut, unused_variables, clippy::identity_op, clippy::derivable_impls, clippy::unit_arg, clippy::derive_partial_eq_without_eq, clippy::manual_range_patterns)]\n/// DO NOT MODIFY. Auto-generated file\n\n",
+                "#![allow(dead_code, unused_mut, unused_variables, clippy::identity_op, clippy::derivable_impls, clippy::unit_arg, clippy::derive_partial_eq_without_eq, clippy::manual_range_patterns)]\n// DO NOT MODIFY. Auto-generated file\n\n",
             );
-            CodeGenerator::generate(self, &extern_paths, request.1, buf);
+            CodeGenerator::generate(self, &extern_paths, request.1, file_descriptors, buf);
         }
 
         if let Some(ref mut service_generator) = self.service_generator {
@@ -918,6 +937,38 @@ impl Config {
     }
 }
 
+/// Serialized descriptor of the file followed by descriptors of its transitive dependencies
+///
+/// Requests must contain all dependencies of the file, `protoc` adds them
+/// with `--include_imports` argument.
+fn file_descriptors(
+    descriptors: &HashMap<String, (Vec<u8>, Vec<String>)>,
+    name: &str,
+) -> Result<Vec<Vec<u8>>> {
+    let mut names = vec![name];
+    let mut result = Vec::new();
+    let mut idx = 0;
+    while idx < names.len() {
+        let (buf, deps) = descriptors.get(names[idx]).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "descriptor of {:?}, dependency of {:?}, is not found",
+                    names[idx], name
+                ),
+            )
+        })?;
+        result.push(buf.clone());
+        for dep in deps {
+            if !names.contains(&dep.as_str()) {
+                names.push(dep);
+            }
+        }
+        idx += 1;
+    }
+    Ok(result)
+}
+
 impl default::Default for Config {
     fn default() -> Config {
         Config {
@@ -1078,7 +1129,7 @@ impl fmt::Display for Module {
 /// ```rust,no_run
 /// # use std::io::Result;
 /// fn main() -> Result<()> {
-///   prost_build::compile_protos(&["src/frontend.proto", "src/backend.proto"], &["src"])?;
+///   ntex_prost_build::compile_protos(&["src/frontend.proto", "src/backend.proto"], &["src"])?;
 ///   Ok(())
 /// }
 /// ```
@@ -1106,3 +1157,50 @@ pub fn protoc_include() -> PathBuf {
         None => PathBuf::from(env!("PROTOC_INCLUDE")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_descriptors() {
+        let file = |name: &str, deps: &[&str]| {
+            let file = FileDescriptorProto {
+                name: Some(name.to_string()),
+                dependency: deps.iter().map(|dep| dep.to_string()).collect(),
+                ..Default::default()
+            };
+            (name.to_string(), (file.encode_to_vec(), file.dependency))
+        };
+        let mut descriptors = HashMap::new();
+        descriptors.extend([
+            file("service.proto", &["types.proto"]),
+            file("types.proto", &["google/protobuf/empty.proto"]),
+            file("google/protobuf/empty.proto", &[]),
+            file("other.proto", &[]),
+        ]);
+
+        let names = file_descriptors(&descriptors, "service.proto")
+            .unwrap()
+            .into_iter()
+            .map(|buf| {
+                FileDescriptorProto::decode(&*buf)
+                    .unwrap()
+                    .name()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "service.proto",
+                "types.proto",
+                "google/protobuf/empty.proto"
+            ]
+        );
+
+        descriptors.remove("google/protobuf/empty.proto");
+        let err = file_descriptors(&descriptors, "service.proto").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}